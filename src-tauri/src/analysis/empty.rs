@@ -0,0 +1,186 @@
+//! Detection of zero-byte files and recursively empty folders
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::models::{decode_path, encode_path, ScanResult};
+
+/// File names that do not count as content when deciding whether a folder is empty
+const JUNK_FILE_NAMES: &[&str] = &[".ds_store", "thumbs.db", "desktop.ini", ".localized"];
+
+/// Empty files and folders found in a scan
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmptyItemsReport {
+    /// Files with a size of zero bytes
+    pub empty_files: Vec<String>,
+
+    /// Folders containing nothing but empty folders, zero-byte files and junk files
+    pub empty_folders: Vec<String>,
+
+    /// All of the above, deepest first, ready to pass to `delete_files`
+    pub delete_order: Vec<String>,
+}
+
+/// Whether a file is system junk such as `.DS_Store` or `Thumbs.db`
+pub fn is_junk_file(name: &str) -> bool {
+    JUNK_FILE_NAMES.contains(&name.to_lowercase().as_str())
+}
+
+/// Find zero-byte files and recursively empty folders in a scan result
+///
+/// Junk files are ignored, so a folder holding only a `.DS_Store` counts as
+/// empty (and deleting it removes the junk as well). Zero-byte files are
+/// reported themselves, so they do not keep their folders from being empty;
+/// `delete_order` removes them before their folders.
+/// Folders that could not be read completely are never reported, and archive
+/// members are ignored. The scan must not be limited in depth.
+pub fn detect_empty_items(scan: &ScanResult) -> EmptyItemsReport {
+    let entries: Vec<_> = scan
+        .entries
+        .iter()
+        .filter(|entry| !entry.is_virtual)
+        .collect();
+    let parents: HashMap<&str, &str> = entries
+        .iter()
+        .map(|entry| (entry.path.as_str(), entry.parent_path.as_str()))
+        .collect();
+
+    // Mark every folder above real content (or above an unreadable path) as used
+    let mut used: HashSet<&str> = HashSet::new();
+    let content = entries
+        .iter()
+        .filter(|entry| !entry.is_directory && entry.size > 0 && !is_junk_file(&entry.name))
+        .map(|entry| entry.parent_path.as_str());
+    let failed_parents: Vec<String> = scan
+        .failed_entries
+        .iter()
+        .filter_map(|failed| decode_path(&failed.path).parent().map(encode_path))
+        .collect();
+    let unreadable = scan
+        .failed_entries
+        .iter()
+        .map(|failed| failed.path.as_str())
+        .chain(failed_parents.iter().map(String::as_str));
+    for start in content.chain(unreadable) {
+        let mut folder = Some(start);
+        while let Some(path) = folder {
+            if !used.insert(path) {
+                break;
+            }
+            folder = parents.get(path).copied();
+        }
+    }
+
+    let mut items: Vec<_> = entries
+        .iter()
+        .filter(|entry| {
+            if entry.is_directory {
+                !used.contains(entry.path.as_str())
+            } else {
+                entry.size == 0 && !is_junk_file(&entry.name)
+            }
+        })
+        .collect();
+    items.sort_by(|a, b| b.depth.cmp(&a.depth).then_with(|| a.path.cmp(&b.path)));
+
+    let mut report = EmptyItemsReport::default();
+    for entry in items {
+        if entry.is_directory {
+            report.empty_folders.push(entry.path.clone());
+        } else {
+            report.empty_files.push(entry.path.clone());
+        }
+        report.delete_order.push(entry.path.clone());
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{scan_directory, ScanOptions};
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_is_junk_file() {
+        assert!(is_junk_file(".DS_Store"));
+        assert!(is_junk_file("thumbs.db"));
+        assert!(!is_junk_file("notes.txt"));
+    }
+
+    #[test]
+    fn test_detect_empty_items() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("empty/nested/deeper")).unwrap();
+        fs::write(root.join("empty/nested/.DS_Store"), "junk").unwrap();
+        fs::create_dir_all(root.join("used/sub")).unwrap();
+        fs::write(root.join("used/sub/data.txt"), "data").unwrap();
+        fs::create_dir(root.join("used/blank")).unwrap();
+        fs::write(root.join("zero.txt"), "").unwrap();
+
+        let scan = scan_directory(root.to_str().unwrap(), &ScanOptions::new()).unwrap();
+        let report = detect_empty_items(&scan);
+
+        let relative = |paths: &[String]| -> Vec<String> {
+            paths
+                .iter()
+                .map(|path| path[scan.root_path.len() + 1..].replace('\\', "/"))
+                .collect()
+        };
+        assert_eq!(relative(&report.empty_files), ["zero.txt"]);
+        assert_eq!(
+            relative(&report.empty_folders),
+            ["empty/nested/deeper", "empty/nested", "used/blank", "empty"]
+        );
+        assert_eq!(
+            relative(&report.delete_order),
+            [
+                "empty/nested/deeper",
+                "empty/nested",
+                "used/blank",
+                "empty",
+                "zero.txt"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_folder_with_zero_byte_file_is_empty() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("logs")).unwrap();
+        fs::write(dir.path().join("logs/empty.log"), "").unwrap();
+
+        let scan = scan_directory(dir.path().to_str().unwrap(), &ScanOptions::new()).unwrap();
+        let report = detect_empty_items(&scan);
+
+        assert_eq!(report.empty_files.len(), 1);
+        assert_eq!(report.empty_folders.len(), 1);
+        assert!(report.empty_folders[0].ends_with("logs"));
+        assert_eq!(
+            report.delete_order,
+            [
+                report.empty_files[0].clone(),
+                report.empty_folders[0].clone()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unreadable_folder_is_not_empty() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("locked")).unwrap();
+
+        let mut scan = scan_directory(dir.path().to_str().unwrap(), &ScanOptions::new()).unwrap();
+        let locked = scan.entries[0].path.clone();
+        scan.failed_entries.push(crate::models::FailedEntry::new(
+            format!("{}/secret", locked),
+            crate::models::FailureReason::PermissionDenied,
+            "Permission denied".to_string(),
+        ));
+
+        assert!(detect_empty_items(&scan).empty_folders.is_empty());
+    }
+}
@@ -0,0 +1,82 @@
+//! Analyses over scan results
+
+mod empty;
+mod portability;
+mod secrets;
+mod security;
+mod stale;
+mod symlinks;
+mod top_n;
+mod treemap;
+
+pub use empty::{detect_empty_items, is_junk_file, EmptyItemsReport};
+pub use portability::{
+    check_portability, safe_name, PortabilityGroup, PortabilityIssue, PortabilityOptions,
+    PortabilityProblem, PortabilityReport,
+};
+pub use secrets::{
+    compile_secret_rules, default_secret_rules, load_secret_rules, scan_secrets, CompiledRule,
+    SecretFinding, SecretRule, SecretRuleFile, SecretScanOptions, SecretScanReport,
+};
+pub use security::{security_audit, AuditFinding, AuditRule, SecurityAuditReport, Severity};
+pub use stale::{find_stale_files, StaleFilesReport, StaleOptions};
+pub use symlinks::{symlink_report, SymlinkIssue, SymlinkReport};
+pub use top_n::{top_n, EntryFilter, FolderSize, TopNReport};
+pub use treemap::{
+    size_hierarchy, treemap_layout, HierarchyOptions, SizeNode, SizeTree, TreemapLayout,
+    TreemapOptions, TreemapRect,
+};
+
+/// Scan results built by hand for the analysis tests
+#[cfg(test)]
+mod test_helpers {
+    use crate::models::{FileCategory, FileEntry, ScanResult, ScanStats};
+    use crate::scanner::classify_extension;
+
+    /// Entry for an absolute path, categorized by its extension like the scanner does
+    pub fn entry(path: &str, size: u64, is_directory: bool) -> FileEntry {
+        let (parent, name) = path.rsplit_once(['/', '\\']).unwrap();
+        let extension = if is_directory {
+            String::new()
+        } else {
+            name.rsplit_once('.')
+                .map_or(String::new(), |(_, ext)| ext.to_lowercase())
+        };
+        let category = if is_directory {
+            FileCategory::Folder
+        } else {
+            classify_extension(&extension)
+        };
+        FileEntry::new(
+            path.to_string(),
+            name.to_string(),
+            is_directory,
+            size,
+            "2025-01-01T00:00:00Z".to_string(),
+            category,
+            extension,
+            0,
+            parent.to_string(),
+        )
+    }
+
+    /// Scan of `root` holding the entries, with their depths below `root`
+    pub fn scan_of(root: &str, entries: Vec<FileEntry>) -> ScanResult {
+        let entries = entries
+            .into_iter()
+            .map(|mut entry| {
+                let relative = entry.path.strip_prefix(root).unwrap_or(&entry.path);
+                entry.depth = relative.matches(['/', '\\']).count() as u32;
+                entry
+            })
+            .collect();
+        ScanResult::new(
+            root.to_string(),
+            entries,
+            ScanStats::new(),
+            Vec::new(),
+            String::new(),
+            0,
+        )
+    }
+}
@@ -0,0 +1,557 @@
+//! Names that break when a folder is copied to another operating system
+//!
+//! Windows is the strictest target: it reserves device names, forbids a set
+//! of characters and trailing dots or spaces, limits paths to 259 characters
+//! and ignores case. macOS stores names decomposed (NFD), so the same name
+//! typed on Linux (usually NFC) may not match after a round trip.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+use crate::models::{decode_path, FileEntry, ScanResult};
+
+/// Device names Windows reserves, with or without an extension (the
+/// superscript digits count as port numbers too)
+const RESERVED_NAMES: &[&str] = &[
+    "CON",
+    "PRN",
+    "AUX",
+    "NUL",
+    "CONIN$",
+    "CONOUT$",
+    "COM0",
+    "COM1",
+    "COM2",
+    "COM3",
+    "COM4",
+    "COM5",
+    "COM6",
+    "COM7",
+    "COM8",
+    "COM9",
+    "COM\u{b9}",
+    "COM\u{b2}",
+    "COM\u{b3}",
+    "LPT0",
+    "LPT1",
+    "LPT2",
+    "LPT3",
+    "LPT4",
+    "LPT5",
+    "LPT6",
+    "LPT7",
+    "LPT8",
+    "LPT9",
+    "LPT\u{b9}",
+    "LPT\u{b2}",
+    "LPT\u{b3}",
+];
+
+/// Characters Windows does not allow in names (besides control characters)
+const ILLEGAL_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Classic Windows path length limit (`MAX_PATH` is 260 including the
+/// terminating NUL)
+const DEFAULT_MAX_PATH_LENGTH: usize = 259;
+
+/// Kinds of portability problems, in report order
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum PortabilityIssue {
+    ReservedName,
+    IllegalCharacter,
+    TrailingDotOrSpace,
+    PathTooLong,
+    CaseConflict,
+    UnicodeNormalization,
+}
+
+impl PortabilityIssue {
+    const ALL: [PortabilityIssue; 6] = [
+        PortabilityIssue::ReservedName,
+        PortabilityIssue::IllegalCharacter,
+        PortabilityIssue::TrailingDotOrSpace,
+        PortabilityIssue::PathTooLong,
+        PortabilityIssue::CaseConflict,
+        PortabilityIssue::UnicodeNormalization,
+    ];
+
+    fn description(self) -> &'static str {
+        match self {
+            PortabilityIssue::ReservedName => "Reserved device name on Windows",
+            PortabilityIssue::IllegalCharacter => "Character not allowed on Windows",
+            PortabilityIssue::TrailingDotOrSpace => "Trailing dot or space, removed by Windows",
+            PortabilityIssue::PathTooLong => "Path longer than Windows allows",
+            PortabilityIssue::CaseConflict => {
+                "Names in the same folder that differ only by case or Unicode normalization"
+            }
+            PortabilityIssue::UnicodeNormalization => {
+                "Name not in NFC form, may not match after copying to or from macOS"
+            }
+        }
+    }
+}
+
+/// Options for the portability check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PortabilityOptions {
+    /// Longest allowed path in UTF-16 units, as Windows counts them
+    pub max_path_length: usize,
+
+    /// Folder the scan root will be copied into, e.g. `C:\Users\me\Documents`;
+    /// path lengths are measured below it (default: the paths as scanned)
+    pub target_folder: Option<String>,
+}
+
+impl Default for PortabilityOptions {
+    fn default() -> Self {
+        Self {
+            max_path_length: DEFAULT_MAX_PATH_LENGTH,
+            target_folder: None,
+        }
+    }
+}
+
+/// One problematic entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortabilityProblem {
+    pub path: String,
+
+    pub name: String,
+
+    /// What exactly is wrong, e.g. the offending characters
+    pub detail: String,
+
+    /// A name that avoids every name-level problem and clashes with no other
+    /// name in the folder (None if renaming the entry alone cannot fix it)
+    pub suggested_name: Option<String>,
+}
+
+/// All problems of one kind
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortabilityGroup {
+    pub issue: PortabilityIssue,
+
+    pub description: String,
+
+    pub problems: Vec<PortabilityProblem>,
+}
+
+/// Result of the portability check; only issues that occur are listed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortabilityReport {
+    pub groups: Vec<PortabilityGroup>,
+
+    pub total_problems: usize,
+}
+
+/// Check every name and path of a scan for cross-platform problems
+///
+/// Archive members are skipped. An entry can appear in several groups.
+pub fn check_portability(scan: &ScanResult, options: &PortabilityOptions) -> PortabilityReport {
+    let mut problems: HashMap<PortabilityIssue, Vec<PortabilityProblem>> = HashMap::new();
+    let mut add = |issue, entry: &FileEntry, detail: String, suggested_name: Option<String>| {
+        problems.entry(issue).or_default().push(PortabilityProblem {
+            path: entry.path.clone(),
+            name: entry.name.clone(),
+            detail,
+            suggested_name,
+        });
+    };
+
+    let entries: Vec<&FileEntry> = scan.entries.iter().filter(|e| !e.is_virtual).collect();
+    let clashes = case_clashes(&entries);
+    let suggestions = suggested_names(&entries, &clashes);
+    for entry in &entries {
+        let name = entry.name.as_str();
+        let suggestion = suggestions.get(entry.path.as_str()).cloned();
+
+        if is_reserved(name) {
+            add(
+                PortabilityIssue::ReservedName,
+                entry,
+                format!(
+                    "'{}' is a device name",
+                    stem(name.trim_end_matches(['.', ' ']))
+                ),
+                suggestion.clone(),
+            );
+        }
+        let illegal = illegal_chars(name);
+        if !illegal.is_empty() {
+            add(
+                PortabilityIssue::IllegalCharacter,
+                entry,
+                format!("Contains {}", illegal),
+                suggestion.clone(),
+            );
+        }
+        if name.ends_with(['.', ' ']) {
+            add(
+                PortabilityIssue::TrailingDotOrSpace,
+                entry,
+                "Ends with a dot or space".to_string(),
+                suggestion.clone(),
+            );
+        }
+        if !is_nfc(name) {
+            add(
+                PortabilityIssue::UnicodeNormalization,
+                entry,
+                "Name is decomposed (NFD)".to_string(),
+                suggestion,
+            );
+        }
+
+        let length = target_path_length(&scan.root_path, &entry.path, options);
+        if length > options.max_path_length {
+            add(
+                PortabilityIssue::PathTooLong,
+                entry,
+                format!("{} characters (limit {})", length, options.max_path_length),
+                None,
+            );
+        }
+    }
+
+    for clash in &clashes {
+        let names: Vec<&str> = clash.iter().map(|entry| entry.name.as_str()).collect();
+        let detail = format!("Clashes with {}", names.join(", "));
+        for entry in clash {
+            add(
+                PortabilityIssue::CaseConflict,
+                entry,
+                detail.clone(),
+                suggestions.get(entry.path.as_str()).cloned(),
+            );
+        }
+    }
+
+    let groups: Vec<PortabilityGroup> = PortabilityIssue::ALL
+        .iter()
+        .filter_map(|&issue| {
+            let problems = problems.remove(&issue)?;
+            Some(PortabilityGroup {
+                issue,
+                description: issue.description().to_string(),
+                problems,
+            })
+        })
+        .collect();
+
+    PortabilityReport {
+        total_problems: groups.iter().map(|group| group.problems.len()).sum(),
+        groups,
+    }
+}
+
+/// Groups of entries whose names collide in their folder on a
+/// case-insensitive file system
+///
+/// Entries whose names are already safe come first in a group: the first
+/// entry keeps its name and the others get renamed.
+fn case_clashes<'a>(entries: &[&'a FileEntry]) -> Vec<Vec<&'a FileEntry>> {
+    let mut by_key: HashMap<(&str, String), Vec<&FileEntry>> = HashMap::new();
+    for entry in entries {
+        let key = safe_name(&entry.name).to_lowercase();
+        by_key
+            .entry((entry.parent_path.as_str(), key))
+            .or_default()
+            .push(entry);
+    }
+
+    let mut clashes: Vec<Vec<&FileEntry>> = by_key
+        .into_values()
+        .filter(|clash| clash.len() > 1)
+        .collect();
+    for clash in &mut clashes {
+        clash
+            .sort_by_cached_key(|entry| (safe_name(&entry.name) != entry.name, entry.name.clone()));
+    }
+    clashes.sort_by(|a, b| a[0].path.cmp(&b[0].path));
+    clashes
+}
+
+/// Suggested name per entry path, for entries with an unsafe name and for
+/// every entry after the first of a clash
+///
+/// A suggestion that would collide with a name in the folder or with an
+/// earlier suggestion gets numbered, so renaming never creates a new clash.
+fn suggested_names<'a>(
+    entries: &[&'a FileEntry],
+    clashes: &[Vec<&'a FileEntry>],
+) -> HashMap<&'a str, String> {
+    let clashing: HashSet<&str> = clashes
+        .iter()
+        .flat_map(|clash| clash.iter().skip(1))
+        .map(|entry| entry.path.as_str())
+        .collect();
+    let mut taken: HashSet<(&str, String)> = entries
+        .iter()
+        .map(|entry| (entry.parent_path.as_str(), name_key(&entry.name)))
+        .collect();
+
+    let mut suggestions = HashMap::new();
+    for entry in entries {
+        let safe = safe_name(&entry.name);
+        let clashing = clashing.contains(entry.path.as_str());
+        if safe == entry.name && !clashing {
+            continue;
+        }
+
+        // Changing only case or normalization is fine, unless the entry has to
+        // make way for another one in a clash
+        let own_key = (!clashing).then(|| name_key(&entry.name));
+        let mut suggestion = safe.clone();
+        let mut number = 1;
+        loop {
+            let key = name_key(&suggestion);
+            if own_key.as_ref() == Some(&key) || taken.insert((entry.parent_path.as_str(), key)) {
+                break;
+            }
+            number += 1;
+            suggestion = numbered_name(&safe, number);
+        }
+        suggestions.insert(entry.path.as_str(), suggestion);
+    }
+    suggestions
+}
+
+/// Name as a case-insensitive, normalization-insensitive file system compares it
+fn name_key(name: &str) -> String {
+    name.nfc().collect::<String>().to_lowercase()
+}
+
+/// A name that is valid everywhere: NFC, legal characters, no trailing dots
+/// or spaces, and no reserved device name
+pub fn safe_name(name: &str) -> String {
+    let mut safe: String = name
+        .nfc()
+        .map(|c| {
+            if ILLEGAL_CHARS.contains(&c) || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+
+    let trimmed_len = safe.trim_end_matches(['.', ' ']).len();
+    safe.truncate(trimmed_len);
+    if safe.is_empty() {
+        safe.push('_');
+    }
+
+    if is_reserved(&safe) {
+        let stem_len = stem(&safe).len();
+        safe.insert(stem_len, '_');
+    }
+    safe
+}
+
+/// Whether Windows treats the name as a device (`CON`, `nul.txt`, `COM1 `)
+fn is_reserved(name: &str) -> bool {
+    let stem = stem(name.trim_end_matches(['.', ' ']));
+    let stem = stem.trim_end_matches(' ');
+    RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(stem))
+}
+
+/// Name up to the first dot
+fn stem(name: &str) -> &str {
+    name.split_once('.').map_or(name, |(stem, _)| stem)
+}
+
+/// Illegal characters of a name, quoted and listed once each
+fn illegal_chars(name: &str) -> String {
+    let mut found: Vec<String> = Vec::new();
+    for c in name.chars() {
+        let shown = if c.is_control() {
+            format!("U+{:04X}", c as u32)
+        } else if ILLEGAL_CHARS.contains(&c) {
+            format!("'{}'", c)
+        } else {
+            continue;
+        };
+        if !found.contains(&shown) {
+            found.push(shown);
+        }
+    }
+    found.join(", ")
+}
+
+/// `report.txt` with number 2 becomes `report (2).txt`
+fn numbered_name(name: &str, number: usize) -> String {
+    match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => {
+            format!("{} ({}).{}", stem, number, extension)
+        }
+        _ => format!("{} ({})", name, number),
+    }
+}
+
+/// Length in UTF-16 units of the path the entry will have at the target
+fn target_path_length(root_path: &str, path: &str, options: &PortabilityOptions) -> usize {
+    let path = decode_path(path);
+    let Some(target_folder) = &options.target_folder else {
+        return path.to_string_lossy().encode_utf16().count();
+    };
+
+    // The root folder itself is copied into the target folder
+    let root = decode_path(root_path);
+    let below_target = path
+        .strip_prefix(root.parent().unwrap_or(&root))
+        .unwrap_or(&path);
+    let separator = usize::from(!target_folder.ends_with(['/', '\\']));
+    target_folder.encode_utf16().count()
+        + separator
+        + below_target.to_string_lossy().encode_utf16().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::test_helpers::{entry, scan_of};
+
+    fn check(paths: &[&str], options: &PortabilityOptions) -> PortabilityReport {
+        let entries = paths.iter().map(|path| entry(path, 0, false)).collect();
+        check_portability(&scan_of("/home/r", entries), options)
+    }
+
+    fn group(report: &PortabilityReport, issue: PortabilityIssue) -> Vec<(&str, Option<&str>)> {
+        report
+            .groups
+            .iter()
+            .find(|group| group.issue == issue)
+            .map(|group| {
+                group
+                    .problems
+                    .iter()
+                    .map(|p| (p.name.as_str(), p.suggested_name.as_deref()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_check_portability_names() {
+        let report = check(
+            &[
+                "/home/r/con.txt",
+                "/home/r/Aux",
+                "/home/r/com\u{b9}.log",
+                "/home/r/CONOUT$",
+                "/home/r/console.txt",
+                "/home/r/what?.txt",
+                "/home/r/notes. ",
+                "/home/r/cafe\u{301}.txt",
+                "/home/r/ok.txt",
+            ],
+            &PortabilityOptions::default(),
+        );
+
+        assert_eq!(
+            group(&report, PortabilityIssue::ReservedName),
+            [
+                ("con.txt", Some("con_.txt")),
+                ("Aux", Some("Aux_")),
+                ("com\u{b9}.log", Some("com\u{b9}_.log")),
+                ("CONOUT$", Some("CONOUT$_")),
+            ]
+        );
+        assert_eq!(
+            group(&report, PortabilityIssue::IllegalCharacter),
+            [("what?.txt", Some("what_.txt"))]
+        );
+        assert_eq!(
+            group(&report, PortabilityIssue::TrailingDotOrSpace),
+            [("notes. ", Some("notes"))]
+        );
+        assert_eq!(
+            group(&report, PortabilityIssue::UnicodeNormalization),
+            [("cafe\u{301}.txt", Some("caf\u{e9}.txt"))]
+        );
+        assert_eq!(report.groups.len(), 4);
+        assert_eq!(report.total_problems, 7);
+        assert_eq!(report.groups[1].problems[0].detail, "Contains '?'");
+    }
+
+    #[test]
+    fn test_check_portability_case_conflicts() {
+        let report = check(
+            &[
+                "/home/r/Report.txt",
+                "/home/r/report.TXT",
+                "/home/r/sub/report.txt",
+                "/home/r/caf\u{e9}",
+                "/home/r/CAFE\u{301}",
+            ],
+            &PortabilityOptions::default(),
+        );
+
+        assert_eq!(
+            group(&report, PortabilityIssue::CaseConflict),
+            [
+                ("Report.txt", None),
+                ("report.TXT", Some("report (2).TXT")),
+                ("caf\u{e9}", None),
+                ("CAFE\u{301}", Some("CAF\u{c9} (2)")),
+            ]
+        );
+        assert_eq!(
+            group(&report, PortabilityIssue::UnicodeNormalization),
+            [("CAFE\u{301}", Some("CAF\u{c9} (2)"))]
+        );
+    }
+
+    #[test]
+    fn test_suggestions_avoid_siblings() {
+        let report = check(
+            &[
+                "/home/r/a?.txt",
+                "/home/r/a_.txt",
+                "/home/r/a*.txt",
+                "/home/r/A_ (3).txt",
+                "/home/r/sub/a?.txt",
+            ],
+            &PortabilityOptions::default(),
+        );
+
+        assert_eq!(
+            group(&report, PortabilityIssue::IllegalCharacter),
+            [
+                ("a?.txt", Some("a_ (2).txt")),
+                ("a*.txt", Some("a_ (4).txt")),
+                ("a?.txt", Some("a_.txt")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_portability_path_length() {
+        let long_name = format!("/home/r/{}.txt", "a".repeat(240));
+        let paths = [long_name.as_str()];
+
+        assert_eq!(
+            check(&paths, &PortabilityOptions::default()).total_problems,
+            0
+        );
+
+        let options = PortabilityOptions {
+            target_folder: Some("C:\\Users\\someone\\Documents\\".to_string()),
+            ..PortabilityOptions::default()
+        };
+        let report = check(&paths, &options);
+        let problem = &report.groups[0].problems[0];
+        assert_eq!(report.groups[0].issue, PortabilityIssue::PathTooLong);
+        assert_eq!(problem.detail, "273 characters (limit 259)");
+        assert_eq!(problem.suggested_name, None);
+    }
+}
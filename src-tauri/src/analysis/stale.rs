@@ -0,0 +1,220 @@
+//! Files nobody has used for a long time
+
+use std::collections::HashMap;
+use std::path::Component;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::analysis::FolderSize;
+use crate::models::{decode_path, encode_path, FileEntry, ScanResult};
+
+/// Options for stale file detection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StaleOptions {
+    /// Files not used within this many days are stale
+    pub max_age_days: u32,
+
+    /// Count reading a file as use (disable on file systems mounted with
+    /// `noatime`, where access times are not kept up to date)
+    pub use_access_time: bool,
+}
+
+impl Default for StaleOptions {
+    fn default() -> Self {
+        Self {
+            max_age_days: 365,
+            use_access_time: true,
+        }
+    }
+}
+
+/// Stale files of a scan with their reclaimable size per top-level folder
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StaleFilesReport {
+    /// Files last used before this moment are stale (RFC 3339)
+    pub cutoff: String,
+
+    /// Stale files, largest first
+    pub files: Vec<FileEntry>,
+
+    /// Stale files per top-level folder, largest first; files directly in
+    /// the scan root are listed under the root itself
+    pub folders: Vec<FolderSize>,
+
+    /// Combined size of all stale files
+    pub total_size: u64,
+}
+
+/// Find files that were neither modified nor accessed within the window
+///
+/// A file's last use is the later of its modification and access time.
+/// Files without a readable modification time are never reported, and
+/// archive members are skipped since they cannot be reclaimed on their own.
+pub fn find_stale_files(
+    scan: &ScanResult,
+    options: &StaleOptions,
+    now: DateTime<Utc>,
+) -> StaleFilesReport {
+    let cutoff = now - Duration::days(i64::from(options.max_age_days));
+
+    let mut files: Vec<&FileEntry> = scan
+        .entries
+        .iter()
+        .filter(|entry| !entry.is_directory && !entry.is_virtual)
+        .filter(|entry| last_used(entry, options.use_access_time).is_some_and(|time| time < cutoff))
+        .collect();
+    files.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+
+    let mut folders: HashMap<String, FolderSize> = HashMap::new();
+    for file in &files {
+        let (path, name) = top_level_folder(&scan.root_path, file);
+        let folder = folders.entry(path.clone()).or_insert_with(|| FolderSize {
+            path,
+            name,
+            total_size: 0,
+            file_count: 0,
+        });
+        folder.total_size += file.size;
+        folder.file_count += 1;
+    }
+    let mut folders: Vec<FolderSize> = folders.into_values().collect();
+    folders.sort_by(|a, b| {
+        b.total_size
+            .cmp(&a.total_size)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+
+    StaleFilesReport {
+        cutoff: cutoff.to_rfc3339(),
+        total_size: files.iter().map(|file| file.size).sum(),
+        files: files.into_iter().cloned().collect(),
+        folders,
+    }
+}
+
+/// Latest of the modification and (optionally) access time
+fn last_used(entry: &FileEntry, use_access_time: bool) -> Option<DateTime<Utc>> {
+    let parse = |time: &str| {
+        DateTime::parse_from_rfc3339(time)
+            .ok()
+            .map(|time| time.with_timezone(&Utc))
+    };
+    let modified = parse(&entry.modified_at)?;
+    let accessed = match &entry.accessed_at {
+        Some(accessed) if use_access_time => parse(accessed),
+        _ => None,
+    };
+    Some(accessed.map_or(modified, |accessed| accessed.max(modified)))
+}
+
+/// Path and name of the top-level folder of the scan containing a file
+fn top_level_folder(root_path: &str, entry: &FileEntry) -> (String, String) {
+    let root = decode_path(root_path);
+    let path = decode_path(&entry.path);
+    let first = path
+        .strip_prefix(&root)
+        .ok()
+        .and_then(|relative| relative.components().next());
+
+    match first {
+        Some(Component::Normal(name)) if entry.depth > 1 => (
+            encode_path(&root.join(name)),
+            name.to_string_lossy().to_string(),
+        ),
+        _ => (
+            root_path.to_string(),
+            root.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::test_helpers::{entry, scan_of};
+
+    fn file(path: &str, size: u64, modified_at: &str, accessed_at: Option<&str>) -> FileEntry {
+        FileEntry {
+            modified_at: modified_at.to_string(),
+            ..entry(path, size, false)
+        }
+        .with_times(accessed_at.map(str::to_string), None)
+    }
+
+    fn scan() -> ScanResult {
+        let entries = vec![
+            file("/r/old.bin", 5, "2020-01-01T00:00:00Z", None),
+            file(
+                "/r/a/x/old.iso",
+                700,
+                "2019-01-01T00:00:00Z",
+                Some("2019-06-01T00:00:00Z"),
+            ),
+            file(
+                "/r/a/read.pdf",
+                50,
+                "2019-01-01T00:00:00Z",
+                Some("2025-05-01T00:00:00Z"),
+            ),
+            file("/r/a/new.txt", 80, "2025-05-01T00:00:00Z", None),
+            file(
+                "/r/b/old.log",
+                30,
+                "2021-01-01T00:00:00Z",
+                Some("2021-01-01T00:00:00Z"),
+            ),
+            file("/r/b/broken", 9, "unknown", None),
+        ];
+        scan_of("/r", entries)
+    }
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2025-06-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_find_stale_files() {
+        let report = find_stale_files(&scan(), &StaleOptions::default(), now());
+
+        let files: Vec<&str> = report.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(files, ["/r/a/x/old.iso", "/r/b/old.log", "/r/old.bin"]);
+        assert_eq!(report.total_size, 735);
+        assert_eq!(report.cutoff, "2024-06-01T00:00:00+00:00");
+
+        let folders: Vec<(&str, &str, u64, u64)> = report
+            .folders
+            .iter()
+            .map(|f| (f.path.as_str(), f.name.as_str(), f.total_size, f.file_count))
+            .collect();
+        assert_eq!(
+            folders,
+            [
+                ("/r/a", "a", 700, 1),
+                ("/r/b", "b", 30, 1),
+                ("/r", "r", 5, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_stale_files_without_access_time() {
+        let options = StaleOptions {
+            max_age_days: 365,
+            use_access_time: false,
+        };
+        let report = find_stale_files(&scan(), &options, now());
+
+        // Reading the PDF no longer keeps it fresh
+        assert_eq!(report.files.len(), 4);
+        assert_eq!(report.folders[0].path, "/r/a");
+        assert_eq!(report.folders[0].total_size, 750);
+        assert_eq!(report.folders[0].file_count, 2);
+    }
+}
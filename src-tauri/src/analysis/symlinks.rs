@@ -0,0 +1,179 @@
+//! Report of dangling symbolic links, links leaving the scan root and link loops
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{decode_path, encode_path, FailureReason, ScanResult};
+
+/// Maximum number of links followed when looking for a cycle
+const MAX_LINK_HOPS: usize = 40;
+
+/// A symbolic link with a problem
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymlinkIssue {
+    /// Path of the link
+    pub path: String,
+
+    /// Target as stored in the link
+    pub link_target: Option<String>,
+
+    /// Fully resolved target, if it exists
+    pub resolved_path: Option<String>,
+}
+
+/// Symbolic links found in a scan, grouped by problem
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymlinkReport {
+    /// Number of symbolic links in the scan
+    pub total_links: u64,
+
+    /// Links whose target does not exist
+    pub dangling: Vec<SymlinkIssue>,
+
+    /// Links resolving to a path outside the scan root
+    pub outside_root: Vec<SymlinkIssue>,
+
+    /// Links pointing to one of their own ancestors, or part of a link cycle
+    pub loops: Vec<SymlinkIssue>,
+}
+
+/// Check every symbolic link of a scan result against the filesystem
+///
+/// Loops are found whether or not the scan followed links; loops reported by
+/// a scan that did follow them (`symlink_loop` failures) are included as well.
+pub fn symlink_report(scan: &ScanResult) -> SymlinkReport {
+    let root = decode_path(&scan.root_path);
+    let mut report = SymlinkReport::default();
+    let mut seen: HashSet<&str> = HashSet::new();
+
+    let links = scan
+        .entries
+        .iter()
+        .filter(|entry| entry.is_symlink && !entry.is_virtual)
+        .map(|entry| (entry.path.as_str(), entry.link_target.clone()));
+    let loop_failures = scan
+        .failed_entries
+        .iter()
+        .filter(|failed| failed.reason == FailureReason::SymlinkLoop)
+        .map(|failed| {
+            let target = std::fs::read_link(decode_path(&failed.path))
+                .ok()
+                .map(|target| target.to_string_lossy().to_string());
+            (failed.path.as_str(), target)
+        });
+
+    for (path, link_target) in links.chain(loop_failures) {
+        if !seen.insert(path) {
+            continue;
+        }
+        report.total_links += 1;
+
+        let link = decode_path(path);
+        let mut issue = SymlinkIssue {
+            path: path.to_string(),
+            link_target,
+            resolved_path: None,
+        };
+        match link.canonicalize() {
+            Ok(resolved) => {
+                issue.resolved_path = Some(encode_path(&resolved));
+                if resolved.is_dir() && link.starts_with(&resolved) {
+                    report.loops.push(issue);
+                } else if !resolved.starts_with(&root) {
+                    report.outside_root.push(issue);
+                }
+            }
+            Err(_) if is_link_cycle(&link) => report.loops.push(issue),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => report.dangling.push(issue),
+            Err(_) => {}
+        }
+    }
+
+    report
+}
+
+/// Follow a chain of links and report whether it runs in a circle
+fn is_link_cycle(link: &Path) -> bool {
+    let mut visited: HashSet<PathBuf> = HashSet::new();
+    let mut current = link.to_path_buf();
+    for _ in 0..MAX_LINK_HOPS {
+        let Ok(target) = std::fs::read_link(&current) else {
+            return false;
+        };
+        if !visited.insert(current.clone()) {
+            return true;
+        }
+        current = current
+            .parent()
+            .map_or_else(|| target.clone(), |parent| parent.join(&target));
+    }
+    true
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::scanner::{scan_directory, ScanOptions};
+    use std::fs;
+    use std::os::unix::fs::symlink;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_symlink_report() {
+        let outside = tempdir().unwrap();
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join("folder")).unwrap();
+        fs::write(root.join("folder/file.txt"), "data").unwrap();
+        symlink("folder/file.txt", root.join("good")).unwrap();
+        symlink("missing.txt", root.join("dangling")).unwrap();
+        symlink(outside.path(), root.join("outside")).unwrap();
+        symlink("..", root.join("folder/up")).unwrap();
+        symlink("cycle_b", root.join("cycle_a")).unwrap();
+        symlink("cycle_a", root.join("cycle_b")).unwrap();
+
+        let scan = scan_directory(root.to_str().unwrap(), &ScanOptions::new()).unwrap();
+        let report = symlink_report(&scan);
+
+        let names = |issues: &[SymlinkIssue]| -> Vec<String> {
+            let mut names: Vec<String> = issues
+                .iter()
+                .map(|issue| {
+                    Path::new(&issue.path)
+                        .file_name()
+                        .unwrap()
+                        .to_string_lossy()
+                        .to_string()
+                })
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(report.total_links, 6);
+        assert_eq!(names(&report.dangling), ["dangling"]);
+        assert_eq!(names(&report.outside_root), ["outside"]);
+        assert_eq!(names(&report.loops), ["cycle_a", "cycle_b", "up"]);
+        assert_eq!(
+            report.dangling[0].link_target.as_deref(),
+            Some("missing.txt")
+        );
+    }
+
+    #[test]
+    fn test_symlink_report_with_followed_links() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("folder")).unwrap();
+        symlink("..", dir.path().join("folder/up")).unwrap();
+
+        let options = ScanOptions::new().with_follow_links(true);
+        let scan = scan_directory(dir.path().to_str().unwrap(), &options).unwrap();
+        let report = symlink_report(&scan);
+
+        assert_eq!(report.loops.len(), 1);
+        assert!(report.loops[0].path.ends_with("up"));
+    }
+}
@@ -0,0 +1,298 @@
+//! Largest files and folders of a scan
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{FileCategory, FileEntry, ScanResult};
+
+/// Restricts an analysis to files of certain categories, extensions,
+/// owners or permissions
+///
+/// Owner, group and permission filters only match entries scanned with
+/// ownership recorded.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EntryFilter {
+    /// Categories to include (default: all)
+    pub categories: Option<Vec<FileCategory>>,
+
+    /// Extensions to include, without dot and case-insensitive (default: all)
+    pub extensions: Option<Vec<String>>,
+
+    /// Owners to include, by user name or numeric uid (default: all)
+    pub owners: Option<Vec<String>>,
+
+    /// Groups to include, by group name or numeric gid (default: all)
+    pub groups: Option<Vec<String>>,
+
+    /// Only include entries with all of these mode bits set, e.g. 0o002 for
+    /// world-writable or 0o4000 for setuid (default: no restriction)
+    pub mode_bits: Option<u32>,
+}
+
+impl EntryFilter {
+    /// Whether a file passes the filter
+    pub fn matches(&self, entry: &FileEntry) -> bool {
+        let category_matches = match &self.categories {
+            Some(categories) => categories.contains(&entry.category),
+            None => true,
+        };
+        let extension_matches = match &self.extensions {
+            Some(extensions) => extensions.iter().any(|extension| {
+                extension
+                    .trim_start_matches('.')
+                    .eq_ignore_ascii_case(&entry.extension)
+            }),
+            None => true,
+        };
+        let owner_matches = match &self.owners {
+            Some(owners) => id_matches(owners, entry.owner.as_deref(), entry.uid),
+            None => true,
+        };
+        let group_matches = match &self.groups {
+            Some(groups) => id_matches(groups, entry.group.as_deref(), entry.gid),
+            None => true,
+        };
+        let mode_matches = match (self.mode_bits, entry.mode) {
+            (Some(bits), Some(mode)) => mode & bits == bits,
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        category_matches && extension_matches && owner_matches && group_matches && mode_matches
+    }
+}
+
+/// Whether a user or group given by name and id is one of the wanted values
+fn id_matches(wanted: &[String], name: Option<&str>, id: Option<u32>) -> bool {
+    wanted
+        .iter()
+        .any(|value| Some(value.as_str()) == name || id.is_some_and(|id| value.parse() == Ok(id)))
+}
+
+/// Total size of a folder including all subfolders
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderSize {
+    /// Folder path
+    pub path: String,
+
+    /// Folder name
+    pub name: String,
+
+    /// Combined size of all matching files below the folder
+    pub total_size: u64,
+
+    /// Number of matching files below the folder
+    pub file_count: u64,
+}
+
+/// Largest files and folders of a scan, largest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopNReport {
+    pub largest_files: Vec<FileEntry>,
+    pub largest_folders: Vec<FolderSize>,
+}
+
+/// Find the `n` largest files and the `n` largest folders by subtree total
+///
+/// Only files passing the filter are ranked and counted towards folder
+/// totals. Archive members are left out, as their archive is counted already.
+/// Files are summed into their parent folder in a single pass over the scan,
+/// then the sums are rolled up from the deepest folders, so the cost is
+/// O(entries + folders × log folders) and bounded heaps keep the ranking small.
+pub fn top_n(scan: &ScanResult, n: usize, filter: &EntryFilter) -> TopNReport {
+    // Folder entry index (once seen), total size and file count per folder path
+    let mut folders: HashMap<&str, (Option<usize>, u64, u64)> = HashMap::new();
+    let mut files: BinaryHeap<Reverse<(u64, Reverse<&str>, usize)>> = BinaryHeap::new();
+
+    for (index, entry) in scan.entries.iter().enumerate() {
+        if entry.is_virtual {
+            continue;
+        }
+        if entry.is_directory {
+            folders.entry(entry.path.as_str()).or_default().0 = Some(index);
+        } else if filter.matches(entry) {
+            push_bounded(
+                &mut files,
+                (entry.size, Reverse(entry.path.as_str()), index),
+                n,
+            );
+            let (_, total_size, file_count) =
+                folders.entry(entry.parent_path.as_str()).or_default();
+            *total_size += entry.size;
+            *file_count += 1;
+        }
+    }
+
+    // Parents of scanned files that are not folders of the scan (the root)
+    folders.retain(|_, (index, _, _)| index.is_some());
+
+    let mut order: Vec<usize> = folders
+        .values()
+        .filter_map(|(index, _, _)| *index)
+        .collect();
+    order.sort_unstable_by_key(|&index| Reverse(scan.entries[index].depth));
+    for index in order {
+        let entry = &scan.entries[index];
+        let (_, total_size, file_count) = folders[entry.path.as_str()];
+        if let Some((_, parent_size, parent_count)) = folders.get_mut(entry.parent_path.as_str()) {
+            *parent_size += total_size;
+            *parent_count += file_count;
+        }
+    }
+
+    let mut largest_folders: BinaryHeap<Reverse<(u64, Reverse<&str>, usize)>> = BinaryHeap::new();
+    for (path, (index, total_size, _)) in &folders {
+        if let Some(index) = index {
+            push_bounded(
+                &mut largest_folders,
+                (*total_size, Reverse(*path), *index),
+                n,
+            );
+        }
+    }
+
+    TopNReport {
+        largest_files: into_sorted(files)
+            .map(|index| scan.entries[index].clone())
+            .collect(),
+        largest_folders: into_sorted(largest_folders)
+            .map(|index| {
+                let entry = &scan.entries[index];
+                let (_, total_size, file_count) = folders[entry.path.as_str()];
+                FolderSize {
+                    path: entry.path.clone(),
+                    name: entry.name.clone(),
+                    total_size,
+                    file_count,
+                }
+            })
+            .collect(),
+    }
+}
+
+/// Push an item into a min-heap that keeps only the `n` largest items
+fn push_bounded<T: Ord>(heap: &mut BinaryHeap<Reverse<T>>, item: T, n: usize) {
+    if heap.len() < n {
+        heap.push(Reverse(item));
+    } else if heap
+        .peek()
+        .is_some_and(|Reverse(smallest)| item > *smallest)
+    {
+        heap.pop();
+        heap.push(Reverse(item));
+    }
+}
+
+/// Entry indices of a bounded heap, largest first (ties by path)
+fn into_sorted(
+    heap: BinaryHeap<Reverse<(u64, Reverse<&str>, usize)>>,
+) -> impl Iterator<Item = usize> + '_ {
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((_, _, index))| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::test_helpers::{entry, scan_of};
+
+    fn scan() -> ScanResult {
+        scan_of(
+            "/r",
+            vec![
+                entry("/r/a", 0, true),
+                entry("/r/a/b", 0, true),
+                entry("/r/a/b/movie.mp4", 500, false),
+                entry("/r/a/notes.txt", 10, false),
+                entry("/r/c", 0, true),
+                entry("/r/c/photo.jpg", 300, false),
+                entry("/r/c/clip.mp4", 300, false),
+                entry("/r/small.txt", 1, false),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_top_n() {
+        let report = top_n(&scan(), 2, &EntryFilter::default());
+
+        let files: Vec<&str> = report
+            .largest_files
+            .iter()
+            .map(|file| file.path.as_str())
+            .collect();
+        assert_eq!(files, ["/r/a/b/movie.mp4", "/r/c/clip.mp4"]);
+
+        let folders: Vec<(&str, u64, u64)> = report
+            .largest_folders
+            .iter()
+            .map(|folder| (folder.path.as_str(), folder.total_size, folder.file_count))
+            .collect();
+        assert_eq!(folders, [("/r/c", 600, 2), ("/r/a", 510, 2)]);
+    }
+
+    #[test]
+    fn test_top_n_filtered() {
+        let filter = EntryFilter {
+            extensions: Some(vec![".MP4".to_string()]),
+            ..EntryFilter::default()
+        };
+        let report = top_n(&scan(), 5, &filter);
+
+        assert_eq!(report.largest_files.len(), 2);
+        assert_eq!(report.largest_folders[0].path, "/r/a");
+        assert_eq!(report.largest_folders[0].total_size, 500);
+        assert_eq!(report.largest_folders.len(), 3);
+
+        let filter = EntryFilter {
+            categories: Some(vec![FileCategory::Document]),
+            ..EntryFilter::default()
+        };
+        let report = top_n(&scan(), 1, &filter);
+        assert_eq!(report.largest_files[0].path, "/r/a/notes.txt");
+    }
+
+    #[test]
+    fn test_entry_filter_ownership() {
+        let file = entry("/r/tool", 1, false)
+            .with_owner(1000, Some("alice".to_string()))
+            .with_group(50, None)
+            .with_mode(0o4757);
+
+        let filter = |f: EntryFilter| f.matches(&file);
+        assert!(filter(EntryFilter {
+            owners: Some(vec!["bob".to_string(), "alice".to_string()]),
+            ..EntryFilter::default()
+        }));
+        assert!(filter(EntryFilter {
+            owners: Some(vec!["1000".to_string()]),
+            groups: Some(vec!["50".to_string()]),
+            ..EntryFilter::default()
+        }));
+        assert!(!filter(EntryFilter {
+            groups: Some(vec!["staff".to_string()]),
+            ..EntryFilter::default()
+        }));
+        assert!(filter(EntryFilter {
+            mode_bits: Some(0o4002),
+            ..EntryFilter::default()
+        }));
+        assert!(!filter(EntryFilter {
+            mode_bits: Some(0o020),
+            ..EntryFilter::default()
+        }));
+
+        // Entries scanned without ownership never match ownership filters
+        let plain = entry("/r/plain", 1, false);
+        assert!(!EntryFilter {
+            mode_bits: Some(0),
+            ..EntryFilter::default()
+        }
+        .matches(&plain));
+    }
+}
@@ -0,0 +1,641 @@
+//! Size hierarchy and squarified treemap layout of a scan
+//!
+//! Both views start at the scan root or at any folder of the scan (drill-down)
+//! and prune small entries: entries below the threshold are merged into one
+//! aggregate item per folder, so the output stays small for huge scans.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{FileCategory, FileEntry, ScanResult};
+
+/// Options for a treemap layout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TreemapOptions {
+    /// Folder to lay out (default: scan root)
+    pub root: Option<String>,
+
+    /// Viewport width in pixels
+    pub width: f64,
+
+    /// Viewport height in pixels
+    pub height: f64,
+
+    /// Entries whose rectangle would be smaller than this (in square pixels)
+    /// are merged into one aggregate rectangle per folder
+    pub min_area: f64,
+
+    /// Number of folder levels to lay out (default: unlimited)
+    pub max_depth: Option<u32>,
+}
+
+impl Default for TreemapOptions {
+    fn default() -> Self {
+        Self {
+            root: None,
+            width: 800.0,
+            height: 600.0,
+            min_area: 16.0,
+            max_depth: None,
+        }
+    }
+}
+
+/// One rectangle of a treemap layout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TreemapRect {
+    /// Entry path (the parent folder's path for an aggregate)
+    pub path: String,
+
+    /// Entry name (empty for an aggregate)
+    pub name: String,
+
+    /// Size in bytes (subtree total for folders)
+    pub size: u64,
+
+    pub is_directory: bool,
+
+    pub category: FileCategory,
+
+    /// Level below the layout root (children of the root = 1)
+    pub depth: u32,
+
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+
+    /// Number of small entries merged into this rectangle (0 for a single entry)
+    pub aggregated_count: u32,
+}
+
+/// Treemap rectangles in drawing order (folders before their contents)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TreemapLayout {
+    pub root_path: String,
+    pub total_size: u64,
+    pub rects: Vec<TreemapRect>,
+}
+
+/// Options for a pruned size hierarchy, e.g. for a sunburst chart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HierarchyOptions {
+    /// Folder to start at (default: scan root)
+    pub root: Option<String>,
+
+    /// Number of levels below the root to include
+    pub max_depth: u32,
+
+    /// Entries smaller than this fraction of the root total are aggregated
+    pub min_fraction: f64,
+}
+
+impl Default for HierarchyOptions {
+    fn default() -> Self {
+        Self {
+            root: None,
+            max_depth: 4,
+            min_fraction: 0.001,
+        }
+    }
+}
+
+/// A node of a pruned size hierarchy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SizeNode {
+    /// Entry path (the parent folder's path for an aggregate)
+    pub path: String,
+
+    /// Entry name (empty for an aggregate)
+    pub name: String,
+
+    /// Size in bytes (subtree total for folders)
+    pub size: u64,
+
+    pub is_directory: bool,
+
+    /// Number of small entries merged into this node (0 for a single entry)
+    pub aggregated_count: u32,
+
+    /// Children, largest first (empty below `max_depth`)
+    pub children: Vec<SizeNode>,
+}
+
+/// A scan with subtree totals and children lists built from `FileEntry.parent_path`
+///
+/// Building the tree is the expensive part of both views, so it is built once
+/// per scan and reused for every layout.
+pub struct SizeTree {
+    scan: ScanResult,
+    totals: Vec<u64>,
+    /// Child indices per folder path, largest first
+    children: HashMap<String, Vec<usize>>,
+}
+
+impl SizeTree {
+    pub fn new(scan: ScanResult) -> Self {
+        let entries = scan.entries.as_slice();
+        let mut index_of: HashMap<&str, usize> = HashMap::new();
+        let mut children: HashMap<String, Vec<usize>> = HashMap::new();
+        for (index, entry) in entries.iter().enumerate() {
+            // Archive members would count their archive's contents twice
+            if entry.is_virtual {
+                continue;
+            }
+            index_of.insert(entry.path.as_str(), index);
+            children
+                .entry(entry.parent_path.clone())
+                .or_default()
+                .push(index);
+        }
+
+        // Accumulate sizes bottom-up, deepest entries first
+        let mut order: Vec<usize> = index_of.values().copied().collect();
+        order.sort_unstable_by_key(|&index| std::cmp::Reverse(entries[index].depth));
+        let mut totals = vec![0u64; entries.len()];
+        for index in order {
+            let entry = &entries[index];
+            if !entry.is_directory {
+                totals[index] += entry.size;
+            }
+            if let Some(&parent) = index_of.get(entry.parent_path.as_str()) {
+                totals[parent] += totals[index];
+            }
+        }
+
+        for list in children.values_mut() {
+            list.sort_by(|&a, &b| {
+                totals[b]
+                    .cmp(&totals[a])
+                    .then_with(|| entries[a].name.cmp(&entries[b].name))
+            });
+        }
+
+        Self {
+            scan,
+            totals,
+            children,
+        }
+    }
+
+    /// Root path of the scan
+    pub fn root_path(&self) -> &str {
+        &self.scan.root_path
+    }
+
+    /// The scan the tree was built from
+    pub fn scan(&self) -> &ScanResult {
+        &self.scan
+    }
+
+    fn entry(&self, index: usize) -> &FileEntry {
+        &self.scan.entries[index]
+    }
+
+    fn children_of(&self, path: &str) -> &[usize] {
+        self.children.get(path).map_or(&[], Vec::as_slice)
+    }
+
+    fn folder_total(&self, path: &str) -> u64 {
+        self.children_of(path)
+            .iter()
+            .map(|&index| self.totals[index])
+            .sum()
+    }
+
+    /// Resolve the requested root to the scan root or a folder of the scan
+    fn resolve_root(&self, root: Option<&String>) -> Result<String, String> {
+        let Some(root) = root.filter(|root| **root != self.scan.root_path) else {
+            return Ok(self.scan.root_path.clone());
+        };
+        let is_folder = self
+            .scan
+            .entries
+            .iter()
+            .any(|entry| entry.is_directory && !entry.is_virtual && entry.path == *root);
+        if is_folder {
+            Ok(root.clone())
+        } else {
+            Err(format!("Folder not found in scan: {}", root))
+        }
+    }
+}
+
+/// Axis-aligned rectangle
+#[derive(Debug, Clone, Copy)]
+struct Bounds {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+/// Lay out a folder of a scan as a squarified treemap
+pub fn treemap_layout(tree: &SizeTree, options: &TreemapOptions) -> Result<TreemapLayout, String> {
+    if !(options.width > 0.0 && options.height > 0.0) {
+        return Err("Viewport width and height must be positive".to_string());
+    }
+
+    let root_path = tree.resolve_root(options.root.as_ref())?;
+    let total_size = tree.folder_total(&root_path);
+
+    let mut rects = Vec::new();
+    let bounds = Bounds {
+        x: 0.0,
+        y: 0.0,
+        width: options.width,
+        height: options.height,
+    };
+    layout_folder(tree, &root_path, total_size, bounds, 1, options, &mut rects);
+
+    Ok(TreemapLayout {
+        root_path,
+        total_size,
+        rects,
+    })
+}
+
+fn layout_folder(
+    tree: &SizeTree,
+    folder: &str,
+    total_size: u64,
+    bounds: Bounds,
+    depth: u32,
+    options: &TreemapOptions,
+    rects: &mut Vec<TreemapRect>,
+) {
+    if total_size == 0 || options.max_depth.is_some_and(|max| depth > max) {
+        return;
+    }
+
+    let scale = bounds.width * bounds.height / total_size as f64;
+    let mut items: Vec<(Option<usize>, u64)> = Vec::new();
+    let (mut small_count, mut small_size) = (0u32, 0u64);
+    for &index in tree.children_of(folder) {
+        let size = tree.totals[index];
+        if size == 0 {
+            continue;
+        }
+        if size as f64 * scale < options.min_area {
+            small_count += 1;
+            small_size += size;
+        } else {
+            items.push((Some(index), size));
+        }
+    }
+    if small_count > 0 {
+        // Squarify needs the areas largest first
+        let position = items.partition_point(|&(_, size)| size >= small_size);
+        items.insert(position, (None, small_size));
+    }
+
+    let areas: Vec<f64> = items.iter().map(|(_, size)| *size as f64 * scale).collect();
+    for ((item, size), rect) in items.into_iter().zip(squarify(&areas, bounds)) {
+        let Some(index) = item else {
+            rects.push(TreemapRect {
+                path: folder.to_string(),
+                name: String::new(),
+                size,
+                is_directory: false,
+                category: FileCategory::Other,
+                depth,
+                x: rect.x,
+                y: rect.y,
+                width: rect.width,
+                height: rect.height,
+                aggregated_count: small_count,
+            });
+            continue;
+        };
+
+        let entry = tree.entry(index);
+        rects.push(TreemapRect {
+            path: entry.path.clone(),
+            name: entry.name.clone(),
+            size,
+            is_directory: entry.is_directory,
+            category: entry.category.clone(),
+            depth,
+            x: rect.x,
+            y: rect.y,
+            width: rect.width,
+            height: rect.height,
+            aggregated_count: 0,
+        });
+        if entry.is_directory {
+            layout_folder(tree, &entry.path, size, rect, depth + 1, options, rects);
+        }
+    }
+}
+
+/// Split `bounds` into rectangles of the given areas (largest first) with
+/// aspect ratios close to 1, following Bruls, Huizing and van Wijk
+fn squarify(areas: &[f64], bounds: Bounds) -> Vec<Bounds> {
+    let mut rects = Vec::with_capacity(areas.len());
+    let mut remaining = bounds;
+    let mut start = 0;
+    while start < areas.len() {
+        let side = remaining.width.min(remaining.height);
+        let mut end = start + 1;
+        while end < areas.len()
+            && worst_ratio(&areas[start..=end], side) <= worst_ratio(&areas[start..end], side)
+        {
+            end += 1;
+        }
+        remaining = layout_row(&areas[start..end], remaining, &mut rects);
+        start = end;
+    }
+    rects
+}
+
+/// Worst aspect ratio of a row of areas laid out along a side of the given length
+fn worst_ratio(row: &[f64], side: f64) -> f64 {
+    let sum: f64 = row.iter().sum();
+    let max = row.iter().copied().fold(0.0, f64::max);
+    let min = row.iter().copied().fold(f64::INFINITY, f64::min);
+    let (side2, sum2) = (side * side, sum * sum);
+    (side2 * max / sum2).max(sum2 / (side2 * min))
+}
+
+/// Place a row along the shorter side of `bounds` and return the space left
+fn layout_row(row: &[f64], bounds: Bounds, rects: &mut Vec<Bounds>) -> Bounds {
+    let sum: f64 = row.iter().sum();
+    if bounds.width >= bounds.height {
+        // Column on the left
+        let width = if bounds.height > 0.0 {
+            sum / bounds.height
+        } else {
+            0.0
+        };
+        let mut y = bounds.y;
+        for area in row {
+            let height = if width > 0.0 { area / width } else { 0.0 };
+            rects.push(Bounds {
+                x: bounds.x,
+                y,
+                width,
+                height,
+            });
+            y += height;
+        }
+        Bounds {
+            x: bounds.x + width,
+            width: (bounds.width - width).max(0.0),
+            ..bounds
+        }
+    } else {
+        // Row along the top
+        let height = if bounds.width > 0.0 {
+            sum / bounds.width
+        } else {
+            0.0
+        };
+        let mut x = bounds.x;
+        for area in row {
+            let width = if height > 0.0 { area / height } else { 0.0 };
+            rects.push(Bounds {
+                x,
+                y: bounds.y,
+                width,
+                height,
+            });
+            x += width;
+        }
+        Bounds {
+            y: bounds.y + height,
+            height: (bounds.height - height).max(0.0),
+            ..bounds
+        }
+    }
+}
+
+/// Build a size hierarchy below a folder of a scan, pruned by depth and size
+pub fn size_hierarchy(tree: &SizeTree, options: &HierarchyOptions) -> Result<SizeNode, String> {
+    let root_path = tree.resolve_root(options.root.as_ref())?;
+    let total_size = tree.folder_total(&root_path);
+    let min_size = (total_size as f64 * options.min_fraction.max(0.0)) as u64;
+
+    let name = tree
+        .scan
+        .entries
+        .iter()
+        .find(|entry| entry.path == root_path)
+        .map_or_else(|| root_path.clone(), |entry| entry.name.clone());
+    Ok(SizeNode {
+        children: hierarchy_children(tree, &root_path, 1, options.max_depth, min_size),
+        path: root_path,
+        name,
+        size: total_size,
+        is_directory: true,
+        aggregated_count: 0,
+    })
+}
+
+fn hierarchy_children(
+    tree: &SizeTree,
+    folder: &str,
+    depth: u32,
+    max_depth: u32,
+    min_size: u64,
+) -> Vec<SizeNode> {
+    if depth > max_depth {
+        return Vec::new();
+    }
+
+    let mut nodes = Vec::new();
+    let (mut small_count, mut small_size) = (0u32, 0u64);
+    for &index in tree.children_of(folder) {
+        let size = tree.totals[index];
+        if size == 0 {
+            continue;
+        }
+        if size < min_size {
+            small_count += 1;
+            small_size += size;
+            continue;
+        }
+
+        let entry = tree.entry(index);
+        let children = if entry.is_directory {
+            hierarchy_children(tree, &entry.path, depth + 1, max_depth, min_size)
+        } else {
+            Vec::new()
+        };
+        nodes.push(SizeNode {
+            path: entry.path.clone(),
+            name: entry.name.clone(),
+            size,
+            is_directory: entry.is_directory,
+            aggregated_count: 0,
+            children,
+        });
+    }
+    if small_count > 0 {
+        // Keep the children largest first
+        let position = nodes.partition_point(|node| node.size >= small_size);
+        nodes.insert(
+            position,
+            SizeNode {
+                path: folder.to_string(),
+                name: String::new(),
+                size: small_size,
+                is_directory: false,
+                aggregated_count: small_count,
+                children: Vec::new(),
+            },
+        );
+    }
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::test_helpers::{entry, scan_of};
+
+    fn tree_of(entries: Vec<FileEntry>) -> SizeTree {
+        SizeTree::new(scan_of("/r", entries))
+    }
+
+    fn tree() -> SizeTree {
+        let mut entries = vec![
+            entry("/r/big", 0, true),
+            entry("/r/big/a.bin", 600, false),
+            entry("/r/big/b.bin", 200, false),
+            entry("/r/c.bin", 150, false),
+            entry("/r/d.bin", 50, false),
+        ];
+        for i in 0..10 {
+            entries.push(entry(&format!("/r/tiny{}.bin", i), 1, false));
+        }
+        tree_of(entries)
+    }
+
+    #[test]
+    fn test_squarify_fills_bounds() {
+        let bounds = Bounds {
+            x: 0.0,
+            y: 0.0,
+            width: 6.0,
+            height: 4.0,
+        };
+        let rects = squarify(&[6.0, 6.0, 4.0, 3.0, 2.0, 2.0, 1.0], bounds);
+
+        assert_eq!(rects.len(), 7);
+        let area: f64 = rects.iter().map(|rect| rect.width * rect.height).sum();
+        assert!((area - 24.0).abs() < 1e-9);
+        for rect in &rects {
+            assert!(rect.x >= -1e-9 && rect.x + rect.width <= 6.0 + 1e-9);
+            assert!(rect.y >= -1e-9 && rect.y + rect.height <= 4.0 + 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_treemap_layout() {
+        let options = TreemapOptions {
+            width: 100.0,
+            height: 100.0,
+            min_area: 20.0,
+            ..TreemapOptions::default()
+        };
+        let layout = treemap_layout(&tree(), &options).unwrap();
+        assert_eq!(layout.total_size, 1010);
+
+        let top: Vec<&TreemapRect> = layout.rects.iter().filter(|r| r.depth == 1).collect();
+        let names: Vec<&str> = top.iter().map(|rect| rect.name.as_str()).collect();
+        assert_eq!(names, ["big", "c.bin", "d.bin", ""]);
+        assert_eq!(top[3].aggregated_count, 10);
+        let area: f64 = top.iter().map(|rect| rect.width * rect.height).sum();
+        assert!((area - 10_000.0).abs() < 1e-6);
+
+        // Contents of a folder follow it and stay inside its rectangle
+        let big = top[0];
+        let inner: Vec<&TreemapRect> = layout.rects.iter().filter(|r| r.depth == 2).collect();
+        assert_eq!(inner.len(), 2);
+        for rect in inner {
+            assert!(rect.x >= big.x - 1e-9 && rect.x + rect.width <= big.x + big.width + 1e-9);
+            assert!(rect.y >= big.y - 1e-9 && rect.y + rect.height <= big.y + big.height + 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_treemap_aggregate_in_size_order() {
+        let mut entries = vec![entry("/r/x.bin", 100, false)];
+        for i in 0..20 {
+            entries.push(entry(&format!("/r/small{}.bin", i), 10, false));
+        }
+        let options = TreemapOptions {
+            width: 100.0,
+            height: 100.0,
+            min_area: 1000.0,
+            ..TreemapOptions::default()
+        };
+        let layout = treemap_layout(&tree_of(entries), &options).unwrap();
+
+        // The aggregate outweighs the single file, so it is laid out first
+        let names: Vec<&str> = layout.rects.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["", "x.bin"]);
+        assert_eq!(layout.rects[0].size, 200);
+    }
+
+    #[test]
+    fn test_treemap_drill_down() {
+        let options = TreemapOptions {
+            root: Some("/r/big".to_string()),
+            ..TreemapOptions::default()
+        };
+        let layout = treemap_layout(&tree(), &options).unwrap();
+        assert_eq!(layout.total_size, 800);
+        assert_eq!(layout.rects.len(), 2);
+        assert_eq!(layout.rects[0].name, "a.bin");
+
+        let options = TreemapOptions {
+            root: Some("/r/c.bin".to_string()),
+            ..TreemapOptions::default()
+        };
+        assert!(treemap_layout(&tree(), &options).is_err());
+    }
+
+    #[test]
+    fn test_size_hierarchy() {
+        let options = HierarchyOptions {
+            max_depth: 1,
+            min_fraction: 0.01,
+            ..HierarchyOptions::default()
+        };
+        let root = size_hierarchy(&tree(), &options).unwrap();
+
+        assert_eq!(root.size, 1010);
+        let sizes: Vec<u64> = root.children.iter().map(|node| node.size).collect();
+        assert_eq!(sizes, [800, 150, 50, 10]);
+        assert_eq!(root.children[3].aggregated_count, 10);
+        // Pruned below max_depth
+        assert!(root.children[0].children.is_empty());
+    }
+
+    #[test]
+    fn test_hierarchy_aggregate_in_size_order() {
+        let mut entries = vec![entry("/r/x.bin", 100, false)];
+        for i in 0..20 {
+            entries.push(entry(&format!("/r/small{}.bin", i), 10, false));
+        }
+        let options = HierarchyOptions {
+            min_fraction: 0.1,
+            ..HierarchyOptions::default()
+        };
+        let root = size_hierarchy(&tree_of(entries), &options).unwrap();
+
+        let sizes: Vec<(u64, u32)> = root
+            .children
+            .iter()
+            .map(|node| (node.size, node.aggregated_count))
+            .collect();
+        assert_eq!(sizes, [(200, 20), (100, 0)]);
+    }
+}
@@ -0,0 +1,268 @@
+//! Analysis command handlers
+
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+use tauri::State;
+
+use crate::analysis::{
+    self, detect_empty_items, symlink_report, EmptyItemsReport, EntryFilter, HierarchyOptions,
+    PortabilityOptions, PortabilityReport, SecretScanOptions, SecretScanReport,
+    SecurityAuditReport, SizeNode, SizeTree, StaleFilesReport, StaleOptions, SymlinkReport,
+    TopNReport, TreemapLayout, TreemapOptions,
+};
+use crate::models::ScanResult;
+use crate::scanner::{scan_directory, ScanOptions};
+
+/// The last scan with its size tree, kept for the analysis commands
+///
+/// Registered as Tauri managed state and filled by `scan_folder`, so the
+/// reports, resizing or drilling down only recompute their results instead
+/// of sending the whole scan back and rebuilding the tree.
+#[derive(Default)]
+pub struct ScanCache {
+    last: Mutex<Option<Arc<SizeTree>>>,
+}
+
+impl ScanCache {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the cached scan
+    pub fn store(&self, scan: ScanResult) {
+        let tree = Arc::new(SizeTree::new(scan));
+        *self.last.lock().expect("scan cache lock poisoned") = Some(tree);
+    }
+
+    /// Size tree of the cached scan of `root`
+    pub fn get(&self, root: &str) -> Result<Arc<SizeTree>, String> {
+        self.last
+            .lock()
+            .expect("scan cache lock poisoned")
+            .as_ref()
+            .filter(|tree| tree.root_path() == root)
+            .cloned()
+            .ok_or_else(|| format!("Folder has not been scanned: {}", root))
+    }
+}
+
+/// Find zero-byte files and recursively empty folders below a folder
+///
+/// Folders that only contain junk such as `.DS_Store` or `Thumbs.db` count as
+/// empty. The returned `delete_order` lists the deepest paths first and can be
+/// passed to `delete_files` directly.
+///
+/// # Arguments
+/// * `path` - Folder to search
+///
+/// # Returns
+/// * `Ok(EmptyItemsReport)` - Empty files and folders found
+/// * `Err(String)` - Error message if the folder could not be scanned
+#[tauri::command]
+pub async fn find_empty_items(path: String) -> Result<EmptyItemsReport, String> {
+    let scan = scan_directory(&path, &ScanOptions::new())?;
+    Ok(detect_empty_items(&scan))
+}
+
+/// Report dangling symbolic links, links leaving the folder and link loops
+///
+/// # Arguments
+/// * `path` - Folder to search
+/// * `follow_links` - Follow links into the folders they point to (default: false)
+///
+/// # Returns
+/// * `Ok(SymlinkReport)` - Problematic links grouped by problem
+/// * `Err(String)` - Error message if the folder could not be scanned
+#[tauri::command]
+pub async fn find_symlink_issues(
+    path: String,
+    follow_links: Option<bool>,
+) -> Result<SymlinkReport, String> {
+    let options = ScanOptions::new().with_follow_links(follow_links.unwrap_or(false));
+    let scan = scan_directory(&path, &options)?;
+    Ok(symlink_report(&scan))
+}
+
+/// Find the N largest files and the N largest folders of the last scan
+///
+/// Folder sizes are subtree totals. With a filter, only matching files are
+/// ranked and counted towards the folder totals.
+///
+/// # Arguments
+/// * `root` - Root path of the last `scan_folder` result
+/// * `n` - Number of files and folders to return
+/// * `filter` - Optional category or extension filter
+///
+/// # Returns
+/// * `Ok(TopNReport)` - Largest files and folders, largest first
+/// * `Err(String)` - Error message if the folder was not scanned or `n` is zero
+#[tauri::command]
+pub async fn top_n(
+    scans: State<'_, ScanCache>,
+    root: String,
+    n: usize,
+    filter: Option<EntryFilter>,
+) -> Result<TopNReport, String> {
+    if n == 0 {
+        return Err("N must be greater than zero".to_string());
+    }
+    let tree = scans.get(&root)?;
+    Ok(analysis::top_n(tree.scan(), n, &filter.unwrap_or_default()))
+}
+
+/// Compute a squarified treemap layout of the last scan for a viewport
+///
+/// Rectangles are returned in drawing order, folders before their contents.
+/// Entries too small to show are merged into one aggregate rectangle per
+/// folder. Set `options.root` to a folder of the scan to drill down into it.
+///
+/// # Arguments
+/// * `root` - Root path of the last `scan_folder` result
+/// * `options` - Folder to lay out, viewport size, minimum area and depth (default: 800x600)
+///
+/// # Returns
+/// * `Ok(TreemapLayout)` - Rectangles with their entries
+/// * `Err(String)` - Error message if the folder was not scanned or the viewport is invalid
+#[tauri::command]
+pub async fn treemap_layout(
+    scans: State<'_, ScanCache>,
+    root: String,
+    options: Option<TreemapOptions>,
+) -> Result<TreemapLayout, String> {
+    let tree = scans.get(&root)?;
+    analysis::treemap_layout(&tree, &options.unwrap_or_default())
+}
+
+/// Build a pruned size hierarchy of the last scan, e.g. for a sunburst chart
+///
+/// # Arguments
+/// * `root` - Root path of the last `scan_folder` result
+/// * `options` - Folder to start at, depth and minimum size fraction
+///
+/// # Returns
+/// * `Ok(SizeNode)` - Root node with nested children, largest first
+/// * `Err(String)` - Error message if the folder was not scanned or is not a folder of the scan
+#[tauri::command]
+pub async fn size_hierarchy(
+    scans: State<'_, ScanCache>,
+    root: String,
+    options: Option<HierarchyOptions>,
+) -> Result<SizeNode, String> {
+    let tree = scans.get(&root)?;
+    analysis::size_hierarchy(&tree, &options.unwrap_or_default())
+}
+
+/// Find files that were neither modified nor accessed within a time window
+///
+/// Access times are only as good as the file system keeps them: with
+/// `noatime` mounts, set `use_access_time` to false to rely on modification
+/// times alone.
+///
+/// # Arguments
+/// * `root` - Root path of the last `scan_folder` result
+/// * `options` - Window in days and whether access counts as use (default: 365 days)
+///
+/// # Returns
+/// * `Ok(StaleFilesReport)` - Stale files and reclaimable size per top-level folder
+/// * `Err(String)` - Error message if the folder was not scanned
+#[tauri::command]
+pub async fn find_stale_files(
+    scans: State<'_, ScanCache>,
+    root: String,
+    options: Option<StaleOptions>,
+) -> Result<StaleFilesReport, String> {
+    let tree = scans.get(&root)?;
+    Ok(analysis::find_stale_files(
+        tree.scan(),
+        &options.unwrap_or_default(),
+        Utc::now(),
+    ))
+}
+
+/// Audit the last scan for risky permissions and ownership
+///
+/// Flags world-writable files and folders, setuid/setgid executables, files
+/// of nonexistent users or groups and private keys readable by others. The
+/// scan must have been made with `include_ownership`; entries without
+/// permission data are counted in `unchecked_entries`.
+///
+/// # Arguments
+/// * `root` - Root path of the last `scan_folder` result
+/// * `export_path` - Also write the report as JSON to this file
+///
+/// # Returns
+/// * `Ok(SecurityAuditReport)` - Findings with rule IDs, severity and remediation
+/// * `Err(String)` - Error message if the folder was not scanned or the report could not be exported
+#[tauri::command]
+pub async fn security_audit(
+    scans: State<'_, ScanCache>,
+    root: String,
+    export_path: Option<String>,
+) -> Result<SecurityAuditReport, String> {
+    let report = analysis::security_audit(scans.get(&root)?.scan());
+    if let Some(export_path) = export_path {
+        std::fs::write(&export_path, report.to_json()?)
+            .map_err(|e| format!("Failed to write audit report: {}", e))?;
+    }
+    Ok(report)
+}
+
+/// Search the text files of the last scan for leaked secrets and credentials
+///
+/// Built-in rules cover AWS keys, private key blocks, JWTs, GitHub and Slack
+/// tokens and high-entropy values assigned to secret-like names. A JSON rule
+/// file can add rules or replace them. Secrets are masked in the report.
+///
+/// # Arguments
+/// * `root` - Root path of the last `scan_folder` result
+/// * `options` - Rule file and maximum file size (default: built-in rules, 5 MB)
+///
+/// # Returns
+/// * `Ok(SecretScanReport)` - Findings with file, line and rule
+/// * `Err(String)` - Error message if the folder was not scanned or the rule file is missing or invalid
+#[tauri::command]
+pub async fn scan_secrets(
+    scans: State<'_, ScanCache>,
+    root: String,
+    options: Option<SecretScanOptions>,
+) -> Result<SecretScanReport, String> {
+    let tree = scans.get(&root)?;
+    let options = options.unwrap_or_default();
+    let rules = analysis::compile_secret_rules(analysis::load_secret_rules(
+        options.rules_file.as_deref(),
+    )?)?;
+    Ok(analysis::scan_secrets(
+        tree.scan(),
+        &rules,
+        options.max_file_size,
+    ))
+}
+
+/// Find names and paths that break on other operating systems
+///
+/// Checks for reserved Windows names, illegal characters, trailing dots or
+/// spaces, overlong paths, names that clash on case-insensitive file systems
+/// and names that are not in Unicode NFC form. Problems are grouped by issue,
+/// with a suggested safe name where renaming helps.
+///
+/// # Arguments
+/// * `root` - Root path of the last `scan_folder` result
+/// * `options` - Path length limit and target folder (default: 259 characters)
+///
+/// # Returns
+/// * `Ok(PortabilityReport)` - Problems grouped by issue
+/// * `Err(String)` - Error message if the folder was not scanned
+#[tauri::command]
+pub async fn check_portability(
+    scans: State<'_, ScanCache>,
+    root: String,
+    options: Option<PortabilityOptions>,
+) -> Result<PortabilityReport, String> {
+    let tree = scans.get(&root)?;
+    Ok(analysis::check_portability(
+        tree.scan(),
+        &options.unwrap_or_default(),
+    ))
+}
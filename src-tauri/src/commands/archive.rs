@@ -0,0 +1,1615 @@
+//! Archive command handlers (create, extract)
+
+use std::collections::HashSet;
+use std::fs::{File, Metadata, OpenOptions};
+use std::io::{BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use chrono::{DateTime, Datelike, Local, Timelike};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+use walkdir::WalkDir;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use super::file_ops::{
+    categorize_io_error, categorize_trash_error, numbered_path, operation_context, write_contents,
+    ConflictPolicy, CopyOutcome, FailedEntry, FailureReason, FileOperationResult, OperationContext,
+    OperationType, OperationWarning, ProcessedFile, ProgressTracker,
+};
+use super::journal::record_operation;
+use super::operation_registry::OperationRegistry;
+use super::preflight::{check_copy_target, PreflightError};
+use super::trash_bin::{finish_result, record_trashed};
+use crate::models::{append_to_encoded, decode_path, encode_path};
+use crate::scanner::{
+    member_path, normalize_member_name, open_tar_stream, read_archive_members, zip_datetime_to_utc,
+    ArchiveKind, ArchiveMemberInfo, ARCHIVE_PATH_SEPARATOR,
+};
+
+/// Supported archive formats
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ArchiveFormat {
+    #[serde(rename = "zip")]
+    Zip,
+    #[serde(rename = "tar.gz")]
+    TarGz,
+}
+
+impl ArchiveFormat {
+    /// Detect the format from an archive file name
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        if name.ends_with(".zip") {
+            Some(Self::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else {
+            None
+        }
+    }
+}
+
+/// Options for archive extraction
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExtractOptions {
+    /// Member names to extract; a folder selects everything below it (default: all)
+    pub members: Option<Vec<String>>,
+
+    /// How to handle files that already exist in the target folder
+    pub conflict_policy: ConflictPolicy,
+}
+
+/// Write selected files and folders into a new ZIP or tar.gz archive
+///
+/// Member names are the paths relative to `base_folder`; paths outside it are
+/// reported as `OutsideBase`. Folders are added recursively and symbolic links
+/// are stored as links. Unreadable files are reported in `failed_files` and left
+/// out of the archive; the archive is removed again if the operation is
+/// cancelled or cannot be completed.
+///
+/// Every archive is recorded in the operation journal. Undoing it reports the
+/// members as not undoable, since the archived files are left unchanged.
+///
+/// Progress events are emitted through the `operation_progress` event channel.
+/// If `operation_id` is given, the operation can be stopped with `cancel_operation`.
+///
+/// # Arguments
+/// * `paths` - Files and folders to archive
+/// * `base_folder` - Folder that member names are relative to
+/// * `archive_path` - Path of the archive to create (must not exist)
+/// * `format` - Archive format (default: detected from `archive_path`)
+/// * `operation_id` - Optional ID used for progress events and cancellation
+/// * `dry_run` - Only validate and list the planned members (default: false)
+///
+/// # Returns
+/// * `Ok(FileOperationResult)` - Result with one processed entry per archived member
+/// * `Err(PreflightError)` - Reason the archive could not be started
+#[tauri::command]
+pub async fn create_archive(
+    app_handle: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    paths: Vec<String>,
+    base_folder: String,
+    archive_path: String,
+    format: Option<ArchiveFormat>,
+    operation_id: Option<String>,
+    dry_run: Option<bool>,
+) -> Result<FileOperationResult, PreflightError> {
+    let context = operation_context(
+        &app_handle,
+        &registry,
+        operation_id,
+        dry_run.unwrap_or(false),
+    );
+    let result = create_archive_from_paths(
+        &paths,
+        &decode_path(&base_folder),
+        &decode_path(&archive_path),
+        format,
+        &context,
+    );
+
+    if let Some(ref id) = context.operation_id {
+        registry.unregister(id, &context.cancel_token);
+    }
+
+    let mut result = result?;
+    if !result.dry_run {
+        record_operation(&app_handle, &mut result);
+    }
+    Ok(result)
+}
+
+/// Extract a ZIP or tar archive (.tar, .tar.gz, .tar.xz, .tar.zst) into a folder
+///
+/// Members are written below `target_folder` under their member names. Names
+/// that would leave the target folder (`..`) and links pointing outside it are
+/// reported as `OutsideBase` and not extracted. Existing files are handled by
+/// the conflict policy; overwritten files are moved to trash first and
+/// journaled as a delete of their own.
+///
+/// Every extraction is recorded in the operation journal; undoing it moves the
+/// extracted files to trash. Files extracted before a cancellation are kept.
+///
+/// Progress events are emitted through the `operation_progress` event channel.
+/// If `operation_id` is given, the operation can be stopped with `cancel_operation`.
+///
+/// # Arguments
+/// * `archive_path` - Archive to extract
+/// * `target_folder` - Folder to extract into (must exist)
+/// * `options` - Member selection and conflict policy (default: all members, skip existing)
+/// * `operation_id` - Optional ID used for progress events and cancellation
+/// * `dry_run` - Only validate and list the planned files (default: false)
+///
+/// # Returns
+/// * `Ok(FileOperationResult)` - Result with one processed entry per extracted file or link
+/// * `Err(PreflightError)` - Reason the extraction could not be started
+#[tauri::command]
+pub async fn extract_archive(
+    app_handle: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    archive_path: String,
+    target_folder: String,
+    options: Option<ExtractOptions>,
+    operation_id: Option<String>,
+    dry_run: Option<bool>,
+) -> Result<FileOperationResult, PreflightError> {
+    let context = operation_context(
+        &app_handle,
+        &registry,
+        operation_id,
+        dry_run.unwrap_or(false),
+    );
+    let mut overwritten = Vec::new();
+    let result = extract_archive_to(
+        &decode_path(&archive_path),
+        &decode_path(&target_folder),
+        &options.unwrap_or_default(),
+        &context,
+        &mut overwritten,
+    );
+
+    if let Some(ref id) = context.operation_id {
+        registry.unregister(id, &context.cancel_token);
+    }
+
+    let mut result = result?;
+    if !result.dry_run {
+        if !overwritten.is_empty() {
+            // Journaled before the extract entry, so undo restores them once
+            // the extracted files are out of the way again
+            let mut trashed = finish_result(
+                OperationType::Delete,
+                Instant::now(),
+                Vec::new(),
+                overwritten,
+                false,
+            );
+            record_trashed(&app_handle, &mut trashed);
+            record_operation(&app_handle, &mut trashed);
+            result.warnings.extend(trashed.warnings);
+            result.warnings.push(OperationWarning {
+                path: target_folder.clone(),
+                message: format!(
+                    "{} overwritten file(s) moved to the trash",
+                    trashed.success_count
+                ),
+            });
+        }
+        record_operation(&app_handle, &mut result);
+    }
+    Ok(result)
+}
+
+/// Create an archive from paths relative to `base`, or only plan it in a dry run
+pub fn create_archive_from_paths(
+    paths: &[String],
+    base: &Path,
+    archive_path: &Path,
+    format: Option<ArchiveFormat>,
+    context: &OperationContext,
+) -> Result<FileOperationResult, PreflightError> {
+    let start_time = Instant::now();
+
+    let format = format
+        .or_else(|| ArchiveFormat::from_path(archive_path))
+        .ok_or_else(|| {
+            PreflightError::new(
+                FailureReason::InvalidName,
+                format!(
+                    "Unknown archive format, use .zip or .tar.gz: {}",
+                    archive_path.display()
+                ),
+            )
+        })?;
+    let archive_path = std::path::absolute(archive_path)
+        .map_err(|e| PreflightError::new(FailureReason::InvalidName, e.to_string()))?;
+    if archive_path.symlink_metadata().is_ok() {
+        return Err(PreflightError::new(
+            FailureReason::FileExists,
+            format!("Archive already exists: {}", archive_path.display()),
+        ));
+    }
+
+    let mut failed_files: Vec<FailedEntry> = Vec::new();
+    let members = collect_members(paths, base, &archive_path, &mut failed_files);
+    let total_bytes: u64 = members
+        .iter()
+        .filter(|member| member.metadata.is_file())
+        .map(|member| member.metadata.len())
+        .sum();
+
+    // The archive is compressed, so its size is only bounded by the input
+    let target_folder = archive_path.parent().unwrap_or(Path::new("."));
+    check_copy_target(target_folder, 0)?;
+
+    let mut tracker = ProgressTracker::new(
+        context,
+        OperationType::Archive,
+        members.len() as u32,
+        total_bytes,
+    );
+    let mut processed_files: Vec<ProcessedFile> = Vec::new();
+    let mut warnings: Vec<OperationWarning> = Vec::new();
+    let mut cancelled = false;
+
+    if context.dry_run {
+        processed_files.extend(
+            members
+                .iter()
+                .map(|member| member.to_processed(&archive_path)),
+        );
+    } else {
+        let mut writer = ArchiveWriter::create(&archive_path, format).map_err(|e| {
+            let (reason, message) = categorize_io_error(&e);
+            PreflightError::new(reason, message)
+        })?;
+
+        for member in &members {
+            if context.cancel_token.is_cancelled() {
+                cancelled = true;
+                break;
+            }
+
+            let path_str = encode_path(&member.path);
+            tracker.start_file(&path_str);
+            match writer.add(member, &mut tracker) {
+                Ok(()) => processed_files.push(member.to_processed(&archive_path)),
+                Err(AddError { error, fatal }) => {
+                    let (reason, message) = categorize_io_error(&error);
+                    failed_files.push(FailedEntry {
+                        path: path_str.clone(),
+                        reason,
+                        error_message: message,
+                    });
+                    if fatal {
+                        warnings.push(OperationWarning {
+                            path: path_str,
+                            message: format!("Archive damaged while adding file: {}", error),
+                        });
+                        break;
+                    }
+                }
+            }
+            tracker.finish_file();
+        }
+
+        let finished = if cancelled || !warnings.is_empty() {
+            // Close the file so it can be removed below, also on Windows
+            drop(writer);
+            Ok(())
+        } else {
+            writer.finish()
+        };
+        if let Err(e) = finished {
+            warnings.push(OperationWarning {
+                path: encode_path(&archive_path),
+                message: format!("Failed to complete archive: {}", e),
+            });
+        }
+        if cancelled || !warnings.is_empty() {
+            // Leave no partial archive behind
+            let _ = std::fs::remove_file(&archive_path);
+            processed_files.clear();
+        }
+    }
+    tracker.report(true);
+
+    Ok(FileOperationResult {
+        operation: OperationType::Archive,
+        success_count: processed_files.len() as u32,
+        failed_count: failed_files.len() as u32,
+        failed_files,
+        duration_ms: start_time.elapsed().as_millis() as u64,
+        cancelled,
+        verified_files: Vec::new(),
+        warnings,
+        processed_files,
+        journal_id: None,
+        dry_run: context.dry_run,
+    })
+}
+
+/// A file, folder or link to be written into an archive
+struct ArchiveMember {
+    path: PathBuf,
+    /// Member name with `/` separators
+    name: String,
+    metadata: Metadata,
+}
+
+impl ArchiveMember {
+    fn to_processed(&self, archive_path: &Path) -> ProcessedFile {
+        ProcessedFile {
+            source_path: encode_path(&self.path),
+            target_path: Some(append_to_encoded(
+                &encode_path(archive_path),
+                &format!("{}{}", ARCHIVE_PATH_SEPARATOR, self.name),
+            )),
+        }
+    }
+}
+
+/// Expand the given paths into archive members named relative to `base`
+///
+/// Members are deduplicated, so selecting a folder and a file inside it is fine.
+fn collect_members(
+    paths: &[String],
+    base: &Path,
+    archive_path: &Path,
+    failed_files: &mut Vec<FailedEntry>,
+) -> Vec<ArchiveMember> {
+    let base = std::path::absolute(base).unwrap_or_else(|_| base.to_path_buf());
+    let mut members = Vec::new();
+    let mut names: HashSet<String> = HashSet::new();
+
+    for path_str in paths {
+        let failed = |reason: FailureReason, error_message: String| FailedEntry {
+            path: path_str.clone(),
+            reason,
+            error_message,
+        };
+
+        let path = decode_path(path_str);
+        let path = std::path::absolute(&path).unwrap_or(path);
+        if path.symlink_metadata().is_err() {
+            failed_files.push(failed(
+                FailureReason::PathNotFound,
+                format!("File not found: {}", path_str),
+            ));
+            continue;
+        }
+        if !path.starts_with(&base) {
+            failed_files.push(failed(
+                FailureReason::OutsideBase,
+                format!("Path is outside the base folder: {}", path_str),
+            ));
+            continue;
+        }
+
+        let walker = WalkDir::new(&path)
+            .follow_links(false)
+            .follow_root_links(false)
+            .sort_by_file_name();
+        for dir_entry in walker {
+            let dir_entry = match dir_entry {
+                Ok(dir_entry) => dir_entry,
+                Err(e) => {
+                    let path = e.path().map_or_else(|| path_str.clone(), encode_path);
+                    let (reason, message) = e
+                        .io_error()
+                        .map_or((FailureReason::Unknown, e.to_string()), categorize_io_error);
+                    failed_files.push(FailedEntry {
+                        path,
+                        reason,
+                        error_message: message,
+                    });
+                    continue;
+                }
+            };
+
+            // Never archive the archive into itself
+            if dir_entry.path() == archive_path {
+                continue;
+            }
+            let Some(name) = member_name(dir_entry.path(), &base) else {
+                continue;
+            };
+            if !names.insert(name.clone()) {
+                continue;
+            }
+            match dir_entry.metadata() {
+                Ok(metadata) => members.push(ArchiveMember {
+                    path: dir_entry.into_path(),
+                    name,
+                    metadata,
+                }),
+                Err(e) => failed_files.push(failed(FailureReason::Unknown, e.to_string())),
+            }
+        }
+    }
+
+    members
+}
+
+/// Relative name of a path below `base`, with `/` separators (None for `base` itself)
+fn member_name(path: &Path, base: &Path) -> Option<String> {
+    let relative = path.strip_prefix(base).ok()?;
+    let name = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    (!name.is_empty()).then_some(name)
+}
+
+/// Failure to add a member to an archive
+struct AddError {
+    error: std::io::Error,
+    /// Part of the member was already written, so the archive is damaged
+    fatal: bool,
+}
+
+impl From<std::io::Error> for AddError {
+    fn from(error: std::io::Error) -> Self {
+        Self {
+            error,
+            fatal: false,
+        }
+    }
+}
+
+impl From<zip::result::ZipError> for AddError {
+    fn from(error: zip::result::ZipError) -> Self {
+        std::io::Error::from(error).into()
+    }
+}
+
+/// Writer for one of the supported archive formats
+enum ArchiveWriter {
+    Zip(ZipWriter<File>),
+    TarGz(tar::Builder<GzEncoder<File>>),
+}
+
+impl ArchiveWriter {
+    fn create(path: &Path, format: ArchiveFormat) -> std::io::Result<Self> {
+        let file = OpenOptions::new().write(true).create_new(true).open(path)?;
+        Ok(match format {
+            ArchiveFormat::Zip => Self::Zip(ZipWriter::new(file)),
+            ArchiveFormat::TarGz => {
+                let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+                builder.follow_symlinks(false);
+                Self::TarGz(builder)
+            }
+        })
+    }
+
+    /// Add a member, streaming file contents through the progress tracker
+    ///
+    /// Everything that can fail for the member alone (opening, stat, reading a
+    /// link) happens before anything is written. A tar member cannot be taken
+    /// back once its header is out, so later failures are fatal.
+    fn add(
+        &mut self,
+        member: &ArchiveMember,
+        tracker: &mut ProgressTracker<'_>,
+    ) -> Result<(), AddError> {
+        let file_type = member.metadata.file_type();
+        match self {
+            Self::Zip(writer) => {
+                let options = zip_options(&member.metadata);
+                if file_type.is_dir() {
+                    writer.add_directory(member.name.as_str(), options)?;
+                } else if file_type.is_symlink() {
+                    let target = std::fs::read_link(&member.path)?;
+                    writer.add_symlink(member.name.as_str(), target.to_string_lossy(), options)?;
+                } else {
+                    let mut reader = ProgressReader {
+                        inner: File::open(&member.path)?,
+                        tracker,
+                    };
+                    writer.start_file(member.name.as_str(), options)?;
+                    if let Err(e) = std::io::copy(&mut reader, writer) {
+                        let _ = writer.abort_file();
+                        return Err(e.into());
+                    }
+                }
+            }
+            Self::TarGz(builder) => {
+                let mut header = tar::Header::new_gnu();
+                let written = if file_type.is_file() {
+                    let file = File::open(&member.path)?;
+                    // Stat the open file, so the header size matches what is read
+                    let metadata = file.metadata()?;
+                    header.set_metadata(&metadata);
+                    let mut reader = ProgressReader {
+                        inner: file,
+                        tracker,
+                    }
+                    .take(metadata.len());
+                    builder
+                        .append_data(&mut header, &member.name, &mut reader)
+                        .and_then(|()| {
+                            if reader.limit() == 0 {
+                                Ok(())
+                            } else {
+                                Err(std::io::Error::new(
+                                    std::io::ErrorKind::UnexpectedEof,
+                                    "File shrank while it was archived",
+                                ))
+                            }
+                        })
+                } else if file_type.is_symlink() {
+                    let target = std::fs::read_link(&member.path)?;
+                    header.set_metadata(&member.metadata);
+                    builder.append_link(&mut header, &member.name, target)
+                } else {
+                    header.set_metadata(&member.metadata);
+                    builder.append_data(&mut header, &member.name, std::io::empty())
+                };
+                written.map_err(|error| AddError { error, fatal: true })?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> std::io::Result<()> {
+        match self {
+            Self::Zip(writer) => writer.finish().map(|_| ()).map_err(std::io::Error::from),
+            Self::TarGz(builder) => builder.into_inner()?.finish().map(|_| ()),
+        }
+    }
+}
+
+/// Deflate options carrying the member's modification time and permissions
+fn zip_options(metadata: &Metadata) -> SimpleFileOptions {
+    let mut options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .large_file(metadata.len() >= u64::from(u32::MAX));
+
+    if let Some(modified) = metadata.modified().ok().map(DateTime::<Local>::from) {
+        let time = zip::DateTime::from_date_and_time(
+            u16::try_from(modified.year()).unwrap_or(0),
+            modified.month() as u8,
+            modified.day() as u8,
+            modified.hour() as u8,
+            modified.minute() as u8,
+            modified.second() as u8,
+        );
+        // ZIP cannot store times before 1980; those keep the default
+        if let Ok(time) = time {
+            options = options.last_modified_time(time);
+        }
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        options = options.unix_permissions(metadata.permissions().mode());
+    }
+
+    options
+}
+
+/// Reader that reports the bytes it reads to a progress tracker
+struct ProgressReader<'a, 'b> {
+    inner: File,
+    tracker: &'a mut ProgressTracker<'b>,
+}
+
+impl Read for ProgressReader<'_, '_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.tracker.add_bytes(read as u64);
+        Ok(read)
+    }
+}
+
+/// Extract an archive below `target`, or only plan it in a dry run
+///
+/// Existing files trashed to make room are added to `overwritten`.
+pub fn extract_archive_to(
+    archive_path: &Path,
+    target: &Path,
+    options: &ExtractOptions,
+    context: &OperationContext,
+    overwritten: &mut Vec<ProcessedFile>,
+) -> Result<FileOperationResult, PreflightError> {
+    let start_time = Instant::now();
+
+    let kind = ArchiveKind::from_path(archive_path).ok_or_else(|| {
+        PreflightError::new(
+            FailureReason::InvalidName,
+            format!(
+                "Unknown archive format, use .zip, .tar, .tar.gz, .tar.xz or .tar.zst: {}",
+                archive_path.display()
+            ),
+        )
+    })?;
+    if !archive_path.is_file() {
+        return Err(PreflightError::new(
+            FailureReason::PathNotFound,
+            format!("Archive not found: {}", archive_path.display()),
+        ));
+    }
+    let listing = read_archive_members(archive_path)
+        .map_err(|message| PreflightError::new(FailureReason::Unknown, message))?;
+
+    let mut failed_files: Vec<FailedEntry> = Vec::new();
+    let selection = options
+        .members
+        .as_ref()
+        .map(|names| select_members(archive_path, names, &listing, &mut failed_files));
+    let selected: Vec<&ArchiveMemberInfo> = listing
+        .iter()
+        .filter(|member| !member.is_directory && is_selected(&member.name, selection.as_deref()))
+        .collect();
+    let total_bytes: u64 = selected.iter().map(|member| member.size).sum();
+
+    check_copy_target(target, total_bytes)?;
+    let target = target.canonicalize().map_err(|e| {
+        let (reason, message) = categorize_io_error(&e);
+        PreflightError::new(reason, message)
+    })?;
+
+    let mut extractor = Extractor {
+        archive_path,
+        target: &target,
+        conflict_policy: options.conflict_policy,
+        selection,
+        context,
+        tracker: ProgressTracker::new(
+            context,
+            OperationType::Extract,
+            selected.len() as u32,
+            total_bytes,
+        ),
+        planned: HashSet::new(),
+        links: HashSet::new(),
+        link_paths: HashSet::new(),
+        processed_files: Vec::new(),
+        failed_files,
+        overwritten,
+        cancelled: false,
+    };
+
+    let extracted = File::open(archive_path).and_then(|file| match kind {
+        ArchiveKind::Zip => extractor.extract_zip(file),
+        _ => extractor.extract_tar(open_tar_stream(file, kind)?),
+    });
+    let mut warnings: Vec<OperationWarning> = Vec::new();
+    if let Err(e) = extracted {
+        warnings.push(OperationWarning {
+            path: encode_path(archive_path),
+            message: format!("Failed to read archive: {}", e),
+        });
+    }
+    extractor.tracker.report(true);
+
+    let Extractor {
+        processed_files,
+        failed_files,
+        cancelled,
+        ..
+    } = extractor;
+    Ok(FileOperationResult {
+        operation: OperationType::Extract,
+        success_count: processed_files.len() as u32,
+        failed_count: failed_files.len() as u32,
+        failed_files,
+        duration_ms: start_time.elapsed().as_millis() as u64,
+        cancelled,
+        verified_files: Vec::new(),
+        warnings,
+        processed_files,
+        journal_id: None,
+        dry_run: context.dry_run,
+    })
+}
+
+/// Normalize the selected member names, reporting names that match no member
+fn select_members(
+    archive_path: &Path,
+    names: &[String],
+    listing: &[ArchiveMemberInfo],
+    failed_files: &mut Vec<FailedEntry>,
+) -> Vec<String> {
+    let mut selection = Vec::new();
+    for raw_name in names {
+        let path = member_path(&encode_path(archive_path), raw_name);
+        let Some(name) = normalize_member_name(raw_name) else {
+            failed_files.push(FailedEntry {
+                error_message: format!("Invalid member name: {}", raw_name),
+                path,
+                reason: FailureReason::InvalidName,
+            });
+            continue;
+        };
+        let selected = std::slice::from_ref(&name);
+        if !listing
+            .iter()
+            .any(|member| is_selected(&member.name, Some(selected)))
+        {
+            failed_files.push(FailedEntry {
+                error_message: format!("Member not found in archive: {}", name),
+                path,
+                reason: FailureReason::PathNotFound,
+            });
+            continue;
+        }
+        selection.push(name);
+    }
+    selection
+}
+
+/// Whether a member is selected, directly or through one of its folders
+fn is_selected(name: &str, selection: Option<&[String]>) -> bool {
+    let Some(selection) = selection else {
+        return true;
+    };
+    selection.iter().any(|selected| {
+        name == selected
+            || name
+                .strip_prefix(selected.as_str())
+                .is_some_and(|rest| rest.starts_with('/'))
+    })
+}
+
+/// Kind of an archive member being extracted
+enum MemberKind {
+    File,
+    Directory,
+    /// Symbolic link with its target
+    Symlink(PathBuf),
+    /// Hard link to another member name
+    HardLink(String),
+}
+
+/// Header information of an archive member being extracted
+struct MemberHeader {
+    /// Member name as stored in the archive, encoded like a path
+    raw_name: String,
+    kind: MemberKind,
+    modified: Option<SystemTime>,
+    mode: Option<u32>,
+}
+
+/// State of a running extraction
+struct Extractor<'a> {
+    archive_path: &'a Path,
+    /// Canonical target folder
+    target: &'a Path,
+    conflict_policy: ConflictPolicy,
+    selection: Option<Vec<String>>,
+    context: &'a OperationContext,
+    tracker: ProgressTracker<'a>,
+    /// Destinations claimed by this extraction (used to detect conflicts in a dry run)
+    planned: HashSet<PathBuf>,
+    /// Symbolic links created (or planned) by this extraction
+    links: HashSet<PathBuf>,
+    /// Paths passed by the targets of links extracted so far
+    link_paths: HashSet<PathBuf>,
+    processed_files: Vec<ProcessedFile>,
+    failed_files: Vec<FailedEntry>,
+    /// Existing files trashed to make room
+    overwritten: &'a mut Vec<ProcessedFile>,
+    cancelled: bool,
+}
+
+impl Extractor<'_> {
+    fn extract_zip(&mut self, file: File) -> std::io::Result<()> {
+        let mut zip = ZipArchive::new(BufReader::new(file))?;
+        for index in 0..zip.len() {
+            if self.check_cancelled() {
+                break;
+            }
+
+            let raw_name = zip.name_for_index(index).unwrap_or_default().to_string();
+            let mut member = match zip.by_index(index) {
+                Ok(member) => member,
+                Err(e) => {
+                    self.fail(&raw_name, FailureReason::Unknown, e.to_string());
+                    continue;
+                }
+            };
+            let kind = if member.is_dir() {
+                MemberKind::Directory
+            } else if member.is_symlink() {
+                match zip_link_target(&mut member) {
+                    Ok(link) => MemberKind::Symlink(link),
+                    Err(e) => {
+                        let (reason, message) = categorize_io_error(&e);
+                        self.fail(&raw_name, reason, message);
+                        continue;
+                    }
+                }
+            } else {
+                MemberKind::File
+            };
+            let header = MemberHeader {
+                raw_name,
+                kind,
+                modified: member
+                    .last_modified()
+                    .and_then(zip_datetime_to_utc)
+                    .map(SystemTime::from),
+                mode: member.unix_mode(),
+            };
+            self.extract_member(&header, &mut member);
+        }
+        Ok(())
+    }
+
+    fn extract_tar(&mut self, reader: Box<dyn Read>) -> std::io::Result<()> {
+        let mut archive = tar::Archive::new(reader);
+        for entry in archive.entries()? {
+            if self.check_cancelled() {
+                break;
+            }
+
+            let mut entry = entry?;
+            let entry_type = entry.header().entry_type();
+            let link_name = || -> std::io::Result<PathBuf> {
+                Ok(entry.link_name()?.unwrap_or_default().into_owned())
+            };
+            let kind = if entry_type.is_dir() {
+                MemberKind::Directory
+            } else if entry_type.is_symlink() {
+                MemberKind::Symlink(link_name()?)
+            } else if entry_type.is_hard_link() {
+                MemberKind::HardLink(encode_path(&link_name()?))
+            } else if entry_type.is_file() {
+                MemberKind::File
+            } else {
+                continue;
+            };
+            let header = MemberHeader {
+                raw_name: encode_path(&entry.path()?),
+                kind,
+                modified: entry
+                    .header()
+                    .mtime()
+                    .ok()
+                    .map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs)),
+                mode: entry.header().mode().ok(),
+            };
+            self.extract_member(&header, &mut entry);
+        }
+        Ok(())
+    }
+
+    fn check_cancelled(&mut self) -> bool {
+        if self.context.cancel_token.is_cancelled() {
+            self.cancelled = true;
+        }
+        self.cancelled
+    }
+
+    fn fail(&mut self, name: &str, reason: FailureReason, error_message: String) {
+        self.failed_files.push(FailedEntry {
+            path: self.member_path(name),
+            reason,
+            error_message,
+        });
+    }
+
+    fn member_path(&self, name: &str) -> String {
+        member_path(&encode_path(self.archive_path), name)
+    }
+
+    /// Extract (or plan) a single member if it is selected
+    fn extract_member(&mut self, header: &MemberHeader, reader: &mut dyn Read) {
+        let Some(name) = normalize_member_name(&header.raw_name) else {
+            if self.selection.is_none() {
+                self.fail(
+                    &header.raw_name,
+                    FailureReason::OutsideBase,
+                    format!("Member path leaves the target folder: {}", header.raw_name),
+                );
+            }
+            return;
+        };
+        if !is_selected(&name, self.selection.as_deref()) {
+            return;
+        }
+
+        let dest = self.target.join(decode_path(&name));
+        if let MemberKind::Directory = header.kind {
+            if !self.context.dry_run {
+                if let Err((reason, message)) = self.create_folder(&dest) {
+                    self.fail(&name, reason, message);
+                }
+            }
+            return;
+        }
+
+        let source = self.member_path(&name);
+        self.tracker.start_file(&source);
+        match self.extract_file(header, reader, &source, &dest) {
+            Ok(CopyOutcome::Completed) => {}
+            Ok(CopyOutcome::Cancelled) => self.cancelled = true,
+            Err(failed) => self.failed_files.push(failed),
+        }
+        self.tracker.finish_file();
+    }
+
+    fn extract_file(
+        &mut self,
+        header: &MemberHeader,
+        reader: &mut dyn Read,
+        source: &str,
+        dest: &Path,
+    ) -> Result<CopyOutcome, FailedEntry> {
+        let failed = |reason: FailureReason, error_message: String| FailedEntry {
+            path: source.to_string(),
+            reason,
+            error_message,
+        };
+        let io_failed = |e: std::io::Error| {
+            let (reason, message) = categorize_io_error(&e);
+            failed(reason, message)
+        };
+
+        // Links must not give access to anything outside the target folder
+        let outside = |link: &Path| {
+            failed(
+                FailureReason::OutsideBase,
+                format!(
+                    "Link points outside the target folder or through another link: {}",
+                    link.display()
+                ),
+            )
+        };
+        let (link_source, passed) = match &header.kind {
+            MemberKind::Symlink(link) => {
+                let folder = dest.parent().unwrap_or(self.target);
+                let passed = self
+                    .resolve_link(folder, link)
+                    .ok_or_else(|| outside(link))?;
+                (None, passed)
+            }
+            MemberKind::HardLink(link) => {
+                let name = normalize_member_name(link);
+                let passed = name
+                    .as_ref()
+                    .and_then(|name| self.resolve_link(self.target, &decode_path(name)))
+                    .ok_or_else(|| outside(&decode_path(link)))?;
+                (
+                    name.map(|name| self.target.join(decode_path(&name))),
+                    passed,
+                )
+            }
+            _ => (None, Vec::new()),
+        };
+
+        let dest = self.resolve_conflict(dest, source)?;
+        if let MemberKind::Symlink(link) = &header.kind {
+            // A link extracted earlier was checked on the assumption that this
+            // path is no link
+            if self.link_paths.contains(&dest) {
+                return Err(outside(link));
+            }
+            self.links.insert(dest.clone());
+        }
+        self.link_paths.extend(passed);
+        let dest_str = encode_path(&dest);
+        if self.context.dry_run {
+            self.planned.insert(dest);
+            self.processed_files.push(ProcessedFile {
+                source_path: source.to_string(),
+                target_path: Some(dest_str),
+            });
+            return Ok(CopyOutcome::Completed);
+        }
+
+        let folder = dest.parent().unwrap_or(self.target);
+        self.create_folder(folder)
+            .map_err(|(reason, message)| failed(reason, message))?;
+        // Existing links in the target folder must not redirect the write either
+        let real_folder = folder.canonicalize().map_err(io_failed)?;
+        if !real_folder.starts_with(self.target) {
+            return Err(failed(
+                FailureReason::OutsideBase,
+                format!("Path leaves the target folder: {}", dest.display()),
+            ));
+        }
+        if dest.symlink_metadata().is_ok() {
+            trash::delete(&dest).map_err(|e| {
+                let (reason, message) = categorize_trash_error(&e);
+                failed(reason, message)
+            })?;
+            self.overwritten.push(ProcessedFile {
+                source_path: dest_str.clone(),
+                target_path: None,
+            });
+        }
+
+        match (&header.kind, link_source) {
+            (MemberKind::Symlink(link), _) => create_symlink(link, &dest).map_err(io_failed)?,
+            (MemberKind::HardLink(_), Some(link_source)) => {
+                std::fs::hard_link(link_source, &dest).map_err(io_failed)?;
+            }
+            _ => {
+                let mut file = OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(&dest)
+                    .map_err(io_failed)?;
+                let outcome = write_contents(reader, &mut file, &mut self.tracker, None);
+                if !matches!(outcome, Ok(CopyOutcome::Completed)) {
+                    // Leave no partial file behind
+                    drop(file);
+                    let _ = std::fs::remove_file(&dest);
+                    return outcome.map_err(io_failed);
+                }
+                restore_metadata(&file, header);
+            }
+        }
+
+        self.planned.insert(dest);
+        self.processed_files.push(ProcessedFile {
+            source_path: source.to_string(),
+            target_path: Some(dest_str),
+        });
+        Ok(CopyOutcome::Completed)
+    }
+
+    /// Create a folder below the target folder one component at a time
+    ///
+    /// Components that are symbolic links are refused instead of followed, so
+    /// links in the target folder cannot move the extraction outside of it.
+    fn create_folder(&self, folder: &Path) -> Result<(), (FailureReason, String)> {
+        let relative = folder.strip_prefix(self.target).map_err(|_| {
+            (
+                FailureReason::OutsideBase,
+                format!("Path leaves the target folder: {}", folder.display()),
+            )
+        })?;
+
+        let mut current = self.target.to_path_buf();
+        for component in relative.components() {
+            current.push(component);
+            match current.symlink_metadata() {
+                Ok(metadata) if metadata.file_type().is_symlink() => {
+                    return Err((
+                        FailureReason::OutsideBase,
+                        format!("Path passes through a link: {}", current.display()),
+                    ));
+                }
+                Ok(metadata) if metadata.is_dir() => {}
+                Ok(_) => {
+                    return Err((
+                        FailureReason::FileExists,
+                        format!("Not a folder: {}", current.display()),
+                    ));
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    std::fs::create_dir(&current).map_err(|e| categorize_io_error(&e))?;
+                }
+                Err(e) => return Err(categorize_io_error(&e)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolve a relative link target below `folder`, without following links
+    ///
+    /// The resolution is lexical, which only holds while nothing on the way is
+    /// a symbolic link, so links on disk or extracted earlier are refused.
+    /// Returns every path passed, or None if the target leaves the target folder.
+    fn resolve_link(&self, folder: &Path, link: &Path) -> Option<Vec<PathBuf>> {
+        let is_link = |path: &Path| {
+            self.links.contains(path) || path.symlink_metadata().is_ok_and(|m| m.is_symlink())
+        };
+
+        let mut resolved = self.target.to_path_buf();
+        let mut passed = Vec::new();
+        let components = folder.strip_prefix(self.target).ok()?.components();
+        for component in components.chain(link.components()) {
+            match component {
+                Component::Normal(part) => {
+                    resolved.push(part);
+                    if is_link(&resolved) {
+                        return None;
+                    }
+                    passed.push(resolved.clone());
+                }
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    if !resolved.pop() {
+                        return None;
+                    }
+                }
+                Component::RootDir | Component::Prefix(_) => return None,
+            }
+        }
+        resolved.starts_with(self.target).then_some(passed)
+    }
+
+    /// Apply the conflict policy to a destination that is already taken
+    fn resolve_conflict(&self, dest: &Path, source: &str) -> Result<PathBuf, FailedEntry> {
+        let taken = |path: &Path| self.planned.contains(path) || path.symlink_metadata().is_ok();
+        if !taken(dest) {
+            return Ok(dest.to_path_buf());
+        }
+
+        match self.conflict_policy {
+            ConflictPolicy::Skip => Err(FailedEntry {
+                path: source.to_string(),
+                reason: FailureReason::FileExists,
+                error_message: format!("File already exists: {}", dest.display()),
+            }),
+            ConflictPolicy::Overwrite => Ok(dest.to_path_buf()),
+            ConflictPolicy::Rename => Ok(numbered_path(dest, taken)),
+        }
+    }
+}
+
+/// Read the target of a ZIP symbolic link, which is stored as its contents
+fn zip_link_target(member: &mut impl Read) -> std::io::Result<PathBuf> {
+    let mut bytes = Vec::new();
+    member.read_to_end(&mut bytes)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+        Ok(PathBuf::from(std::ffi::OsString::from_vec(bytes)))
+    }
+    #[cfg(not(unix))]
+    String::from_utf8(bytes)
+        .map(PathBuf::from)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(unix)]
+fn create_symlink(link: &Path, dest: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(link, dest)
+}
+
+#[cfg(windows)]
+fn create_symlink(link: &Path, dest: &Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_file(link, dest)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn create_symlink(_link: &Path, _dest: &Path) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "Symbolic links are not supported on this platform",
+    ))
+}
+
+/// Restore the stored modification time and permissions (best effort)
+fn restore_metadata(file: &File, header: &MemberHeader) {
+    if let Some(modified) = header.modified {
+        let _ = file.set_modified(modified);
+    }
+
+    #[cfg(unix)]
+    if let Some(mode) = header.mode {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = file.set_permissions(std::fs::Permissions::from_mode(mode & 0o777));
+    }
+    #[cfg(not(unix))]
+    let _ = header.mode;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_tree(dir: &Path) -> PathBuf {
+        let base = dir.join("project");
+        std::fs::create_dir_all(base.join("docs").join("empty")).unwrap();
+        std::fs::write(base.join("readme.txt"), "readme").unwrap();
+        std::fs::write(base.join("docs").join("a.pdf"), "pdf data").unwrap();
+        base
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            ArchiveFormat::from_path(Path::new("backup.ZIP")),
+            Some(ArchiveFormat::Zip)
+        );
+        assert_eq!(
+            ArchiveFormat::from_path(Path::new("backup.tgz")),
+            Some(ArchiveFormat::TarGz)
+        );
+        assert_eq!(ArchiveFormat::from_path(Path::new("backup.rar")), None);
+    }
+
+    #[test]
+    fn test_create_zip() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = create_tree(temp_dir.path());
+        let archive_path = temp_dir.path().join("out.zip");
+
+        let paths = vec![
+            base.join("docs").to_string_lossy().to_string(),
+            base.join("readme.txt").to_string_lossy().to_string(),
+        ];
+        let result =
+            create_archive_from_paths(&paths, &base, &archive_path, None, &OperationContext::new())
+                .unwrap();
+
+        // docs, docs/a.pdf, docs/empty, readme.txt
+        assert_eq!(result.success_count, 4);
+        assert!(result.processed_files.iter().any(|file| file
+            .target_path
+            .as_deref()
+            .unwrap()
+            .ends_with("out.zip!/docs/a.pdf")));
+
+        let mut archive = zip::ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+        let mut content = String::new();
+        archive
+            .by_name("docs/a.pdf")
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "pdf data");
+        assert!(archive.by_name("docs/empty/").is_ok());
+    }
+
+    #[test]
+    fn test_create_tar_gz() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = create_tree(temp_dir.path());
+        let archive_path = temp_dir.path().join("out.tar.gz");
+
+        let paths = vec![base.to_string_lossy().to_string()];
+        let result =
+            create_archive_from_paths(&paths, &base, &archive_path, None, &OperationContext::new())
+                .unwrap();
+        assert_eq!(result.failed_count, 0);
+
+        let file = File::open(&archive_path).unwrap();
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(file));
+        let names: Vec<String> = archive
+            .entries()
+            .unwrap()
+            .map(|entry| entry.unwrap().path().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert!(names.contains(&"docs/a.pdf".to_string()));
+        assert!(names.contains(&"readme.txt".to_string()));
+    }
+
+    #[test]
+    fn test_outside_base_and_existing_archive() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = create_tree(temp_dir.path());
+        let outside = temp_dir.path().join("outside.txt");
+        std::fs::write(&outside, "outside").unwrap();
+        let archive_path = temp_dir.path().join("out.zip");
+
+        let paths = vec![outside.to_string_lossy().to_string()];
+        let result =
+            create_archive_from_paths(&paths, &base, &archive_path, None, &OperationContext::new())
+                .unwrap();
+        assert_eq!(result.failed_files[0].reason, FailureReason::OutsideBase);
+
+        let existing =
+            create_archive_from_paths(&paths, &base, &archive_path, None, &OperationContext::new());
+        assert_eq!(existing.unwrap_err().reason, FailureReason::FileExists);
+    }
+
+    #[test]
+    fn test_create_archive_dry_run() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = create_tree(temp_dir.path());
+        let archive_path = temp_dir.path().join("out.tar.gz");
+
+        let context = OperationContext {
+            dry_run: true,
+            ..OperationContext::default()
+        };
+        let paths = vec![base.join("readme.txt").to_string_lossy().to_string()];
+        let result =
+            create_archive_from_paths(&paths, &base, &archive_path, None, &context).unwrap();
+
+        assert_eq!(result.success_count, 1);
+        assert!(!archive_path.exists());
+    }
+
+    fn extract_options(
+        members: Option<&[&str]>,
+        conflict_policy: ConflictPolicy,
+    ) -> ExtractOptions {
+        ExtractOptions {
+            members: members.map(|names| names.iter().map(ToString::to_string).collect()),
+            conflict_policy,
+        }
+    }
+
+    #[test]
+    fn test_extract_zip() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = create_tree(temp_dir.path());
+        let archive_path = temp_dir.path().join("out.zip");
+        let paths = vec![base.to_string_lossy().to_string()];
+        create_archive_from_paths(&paths, &base, &archive_path, None, &OperationContext::new())
+            .unwrap();
+
+        let target = temp_dir.path().join("extracted");
+        std::fs::create_dir(&target).unwrap();
+        let options = extract_options(None, ConflictPolicy::Skip);
+        let result = extract_archive_to(
+            &archive_path,
+            &target,
+            &options,
+            &OperationContext::new(),
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        // docs/a.pdf and readme.txt; folders are created but not listed
+        assert_eq!(result.success_count, 2);
+        assert_eq!(result.failed_count, 0);
+        assert_eq!(
+            std::fs::read_to_string(target.join("docs").join("a.pdf")).unwrap(),
+            "pdf data"
+        );
+        assert!(target.join("docs").join("empty").is_dir());
+
+        // Extracting again runs into the existing files
+        let result = extract_archive_to(
+            &archive_path,
+            &target,
+            &options,
+            &OperationContext::new(),
+            &mut Vec::new(),
+        )
+        .unwrap();
+        assert_eq!(result.failed_count, 2);
+        assert_eq!(result.failed_files[0].reason, FailureReason::FileExists);
+
+        let options = extract_options(Some(&["readme.txt"]), ConflictPolicy::Rename);
+        let result = extract_archive_to(
+            &archive_path,
+            &target,
+            &options,
+            &OperationContext::new(),
+            &mut Vec::new(),
+        )
+        .unwrap();
+        assert_eq!(result.success_count, 1);
+        assert!(target.join("readme (1).txt").exists());
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    fn test_extract_overwrite_reports_trashed_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = create_tree(temp_dir.path());
+        let archive_path = temp_dir.path().join("out.zip");
+        let paths = vec![base.join("readme.txt").to_string_lossy().to_string()];
+        create_archive_from_paths(&paths, &base, &archive_path, None, &OperationContext::new())
+            .unwrap();
+        let target = temp_dir.path().join("extracted");
+        std::fs::create_dir(&target).unwrap();
+        std::fs::write(target.join("readme.txt"), "changed").unwrap();
+
+        let mut overwritten = Vec::new();
+        let result = extract_archive_to(
+            &archive_path,
+            &target,
+            &extract_options(None, ConflictPolicy::Overwrite),
+            &OperationContext::new(),
+            &mut overwritten,
+        )
+        .unwrap();
+
+        assert_eq!(result.success_count, 1);
+        assert_eq!(
+            std::fs::read(target.join("readme.txt")).unwrap(),
+            std::fs::read(base.join("readme.txt")).unwrap()
+        );
+        assert_eq!(overwritten.len(), 1);
+        assert_eq!(
+            overwritten[0].source_path,
+            target.join("readme.txt").to_string_lossy()
+        );
+    }
+
+    #[test]
+    fn test_extract_tar_xz_selection() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive_path = temp_dir.path().join("data.tar.xz");
+        let encoder = xz2::write::XzEncoder::new(File::create(&archive_path).unwrap(), 6);
+        let mut builder = tar::Builder::new(encoder);
+        for name in ["docs/a.pdf", "docs/b.txt", "docsx/c.txt", "other.txt"] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(4);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, &b"data"[..])
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let target = temp_dir.path().join("extracted");
+        std::fs::create_dir(&target).unwrap();
+        let options = extract_options(Some(&["./docs/", "missing"]), ConflictPolicy::Skip);
+        let result = extract_archive_to(
+            &archive_path,
+            &target,
+            &options,
+            &OperationContext::new(),
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        assert_eq!(result.success_count, 2);
+        assert_eq!(result.failed_files[0].reason, FailureReason::PathNotFound);
+        assert!(target.join("docs").join("b.txt").exists());
+        assert!(!target.join("docsx").exists());
+        assert!(!target.join("other.txt").exists());
+    }
+
+    #[test]
+    fn test_extract_rejects_paths_outside_target() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive_path = temp_dir.path().join("evil.zip");
+        let mut zip = ZipWriter::new(File::create(&archive_path).unwrap());
+        zip.start_file("../evil.txt", SimpleFileOptions::default())
+            .unwrap();
+        std::io::Write::write_all(&mut zip, b"evil").unwrap();
+        zip.add_symlink("link", "../../outside", SimpleFileOptions::default())
+            .unwrap();
+        zip.add_symlink("inner", "docs/a.pdf", SimpleFileOptions::default())
+            .unwrap();
+        zip.finish().unwrap();
+
+        let target = temp_dir.path().join("extracted");
+        std::fs::create_dir(&target).unwrap();
+        let options = ExtractOptions::default();
+        let result = extract_archive_to(
+            &archive_path,
+            &target,
+            &options,
+            &OperationContext::new(),
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        assert_eq!(result.failed_count, 2);
+        assert!(result
+            .failed_files
+            .iter()
+            .all(|file| file.reason == FailureReason::OutsideBase));
+        assert!(!temp_dir.path().join("evil.txt").exists());
+        assert!(target.join("link").symlink_metadata().is_err());
+        #[cfg(unix)]
+        assert!(target.join("inner").symlink_metadata().is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_extract_rejects_chained_links() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive_path = temp_dir.path().join("chained.tar");
+        let mut builder = tar::Builder::new(File::create(&archive_path).unwrap());
+        let mut append = |entry_type: tar::EntryType, name: &str, link: &str| {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(entry_type);
+            header.set_size(0);
+            builder.append_link(&mut header, name, link).unwrap();
+        };
+        // Each link stays inside on its own, but not once `up` is followed
+        append(tar::EntryType::Symlink, "a/b/up", "..");
+        append(tar::EntryType::Symlink, "esc", "a/b/up/../../..");
+        append(tar::EntryType::Link, "hard", "a/b/up/b/up");
+        // The same chain with the link that is passed extracted last
+        append(tar::EntryType::Symlink, "late", "c/d/up/../../..");
+        append(tar::EntryType::Symlink, "c/d/up", "..");
+        builder.finish().unwrap();
+        drop(builder);
+
+        let target = temp_dir.path().join("extracted");
+        std::fs::create_dir(&target).unwrap();
+        let options = ExtractOptions::default();
+        let result = extract_archive_to(
+            &archive_path,
+            &target,
+            &options,
+            &OperationContext::new(),
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        assert_eq!(result.success_count, 2);
+        assert_eq!(result.failed_count, 3);
+        assert!(result
+            .failed_files
+            .iter()
+            .all(|file| file.reason == FailureReason::OutsideBase));
+        assert!(target.join("a/b/up").symlink_metadata().is_ok());
+        assert!(target.join("late").symlink_metadata().is_ok());
+        assert!(target.join("esc").symlink_metadata().is_err());
+        assert!(target.join("hard").symlink_metadata().is_err());
+        assert!(target.join("c/d/up").symlink_metadata().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_extract_refuses_folders_through_links() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive_path = temp_dir.path().join("through.tar");
+        let mut builder = tar::Builder::new(File::create(&archive_path).unwrap());
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Directory);
+        header.set_size(0);
+        builder
+            .append_data(&mut header, "link/sub/", std::io::empty())
+            .unwrap();
+        let mut header = tar::Header::new_gnu();
+        header.set_size(4);
+        builder
+            .append_data(&mut header, "link/deep/x.txt", &b"data"[..])
+            .unwrap();
+        builder.finish().unwrap();
+        drop(builder);
+
+        let outside = temp_dir.path().join("outside");
+        let target = temp_dir.path().join("extracted");
+        std::fs::create_dir(&outside).unwrap();
+        std::fs::create_dir(&target).unwrap();
+        std::os::unix::fs::symlink(&outside, target.join("link")).unwrap();
+
+        let result = extract_archive_to(
+            &archive_path,
+            &target,
+            &ExtractOptions::default(),
+            &OperationContext::new(),
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        assert_eq!(result.success_count, 0);
+        assert_eq!(result.failed_count, 2);
+        assert!(result
+            .failed_files
+            .iter()
+            .all(|file| file.reason == FailureReason::OutsideBase));
+        assert_eq!(std::fs::read_dir(&outside).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_extract_dry_run() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = create_tree(temp_dir.path());
+        let archive_path = temp_dir.path().join("out.tar.gz");
+        let paths = vec![base.to_string_lossy().to_string()];
+        create_archive_from_paths(&paths, &base, &archive_path, None, &OperationContext::new())
+            .unwrap();
+
+        let context = OperationContext {
+            dry_run: true,
+            ..OperationContext::default()
+        };
+        let options = extract_options(None, ConflictPolicy::Skip);
+        let result =
+            extract_archive_to(&archive_path, &base, &options, &context, &mut Vec::new()).unwrap();
+
+        // Both files already exist in the folder they were archived from
+        assert_eq!(result.success_count, 0);
+        assert_eq!(result.failed_count, 2);
+
+        let missing = extract_archive_to(
+            &temp_dir.path().join("missing.zip"),
+            &base,
+            &options,
+            &context,
+            &mut Vec::new(),
+        );
+        assert_eq!(missing.unwrap_err().reason, FailureReason::PathNotFound);
+    }
+}
@@ -0,0 +1,164 @@
+//! Preservation of file metadata when copying
+
+use std::fs::{FileTimes, Metadata, OpenOptions};
+use std::path::Path;
+
+use super::file_ops::CopyOptions;
+
+/// Copy the requested metadata from the source to a freshly written target
+///
+/// Attributes that cannot be preserved do not fail the copy; a warning
+/// message is returned for each of them instead.
+pub(super) fn preserve_attributes(
+    source: &Path,
+    dest: &Path,
+    source_metadata: &Metadata,
+    options: &CopyOptions,
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    // Ownership first: changing the owner may clear setuid/setgid bits
+    if options.preserve_ownership {
+        if let Err(e) = copy_ownership(dest, source_metadata) {
+            warnings.push(format!("Could not preserve ownership: {}", e));
+        }
+    }
+
+    if options.preserve_xattrs {
+        warnings.extend(copy_xattrs(source, dest));
+    }
+
+    if options.preserve_timestamps {
+        if let Err(e) = copy_timestamps(dest, source_metadata) {
+            warnings.push(format!("Could not preserve timestamps: {}", e));
+        }
+    }
+
+    // Permissions last, since a read-only target can no longer be modified
+    if options.preserve_permissions {
+        if let Err(e) = std::fs::set_permissions(dest, source_metadata.permissions()) {
+            warnings.push(format!("Could not preserve permissions: {}", e));
+        }
+    }
+
+    warnings
+}
+
+/// Set modification and access times of the target to those of the source
+fn copy_timestamps(dest: &Path, source_metadata: &Metadata) -> std::io::Result<()> {
+    let mut times = FileTimes::new();
+    if let Ok(modified) = source_metadata.modified() {
+        times = times.set_modified(modified);
+    }
+    if let Ok(accessed) = source_metadata.accessed() {
+        times = times.set_accessed(accessed);
+    }
+
+    OpenOptions::new().write(true).open(dest)?.set_times(times)
+}
+
+#[cfg(unix)]
+fn copy_ownership(dest: &Path, source_metadata: &Metadata) -> std::io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    std::os::unix::fs::chown(
+        dest,
+        Some(source_metadata.uid()),
+        Some(source_metadata.gid()),
+    )
+}
+
+#[cfg(not(unix))]
+fn copy_ownership(_dest: &Path, _source_metadata: &Metadata) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "not supported on this platform",
+    ))
+}
+
+/// Copy extended attributes, returning a warning for each one that failed
+#[cfg(unix)]
+fn copy_xattrs(source: &Path, dest: &Path) -> Vec<String> {
+    let names = match xattr::list(source) {
+        Ok(names) => names,
+        Err(e) => return vec![format!("Could not read extended attributes: {}", e)],
+    };
+
+    let mut warnings = Vec::new();
+    for name in names {
+        let result = xattr::get(source, &name).and_then(|value| match value {
+            Some(value) => xattr::set(dest, &name, &value),
+            None => Ok(()),
+        });
+        if let Err(e) = result {
+            warnings.push(format!(
+                "Could not preserve extended attribute {}: {}",
+                name.to_string_lossy(),
+                e
+            ));
+        }
+    }
+    warnings
+}
+
+#[cfg(not(unix))]
+fn copy_xattrs(_source: &Path, _dest: &Path) -> Vec<String> {
+    vec!["Could not preserve extended attributes: not supported on this platform".to_string()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_preserve_timestamps() {
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("photo.jpg");
+        let dest = dir.path().join("copy.jpg");
+        std::fs::write(&source, "source").unwrap();
+        std::fs::write(&dest, "source").unwrap();
+
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_500_000_000);
+        OpenOptions::new()
+            .write(true)
+            .open(&source)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+
+        let options = CopyOptions {
+            preserve_timestamps: true,
+            ..CopyOptions::default()
+        };
+        let metadata = std::fs::metadata(&source).unwrap();
+        let warnings = preserve_attributes(&source, &dest, &metadata, &options);
+
+        assert!(warnings.is_empty());
+        assert_eq!(
+            std::fs::metadata(&dest).unwrap().modified().unwrap(),
+            modified
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_preserve_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("script.sh");
+        let dest = dir.path().join("copy.sh");
+        std::fs::write(&source, "#!/bin/sh").unwrap();
+        std::fs::write(&dest, "#!/bin/sh").unwrap();
+        std::fs::set_permissions(&source, std::fs::Permissions::from_mode(0o750)).unwrap();
+
+        let metadata = std::fs::metadata(&source).unwrap();
+        let warnings = preserve_attributes(&source, &dest, &metadata, &CopyOptions::default());
+
+        assert!(warnings.is_empty());
+        let mode = std::fs::metadata(&dest).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o750);
+    }
+}
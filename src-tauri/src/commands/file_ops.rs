@@ -1,20 +1,63 @@
 //! File operation command handlers (delete, copy)
 
-use std::path::Path;
-use std::time::Instant;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter, State};
+
+use super::attributes::preserve_attributes;
+use super::journal::record_operation;
+use super::operation_registry::{CancellationToken, OperationRegistry};
+use super::preflight::{
+    available_space, check_copy_target, is_readable, is_writable, PreflightError,
+};
+use super::quarantine;
+use super::trash_bin::record_trashed;
+use crate::models::{decode_path, encode_path};
+
+/// Buffer size used when copying file contents
+const COPY_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Minimum interval between progress events
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
 
 /// Operation type for file operations
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum OperationType {
     Delete,
     Copy,
+    Move,
+    Restore,
+    Purge,
+    Quarantine,
+    /// Permanent deletion with overwritten contents
+    Shred,
+    /// Creation of an archive from existing files
+    Archive,
+    /// Extraction of archive members into a folder
+    Extract,
+}
+
+/// How to handle a destination that already exists
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictPolicy {
+    /// Leave the existing file alone and report `FileExists`
+    #[default]
+    Skip,
+    /// Replace the existing file (the old one is moved to trash)
+    Overwrite,
+    /// Keep both by giving the new file a numbered name, e.g. `name (1).ext`
+    Rename,
 }
 
 /// Failure reason for failed file operations
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum FailureReason {
     PermissionDenied,
@@ -23,6 +66,15 @@ pub enum FailureReason {
     TargetNotFound,
     FileExists,
     SameFolder,
+    VerificationFailed,
+    NotUndoable,
+    InsufficientSpace,
+    /// A generated file name is empty or not allowed
+    InvalidName,
+    /// Not processed because another file in an all-or-nothing batch failed
+    Aborted,
+    /// Path lies outside the base or target folder of the operation
+    OutsideBase,
     Unknown,
 }
 
@@ -44,28 +96,389 @@ pub struct FileOperationResult {
     pub failed_count: u32,
     pub failed_files: Vec<FailedEntry>,
     pub duration_ms: u64,
+    /// Whether the operation was cancelled before all files were processed
+    pub cancelled: bool,
+    /// Checksums of verified copies (empty unless verification was requested)
+    pub verified_files: Vec<VerifiedFile>,
+    /// Non-fatal problems, such as attributes that could not be preserved
+    pub warnings: Vec<OperationWarning>,
+    /// Files that were processed successfully
+    pub processed_files: Vec<ProcessedFile>,
+    /// ID of the journal entry recording this operation
+    pub journal_id: Option<String>,
+    /// Whether this is a plan from a dry run (nothing was changed)
+    pub dry_run: bool,
+}
+
+/// A successfully processed file and where it ended up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessedFile {
+    pub source_path: String,
+    /// Destination path, if the operation produced one
+    pub target_path: Option<String>,
+}
+
+/// Non-fatal problem encountered while processing a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationWarning {
+    pub path: String,
+    pub message: String,
+}
+
+/// Checksum record for a copied file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifiedFile {
+    pub source_path: String,
+    pub target_path: String,
+    /// Hash algorithm used for both digests
+    pub algorithm: String,
+    /// Hex-encoded digest of the data read from the source
+    pub source_digest: String,
+    /// Hex-encoded digest of the written target file
+    pub target_digest: String,
+    pub matched: bool,
+}
+
+/// Options for copy operations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CopyOptions {
+    /// Hash source and target with SHA-256 after each copy
+    pub verify: bool,
+
+    /// Preserve modification and access times
+    pub preserve_timestamps: bool,
+
+    /// Preserve permission bits (enabled by default, like `std::fs::copy`)
+    pub preserve_permissions: bool,
+
+    /// Preserve owner and group (Unix only, usually requires elevated rights)
+    pub preserve_ownership: bool,
+
+    /// Preserve extended attributes (Unix only)
+    pub preserve_xattrs: bool,
+}
+
+impl Default for CopyOptions {
+    fn default() -> Self {
+        Self {
+            verify: false,
+            preserve_timestamps: false,
+            preserve_permissions: true,
+            preserve_ownership: false,
+            preserve_xattrs: false,
+        }
+    }
+}
+
+/// Progress information for a running file operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationProgress {
+    /// Operation ID, if the caller supplied one
+    pub operation_id: Option<String>,
+
+    /// Operation type
+    pub operation: OperationType,
+
+    /// Number of files processed so far (succeeded or failed)
+    pub files_done: u32,
+
+    /// Total number of files in this operation
+    pub total_files: u32,
+
+    /// Number of bytes processed so far
+    pub bytes_done: u64,
+
+    /// Total number of bytes in this operation
+    pub total_bytes: u64,
+
+    /// Path currently being processed
+    pub current_path: String,
+
+    /// Average throughput since the operation started
+    pub bytes_per_second: u64,
+}
+
+/// Progress callback type for reporting file operation progress
+pub type OperationProgressCallback = Box<dyn Fn(OperationProgress) + Send + Sync>;
+
+/// Progress reporting and cancellation hooks for a file operation
+#[derive(Default)]
+pub struct OperationContext {
+    /// Operation ID reported in progress events
+    pub operation_id: Option<String>,
+
+    /// Token checked between files (and between chunks when copying)
+    pub cancel_token: CancellationToken,
+
+    /// Callback receiving throttled progress updates
+    pub progress_callback: Option<OperationProgressCallback>,
+
+    /// Validate and report what would happen without touching the filesystem
+    pub dry_run: bool,
+}
+
+impl OperationContext {
+    /// Create a context without progress reporting or an operation ID
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Accumulates progress counters and emits throttled progress updates
+pub(super) struct ProgressTracker<'a> {
+    context: &'a OperationContext,
+    operation: OperationType,
+    total_files: u32,
+    total_bytes: u64,
+    files_done: u32,
+    bytes_done: u64,
+    current_path: String,
+    start_time: Instant,
+    last_update: Instant,
+}
+
+impl<'a> ProgressTracker<'a> {
+    pub(super) fn new(
+        context: &'a OperationContext,
+        operation: OperationType,
+        total_files: u32,
+        total_bytes: u64,
+    ) -> Self {
+        let now = Instant::now();
+        Self {
+            context,
+            operation,
+            total_files,
+            total_bytes,
+            files_done: 0,
+            bytes_done: 0,
+            current_path: String::new(),
+            start_time: now,
+            last_update: now,
+        }
+    }
+
+    pub(super) fn start_file(&mut self, path: &str) {
+        self.current_path = path.to_string();
+        self.report(false);
+    }
+
+    pub(super) fn add_bytes(&mut self, bytes: u64) {
+        self.bytes_done += bytes;
+        self.report(false);
+    }
+
+    pub(super) fn finish_file(&mut self) {
+        self.files_done += 1;
+        self.report(false);
+    }
+
+    /// Emit a progress update if the interval has elapsed (or always if forced)
+    pub(super) fn report(&mut self, force: bool) {
+        let Some(ref callback) = self.context.progress_callback else {
+            return;
+        };
+        if !force && self.last_update.elapsed() < PROGRESS_INTERVAL {
+            return;
+        }
+
+        let elapsed = self.start_time.elapsed().as_secs_f64();
+        let bytes_per_second = if elapsed > 0.0 {
+            (self.bytes_done as f64 / elapsed) as u64
+        } else {
+            0
+        };
+
+        callback(OperationProgress {
+            operation_id: self.context.operation_id.clone(),
+            operation: self.operation.clone(),
+            files_done: self.files_done,
+            total_files: self.total_files,
+            bytes_done: self.bytes_done,
+            total_bytes: self.total_bytes,
+            current_path: self.current_path.clone(),
+            bytes_per_second,
+        });
+        self.last_update = Instant::now();
+    }
+}
+
+/// Outcome of copying a single file's contents
+pub(super) enum CopyOutcome {
+    Completed,
+    Cancelled,
 }
 
 /// Delete multiple files by moving them to trash
 ///
+/// Every completed delete or copy is recorded in the operation journal; the
+/// result carries the `journal_id` that `undo_operation` accepts.
+///
 /// This command safely deletes files by moving them to the system trash/recycle bin,
-/// allowing users to recover them if needed.
+/// allowing users to recover them if needed. Trashed paths are remembered so that
+/// `list_trashed_items` can show them later.
+///
+/// Depending on the quarantine settings, files are moved to the quarantine folder
+/// instead, either all of them or only those the system trash fails on. Such
+/// files have their quarantine location as target path in the result.
+///
+/// Progress events are emitted through the `operation_progress` event channel.
+/// If `operation_id` is given, the operation can be stopped with `cancel_operation`.
 ///
 /// # Arguments
 /// * `paths` - Vector of file paths to delete
+/// * `operation_id` - Optional ID used for progress events and cancellation
+/// * `dry_run` - Only validate and return the planned result (default: false)
 ///
 /// # Returns
 /// * `Ok(FileOperationResult)` - Operation result with success/failure counts
 /// * `Err(String)` - Error message if the operation completely fails
 #[tauri::command]
-pub async fn delete_files(paths: Vec<String>) -> Result<FileOperationResult, String> {
+pub async fn delete_files(
+    app_handle: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    paths: Vec<String>,
+    operation_id: Option<String>,
+    dry_run: Option<bool>,
+) -> Result<FileOperationResult, String> {
+    let context = operation_context(
+        &app_handle,
+        &registry,
+        operation_id,
+        dry_run.unwrap_or(false),
+    );
+    let result = quarantine::delete_with_settings(&app_handle, &paths, &context);
+
+    if let Some(ref id) = context.operation_id {
+        registry.unregister(id, &context.cancel_token);
+    }
+
+    let mut result = result?;
+    if !result.dry_run {
+        record_trashed(&app_handle, &mut result);
+        record_operation(&app_handle, &mut result);
+    }
+    Ok(result)
+}
+
+/// Copy multiple files to a target folder
+///
+/// Progress events are emitted through the `operation_progress` event channel.
+/// If `operation_id` is given, the operation can be stopped with `cancel_operation`;
+/// a partially copied file is removed when the copy is cancelled or fails.
+///
+/// Before copying, the target folder is checked for existence, write permission
+/// and enough free space for all copyable sources. If any check fails, nothing
+/// is copied and a structured `PreflightError` is returned.
+///
+/// With `options.verify` set, each copy is checked by comparing SHA-256 digests of
+/// the source data and the written target. Mismatching copies are removed and
+/// reported as `VerificationFailed`; all digests are listed in `verified_files`.
+///
+/// Timestamps, permissions, ownership and extended attributes are preserved as
+/// requested in `options`. Attributes that cannot be preserved are reported in
+/// `warnings` without failing the copy.
+///
+/// # Arguments
+/// * `source_paths` - Vector of source file paths to copy
+/// * `target_folder` - Target folder path where files will be copied
+/// * `options` - Optional copy options (defaults to no verification)
+/// * `operation_id` - Optional ID used for progress events and cancellation
+/// * `dry_run` - Only validate and return the planned result (default: false)
+///
+/// # Returns
+/// * `Ok(FileOperationResult)` - Operation result with success/failure counts
+/// * `Err(PreflightError)` - Reason the copy was rejected before it started
+#[tauri::command]
+pub async fn copy_files(
+    app_handle: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    source_paths: Vec<String>,
+    target_folder: String,
+    options: Option<CopyOptions>,
+    operation_id: Option<String>,
+    dry_run: Option<bool>,
+) -> Result<FileOperationResult, PreflightError> {
+    let context = operation_context(
+        &app_handle,
+        &registry,
+        operation_id,
+        dry_run.unwrap_or(false),
+    );
+    let options = options.unwrap_or_default();
+    let result = copy_paths(&source_paths, &target_folder, &options, &context);
+
+    if let Some(ref id) = context.operation_id {
+        registry.unregister(id, &context.cancel_token);
+    }
+
+    let mut result = result?;
+    if !result.dry_run {
+        record_operation(&app_handle, &mut result);
+    }
+    Ok(result)
+}
+
+/// Build an operation context that emits `operation_progress` events
+pub(super) fn operation_context(
+    app_handle: &AppHandle,
+    registry: &OperationRegistry,
+    operation_id: Option<String>,
+    dry_run: bool,
+) -> OperationContext {
+    let cancel_token = match operation_id {
+        Some(ref id) => registry.register(id),
+        None => CancellationToken::new(),
+    };
+
+    let app = app_handle.clone();
+    let progress_callback: OperationProgressCallback = Box::new(move |progress| {
+        let _ = app.emit("operation_progress", progress);
+    });
+
+    OperationContext {
+        operation_id,
+        cancel_token,
+        progress_callback: Some(progress_callback),
+        dry_run,
+    }
+}
+
+/// Move files to trash, reporting progress through the given context
+pub fn delete_paths(paths: &[String], context: &OperationContext) -> FileOperationResult {
     let start_time = Instant::now();
 
+    let total_bytes = paths
+        .iter()
+        .filter_map(|path| std::fs::symlink_metadata(decode_path(path)).ok())
+        .filter(std::fs::Metadata::is_file)
+        .map(|metadata| metadata.len())
+        .sum();
+    let mut tracker = ProgressTracker::new(
+        context,
+        OperationType::Delete,
+        paths.len() as u32,
+        total_bytes,
+    );
+
     let mut success_count: u32 = 0;
     let mut failed_files: Vec<FailedEntry> = Vec::new();
+    let mut processed_files: Vec<ProcessedFile> = Vec::new();
+    let mut cancelled = false;
 
-    for path_str in &paths {
-        let path = Path::new(path_str);
+    for path_str in paths {
+        if context.cancel_token.is_cancelled() {
+            cancelled = true;
+            break;
+        }
+
+        let path = decode_path(path_str);
+        tracker.start_file(path_str);
 
         // Check if path exists
         if !path.exists() {
@@ -74,16 +487,31 @@ pub async fn delete_files(paths: Vec<String>) -> Result<FileOperationResult, Str
                 reason: FailureReason::PathNotFound,
                 error_message: format!("File not found: {}", path_str),
             });
+            tracker.finish_file();
             continue;
         }
 
-        // Attempt to move to trash
-        match trash::delete(path) {
+        let size = std::fs::symlink_metadata(&path)
+            .ok()
+            .filter(std::fs::Metadata::is_file)
+            .map_or(0, |metadata| metadata.len());
+
+        // Attempt to move to trash (or only check permissions in a dry run)
+        let deleted = if context.dry_run {
+            check_delete_plan(&path)
+        } else {
+            trash::delete(&path).map_err(|e| categorize_trash_error(&e))
+        };
+        match deleted {
             Ok(()) => {
                 success_count += 1;
+                processed_files.push(ProcessedFile {
+                    source_path: path_str.clone(),
+                    target_path: None,
+                });
+                tracker.add_bytes(size);
             }
-            Err(e) => {
-                let (reason, message) = categorize_trash_error(&e);
+            Err((reason, message)) => {
                 failed_files.push(FailedEntry {
                     path: path_str.clone(),
                     reason,
@@ -91,114 +519,127 @@ pub async fn delete_files(paths: Vec<String>) -> Result<FileOperationResult, Str
                 });
             }
         }
+        tracker.finish_file();
     }
+    tracker.report(true);
 
     let duration_ms = start_time.elapsed().as_millis() as u64;
     let failed_count = failed_files.len() as u32;
 
-    Ok(FileOperationResult {
+    FileOperationResult {
         operation: OperationType::Delete,
         success_count,
         failed_count,
         failed_files,
         duration_ms,
-    })
+        cancelled,
+        verified_files: Vec::new(),
+        warnings: Vec::new(),
+        processed_files,
+        journal_id: None,
+        dry_run: context.dry_run,
+    }
 }
 
-/// Copy multiple files to a target folder
-///
-/// # Arguments
-/// * `source_paths` - Vector of source file paths to copy
-/// * `target_folder` - Target folder path where files will be copied
-///
-/// # Returns
-/// * `Ok(FileOperationResult)` - Operation result with success/failure counts
-/// * `Err(String)` - Error message if the operation completely fails
-#[tauri::command]
-pub async fn copy_files(
-    source_paths: Vec<String>,
-    target_folder: String,
-) -> Result<FileOperationResult, String> {
+/// Copy files into a target folder, reporting progress through the given context
+pub fn copy_paths(
+    source_paths: &[String],
+    target_folder: &str,
+    options: &CopyOptions,
+    context: &OperationContext,
+) -> Result<FileOperationResult, PreflightError> {
     let start_time = Instant::now();
 
-    let target_path = Path::new(&target_folder);
+    let target_path = &decode_path(target_folder);
 
-    // Validate target folder exists and is a directory
-    if !target_path.exists() {
-        return Err(format!("Target folder not found: {}", target_folder));
-    }
-    if !target_path.is_dir() {
-        return Err(format!("Target path is not a directory: {}", target_folder));
+    // Only sources that pass validation will be copied and need space
+    let total_bytes = source_paths
+        .iter()
+        .filter(|path| resolve_copy_target(path, target_path).is_ok())
+        .filter_map(|path| std::fs::metadata(decode_path(path)).ok())
+        .map(|metadata| metadata.len())
+        .sum();
+
+    // A dry run reports permission and space problems per file instead
+    if context.dry_run {
+        check_copy_target(target_path, 0).or_else(|e| match e.reason {
+            FailureReason::PermissionDenied => Ok(()),
+            _ => Err(e),
+        })?;
+    } else {
+        check_copy_target(target_path, total_bytes)?;
     }
+    let mut tracker = ProgressTracker::new(
+        context,
+        OperationType::Copy,
+        source_paths.len() as u32,
+        total_bytes,
+    );
 
     let mut success_count: u32 = 0;
     let mut failed_files: Vec<FailedEntry> = Vec::new();
+    let mut verified_files: Vec<VerifiedFile> = Vec::new();
+    let mut warnings: Vec<OperationWarning> = Vec::new();
+    let mut processed_files: Vec<ProcessedFile> = Vec::new();
+    let mut cancelled = false;
 
-    for source_path_str in &source_paths {
-        let source_path = Path::new(source_path_str);
+    // Only needed to validate a dry-run plan
+    let target_writable = context.dry_run && is_writable(target_path);
+    let mut remaining_space = if context.dry_run {
+        available_space(target_path).ok()
+    } else {
+        None
+    };
 
-        // Check if source exists
-        if !source_path.exists() {
-            failed_files.push(FailedEntry {
-                path: source_path_str.clone(),
-                reason: FailureReason::PathNotFound,
-                error_message: format!("Source file not found: {}", source_path_str),
-            });
-            continue;
+    for source_path_str in source_paths {
+        if context.cancel_token.is_cancelled() {
+            cancelled = true;
+            break;
         }
 
-        // Get file name for target path
-        let file_name = match source_path.file_name() {
-            Some(name) => name,
-            None => {
-                failed_files.push(FailedEntry {
-                    path: source_path_str.clone(),
-                    reason: FailureReason::Unknown,
-                    error_message: "Could not determine file name".to_string(),
-                });
-                continue;
-            }
-        };
-
-        let dest_path = target_path.join(file_name);
+        let source_path = decode_path(source_path_str);
+        tracker.start_file(source_path_str);
 
-        // Check if source and destination are the same folder
-        if let Some(source_parent) = source_path.parent() {
-            if source_parent == target_path {
-                failed_files.push(FailedEntry {
-                    path: source_path_str.clone(),
-                    reason: FailureReason::SameFolder,
-                    error_message: "Source and target folder are the same".to_string(),
-                });
+        let dest_path = match resolve_copy_target(source_path_str, target_path) {
+            Ok(dest_path) => dest_path,
+            Err(failed) => {
+                failed_files.push(failed);
+                tracker.finish_file();
                 continue;
             }
-        }
+        };
 
-        // Check if destination already exists
-        if dest_path.exists() {
-            failed_files.push(FailedEntry {
-                path: source_path_str.clone(),
-                reason: FailureReason::FileExists,
-                error_message: format!("File already exists: {}", dest_path.display()),
-            });
-            continue;
-        }
+        let outcome = if context.dry_run {
+            check_copy_plan(&source_path, target_writable, &mut remaining_space)
+                .map(|()| CopyOutcome::Completed)
+        } else {
+            copy_single_file(
+                source_path_str,
+                &dest_path,
+                options,
+                &mut tracker,
+                &mut verified_files,
+                &mut warnings,
+            )
+        };
 
-        // Perform the copy
-        match std::fs::copy(source_path, &dest_path) {
-            Ok(_) => {
+        match outcome {
+            Ok(CopyOutcome::Completed) => {
                 success_count += 1;
-            }
-            Err(e) => {
-                let (reason, message) = categorize_io_error(&e);
-                failed_files.push(FailedEntry {
-                    path: source_path_str.clone(),
-                    reason,
-                    error_message: message,
+                processed_files.push(ProcessedFile {
+                    source_path: source_path_str.clone(),
+                    target_path: Some(encode_path(&dest_path)),
                 });
             }
+            Ok(CopyOutcome::Cancelled) => {
+                cancelled = true;
+                break;
+            }
+            Err(failed) => failed_files.push(failed),
         }
+        tracker.finish_file();
     }
+    tracker.report(true);
 
     let duration_ms = start_time.elapsed().as_millis() as u64;
     let failed_count = failed_files.len() as u32;
@@ -209,11 +650,364 @@ pub async fn copy_files(
         failed_count,
         failed_files,
         duration_ms,
+        cancelled,
+        verified_files,
+        warnings,
+        processed_files,
+        journal_id: None,
+        dry_run: context.dry_run,
+    })
+}
+
+/// Check that a path could be moved out of its folder, without touching it
+pub(super) fn check_delete_plan(path: &Path) -> Result<(), (FailureReason, String)> {
+    match path.parent() {
+        Some(parent) if !is_writable(parent) => Err((
+            FailureReason::PermissionDenied,
+            format!("No write permission on folder: {}", parent.display()),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Validate a copy source and return its destination path in the target folder
+fn resolve_copy_target(source_path_str: &str, target_path: &Path) -> Result<PathBuf, FailedEntry> {
+    let source_path = decode_path(source_path_str);
+
+    // Check if source exists
+    if !source_path.exists() {
+        return Err(FailedEntry {
+            path: source_path_str.to_string(),
+            reason: FailureReason::PathNotFound,
+            error_message: format!("Source file not found: {}", source_path_str),
+        });
+    }
+
+    // Get file name for target path
+    let Some(file_name) = source_path.file_name() else {
+        return Err(FailedEntry {
+            path: source_path_str.to_string(),
+            reason: FailureReason::Unknown,
+            error_message: "Could not determine file name".to_string(),
+        });
+    };
+
+    let dest_path = target_path.join(file_name);
+
+    // Check if source and destination are the same folder
+    if source_path.parent() == Some(target_path) {
+        return Err(FailedEntry {
+            path: source_path_str.to_string(),
+            reason: FailureReason::SameFolder,
+            error_message: "Source and target folder are the same".to_string(),
+        });
+    }
+
+    // Check if destination already exists
+    if dest_path.exists() {
+        return Err(FailedEntry {
+            path: source_path_str.to_string(),
+            reason: FailureReason::FileExists,
+            error_message: format!("File already exists: {}", dest_path.display()),
+        });
+    }
+
+    Ok(dest_path)
+}
+
+/// Copy one file, then verify it and preserve its attributes as requested
+///
+/// A cancelled copy or a failed verification leaves no file behind.
+pub(super) fn copy_single_file(
+    source_path_str: &str,
+    dest_path: &Path,
+    options: &CopyOptions,
+    tracker: &mut ProgressTracker<'_>,
+    verified_files: &mut Vec<VerifiedFile>,
+    warnings: &mut Vec<OperationWarning>,
+) -> Result<CopyOutcome, FailedEntry> {
+    let source_path = &decode_path(source_path_str);
+
+    let mut hasher = options.verify.then(Sha256::new);
+    match copy_file_contents(source_path, dest_path, tracker, hasher.as_mut()) {
+        Ok(CopyOutcome::Completed) => {}
+        Ok(CopyOutcome::Cancelled) => return Ok(CopyOutcome::Cancelled),
+        Err(e) => {
+            let (reason, message) = categorize_io_error(&e);
+            return Err(FailedEntry {
+                path: source_path_str.to_string(),
+                reason,
+                error_message: message,
+            });
+        }
+    }
+
+    if let Some(hasher) = hasher {
+        if let Err(failed) = check_copy(source_path_str, dest_path, hasher, verified_files) {
+            // Do not leave a corrupt copy behind
+            let _ = std::fs::remove_file(dest_path);
+            return Err(failed);
+        }
+    }
+
+    if let Ok(metadata) = std::fs::metadata(source_path) {
+        warnings.extend(
+            preserve_attributes(source_path, dest_path, &metadata, options)
+                .into_iter()
+                .map(|message| OperationWarning {
+                    path: source_path_str.to_string(),
+                    message,
+                }),
+        );
+    }
+
+    Ok(CopyOutcome::Completed)
+}
+
+/// Check that a planned copy would be permitted and fit in the remaining space
+pub(super) fn check_copy_plan(
+    source_path: &Path,
+    target_writable: bool,
+    remaining_space: &mut Option<u64>,
+) -> Result<(), FailedEntry> {
+    let failed = |reason: FailureReason, error_message: String| FailedEntry {
+        path: encode_path(source_path),
+        reason,
+        error_message,
+    };
+
+    if !is_readable(source_path) {
+        return Err(failed(
+            FailureReason::PermissionDenied,
+            format!("No read permission: {}", source_path.display()),
+        ));
+    }
+    if !target_writable {
+        return Err(failed(
+            FailureReason::PermissionDenied,
+            "No write permission on target folder".to_string(),
+        ));
+    }
+
+    let size = std::fs::metadata(source_path).map_or(0, |metadata| metadata.len());
+    if let Some(ref mut space) = remaining_space {
+        if size > *space {
+            return Err(failed(
+                FailureReason::InsufficientSpace,
+                format!(
+                    "Not enough free space: need {} bytes, {} available",
+                    size, space
+                ),
+            ));
+        }
+        *space -= size;
+    }
+
+    Ok(())
+}
+
+/// Copy a file in chunks, checking for cancellation between chunks
+///
+/// If a hasher is given, every chunk read from the source is fed into it.
+/// The target file is removed again if the copy is cancelled or fails.
+fn copy_file_contents(
+    source: &Path,
+    dest: &Path,
+    tracker: &mut ProgressTracker<'_>,
+    hasher: Option<&mut Sha256>,
+) -> std::io::Result<CopyOutcome> {
+    let mut reader = File::open(source)?;
+    let mut writer = OpenOptions::new().write(true).create_new(true).open(dest)?;
+
+    let outcome = write_contents(&mut reader, &mut writer, tracker, hasher);
+    if !matches!(outcome, Ok(CopyOutcome::Completed)) {
+        // Leave no partial file behind
+        drop(writer);
+        let _ = std::fs::remove_file(dest);
+    }
+    outcome
+}
+
+/// Stream the reader into the writer, reporting progress per chunk
+pub(super) fn write_contents(
+    reader: &mut dyn Read,
+    writer: &mut File,
+    tracker: &mut ProgressTracker<'_>,
+    mut hasher: Option<&mut Sha256>,
+) -> std::io::Result<CopyOutcome> {
+    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+    loop {
+        if tracker.context.cancel_token.is_cancelled() {
+            return Ok(CopyOutcome::Cancelled);
+        }
+
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        writer.write_all(&buffer[..read])?;
+        if let Some(ref mut hasher) = hasher {
+            hasher.update(&buffer[..read]);
+        }
+        tracker.add_bytes(read as u64);
+    }
+    writer.flush()?;
+
+    Ok(CopyOutcome::Completed)
+}
+
+/// Verify a finished copy and record its digests
+fn check_copy(
+    source_path: &str,
+    dest_path: &Path,
+    source_hasher: Sha256,
+    verified_files: &mut Vec<VerifiedFile>,
+) -> Result<(), FailedEntry> {
+    let record = verify_copy(source_path, dest_path, source_hasher).map_err(|e| FailedEntry {
+        path: source_path.to_string(),
+        reason: FailureReason::VerificationFailed,
+        error_message: format!("Could not verify copy: {}", e),
+    })?;
+
+    let outcome = if record.matched {
+        Ok(())
+    } else {
+        Err(FailedEntry {
+            path: source_path.to_string(),
+            reason: FailureReason::VerificationFailed,
+            error_message: format!(
+                "Checksum mismatch: source {} != target {}",
+                record.source_digest, record.target_digest
+            ),
+        })
+    };
+    verified_files.push(record);
+    outcome
+}
+
+/// Compare the digest of the copied source data with a fresh digest of the target
+fn verify_copy(
+    source_path: &str,
+    dest_path: &Path,
+    source_hasher: Sha256,
+) -> std::io::Result<VerifiedFile> {
+    let source_digest = format!("{:x}", source_hasher.finalize());
+    let target_digest = hash_file(dest_path)?;
+
+    Ok(VerifiedFile {
+        source_path: source_path.to_string(),
+        target_path: encode_path(dest_path),
+        algorithm: "sha256".to_string(),
+        matched: source_digest == target_digest,
+        source_digest,
+        target_digest,
     })
 }
 
+/// Compute the hex-encoded SHA-256 digest of a file
+fn hash_file(path: &Path) -> std::io::Result<String> {
+    let mut reader = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Find a free path next to `path` by appending a counter, e.g. `name (1).ext`
+pub(super) fn unique_path(path: &Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+    numbered_path(path, Path::exists)
+}
+
+/// First numbered variant of `path`, e.g. `name (1).ext`, that is not `taken`
+pub(super) fn numbered_path(path: &Path, taken: impl Fn(&Path) -> bool) -> PathBuf {
+    // Built from OS strings so names that are not valid Unicode stay intact
+    let numbered_name = |counter: u32| {
+        let mut name = path.file_stem().unwrap_or_default().to_os_string();
+        name.push(format!(" ({})", counter));
+        if let Some(extension) = path.extension() {
+            name.push(".");
+            name.push(extension);
+        }
+        name
+    };
+
+    (1..)
+        .map(|counter| path.with_file_name(numbered_name(counter)))
+        .find(|candidate| !taken(candidate))
+        .expect("unbounded counter always yields a free path")
+}
+
+/// Rename a file or folder, copying it when the target is on another filesystem
+pub(super) fn move_path(from: &Path, to: &Path) -> std::io::Result<()> {
+    match std::fs::rename(from, to) {
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            if let Err(e) = copy_recursive(from, to) {
+                let _ = remove_path(to);
+                return Err(e);
+            }
+            remove_path(from)
+        }
+        result => result,
+    }
+}
+
+/// Copy a file, symlink or folder tree
+fn copy_recursive(from: &Path, to: &Path) -> std::io::Result<()> {
+    let metadata = std::fs::symlink_metadata(from)?;
+    if metadata.is_dir() {
+        std::fs::create_dir(to)?;
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        std::fs::set_permissions(to, metadata.permissions())
+    } else if metadata.file_type().is_symlink() {
+        copy_symlink(from, to)
+    } else {
+        std::fs::copy(from, to).map(|_| ())
+    }
+}
+
+#[cfg(unix)]
+fn copy_symlink(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(std::fs::read_link(from)?, to)
+}
+
+#[cfg(windows)]
+fn copy_symlink(from: &Path, to: &Path) -> std::io::Result<()> {
+    let target = std::fs::read_link(from)?;
+    if from.is_dir() {
+        std::os::windows::fs::symlink_dir(target, to)
+    } else {
+        std::os::windows::fs::symlink_file(target, to)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn copy_symlink(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::copy(from, to).map(|_| ())
+}
+
+/// Remove a file, symlink or folder tree
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    if std::fs::symlink_metadata(path)?.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+}
+
 /// Categorize trash crate errors into FailureReason
-fn categorize_trash_error(error: &trash::Error) -> (FailureReason, String) {
+pub(super) fn categorize_trash_error(error: &trash::Error) -> (FailureReason, String) {
     let message = error.to_string();
 
     match error {
@@ -231,13 +1025,14 @@ fn categorize_trash_error(error: &trash::Error) -> (FailureReason, String) {
 }
 
 /// Categorize std::io::Error into FailureReason
-fn categorize_io_error(error: &std::io::Error) -> (FailureReason, String) {
+pub(super) fn categorize_io_error(error: &std::io::Error) -> (FailureReason, String) {
     let message = error.to_string();
 
     match error.kind() {
         std::io::ErrorKind::PermissionDenied => (FailureReason::PermissionDenied, message),
         std::io::ErrorKind::NotFound => (FailureReason::PathNotFound, message),
         std::io::ErrorKind::AlreadyExists => (FailureReason::FileExists, message),
+        std::io::ErrorKind::StorageFull => (FailureReason::InsufficientSpace, message),
         _ => {
             if message.contains("locked") || message.contains("in use") {
                 (FailureReason::FileLocked, message)
@@ -252,31 +1047,31 @@ fn categorize_io_error(error: &std::io::Error) -> (FailureReason, String) {
 mod tests {
     use super::*;
     use std::fs::File;
+    use std::sync::{Arc, Mutex};
     use tempfile::TempDir;
 
-    #[tokio::test]
-    async fn test_delete_files_nonexistent_path() {
-        let result = delete_files(vec!["/nonexistent/path/file.txt".to_string()]).await;
+    #[test]
+    fn test_delete_files_nonexistent_path() {
+        let result = delete_paths(
+            &["/nonexistent/path/file.txt".to_string()],
+            &OperationContext::new(),
+        );
 
-        assert!(result.is_ok());
-        let result = result.unwrap();
         assert_eq!(result.success_count, 0);
         assert_eq!(result.failed_count, 1);
         assert_eq!(result.failed_files[0].reason, FailureReason::PathNotFound);
     }
 
-    #[tokio::test]
-    async fn test_delete_files_empty_paths() {
-        let result = delete_files(vec![]).await;
+    #[test]
+    fn test_delete_files_empty_paths() {
+        let result = delete_paths(&[], &OperationContext::new());
 
-        assert!(result.is_ok());
-        let result = result.unwrap();
         assert_eq!(result.success_count, 0);
         assert_eq!(result.failed_count, 0);
     }
 
-    #[tokio::test]
-    async fn test_delete_files_success() {
+    #[test]
+    fn test_delete_files_success() {
         // Create a temporary directory and file
         let temp_dir = TempDir::new().unwrap();
         let file_path = temp_dir.path().join("test_delete.txt");
@@ -284,10 +1079,11 @@ mod tests {
 
         assert!(file_path.exists());
 
-        let result = delete_files(vec![file_path.to_string_lossy().to_string()]).await;
+        let result = delete_paths(
+            &[file_path.to_string_lossy().to_string()],
+            &OperationContext::new(),
+        );
 
-        assert!(result.is_ok());
-        let result = result.unwrap();
         assert_eq!(result.success_count, 1);
         assert_eq!(result.failed_count, 0);
         assert_eq!(result.operation, OperationType::Delete);
@@ -296,26 +1092,45 @@ mod tests {
         assert!(!file_path.exists());
     }
 
-    #[tokio::test]
-    async fn test_copy_files_nonexistent_target() {
-        let result = copy_files(
-            vec!["/some/file.txt".to_string()],
-            "/nonexistent/target".to_string(),
-        )
-        .await;
+    #[test]
+    fn test_delete_files_cancelled() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("keep.txt");
+        File::create(&file_path).unwrap();
+
+        let context = OperationContext::new();
+        context.cancel_token.cancel();
+
+        let result = delete_paths(&[file_path.to_string_lossy().to_string()], &context);
+
+        assert!(result.cancelled);
+        assert_eq!(result.success_count, 0);
+        assert_eq!(result.failed_count, 0);
+        assert!(file_path.exists());
+    }
+
+    #[test]
+    fn test_copy_files_nonexistent_target() {
+        let result = copy_paths(
+            &["/some/file.txt".to_string()],
+            "/nonexistent/target",
+            &CopyOptions::default(),
+            &OperationContext::new(),
+        );
 
         assert!(result.is_err());
     }
 
-    #[tokio::test]
-    async fn test_copy_files_nonexistent_source() {
+    #[test]
+    fn test_copy_files_nonexistent_source() {
         let temp_dir = TempDir::new().unwrap();
 
-        let result = copy_files(
-            vec!["/nonexistent/source.txt".to_string()],
-            temp_dir.path().to_string_lossy().to_string(),
-        )
-        .await;
+        let result = copy_paths(
+            &["/nonexistent/source.txt".to_string()],
+            &temp_dir.path().to_string_lossy(),
+            &CopyOptions::default(),
+            &OperationContext::new(),
+        );
 
         assert!(result.is_ok());
         let result = result.unwrap();
@@ -324,8 +1139,8 @@ mod tests {
         assert_eq!(result.failed_files[0].reason, FailureReason::PathNotFound);
     }
 
-    #[tokio::test]
-    async fn test_copy_files_success() {
+    #[test]
+    fn test_copy_files_success() {
         // Create source file
         let source_dir = TempDir::new().unwrap();
         let source_file = source_dir.path().join("source.txt");
@@ -334,11 +1149,12 @@ mod tests {
         // Create target directory
         let target_dir = TempDir::new().unwrap();
 
-        let result = copy_files(
-            vec![source_file.to_string_lossy().to_string()],
-            target_dir.path().to_string_lossy().to_string(),
-        )
-        .await;
+        let result = copy_paths(
+            &[source_file.to_string_lossy().to_string()],
+            &target_dir.path().to_string_lossy(),
+            &CopyOptions::default(),
+            &OperationContext::new(),
+        );
 
         assert!(result.is_ok());
         let result = result.unwrap();
@@ -355,17 +1171,58 @@ mod tests {
         );
     }
 
-    #[tokio::test]
-    async fn test_copy_files_same_folder() {
+    #[cfg(unix)]
+    #[test]
+    fn test_copy_and_delete_non_utf8_name() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let source_dir = TempDir::new().unwrap();
+        let name = OsStr::from_bytes(b"caf\xe9.txt");
+        let source_file = source_dir.path().join(name);
+        std::fs::write(&source_file, "bytes").unwrap();
+        let target_dir = TempDir::new().unwrap();
+
+        let result = copy_paths(
+            &[encode_path(&source_file)],
+            &encode_path(target_dir.path()),
+            &CopyOptions::default(),
+            &OperationContext::new(),
+        )
+        .unwrap();
+
+        let copied_file = target_dir.path().join(name);
+        assert_eq!(result.success_count, 1);
+        assert_eq!(
+            result.processed_files[0].target_path,
+            Some(encode_path(&copied_file))
+        );
+        assert_eq!(std::fs::read_to_string(&copied_file).unwrap(), "bytes");
+
+        let context = OperationContext {
+            dry_run: true,
+            ..OperationContext::new()
+        };
+        let planned = delete_paths(&[encode_path(&copied_file)], &context);
+        assert_eq!(planned.success_count, 1);
+        assert_eq!(
+            planned.processed_files[0].source_path,
+            encode_path(&copied_file)
+        );
+    }
+
+    #[test]
+    fn test_copy_files_same_folder() {
         let temp_dir = TempDir::new().unwrap();
         let source_file = temp_dir.path().join("test.txt");
         std::fs::write(&source_file, "content").unwrap();
 
-        let result = copy_files(
-            vec![source_file.to_string_lossy().to_string()],
-            temp_dir.path().to_string_lossy().to_string(),
-        )
-        .await;
+        let result = copy_paths(
+            &[source_file.to_string_lossy().to_string()],
+            &temp_dir.path().to_string_lossy(),
+            &CopyOptions::default(),
+            &OperationContext::new(),
+        );
 
         assert!(result.is_ok());
         let result = result.unwrap();
@@ -374,8 +1231,8 @@ mod tests {
         assert_eq!(result.failed_files[0].reason, FailureReason::SameFolder);
     }
 
-    #[tokio::test]
-    async fn test_copy_files_file_exists() {
+    #[test]
+    fn test_copy_files_file_exists() {
         // Create source file
         let source_dir = TempDir::new().unwrap();
         let source_file = source_dir.path().join("test.txt");
@@ -386,11 +1243,12 @@ mod tests {
         let existing_file = target_dir.path().join("test.txt");
         std::fs::write(&existing_file, "existing content").unwrap();
 
-        let result = copy_files(
-            vec![source_file.to_string_lossy().to_string()],
-            target_dir.path().to_string_lossy().to_string(),
-        )
-        .await;
+        let result = copy_paths(
+            &[source_file.to_string_lossy().to_string()],
+            &target_dir.path().to_string_lossy(),
+            &CopyOptions::default(),
+            &OperationContext::new(),
+        );
 
         assert!(result.is_ok());
         let result = result.unwrap();
@@ -399,6 +1257,186 @@ mod tests {
         assert_eq!(result.failed_files[0].reason, FailureReason::FileExists);
     }
 
+    #[test]
+    fn test_copy_files_reports_progress() {
+        let source_dir = TempDir::new().unwrap();
+        let source_file = source_dir.path().join("data.bin");
+        std::fs::write(&source_file, vec![7u8; 4096]).unwrap();
+        let target_dir = TempDir::new().unwrap();
+
+        let updates: Arc<Mutex<Vec<OperationProgress>>> = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&updates);
+        let context = OperationContext {
+            operation_id: Some("copy-1".to_string()),
+            progress_callback: Some(Box::new(move |progress| {
+                sink.lock().unwrap().push(progress);
+            })),
+            ..OperationContext::new()
+        };
+
+        let result = copy_paths(
+            &[source_file.to_string_lossy().to_string()],
+            &target_dir.path().to_string_lossy(),
+            &CopyOptions::default(),
+            &context,
+        )
+        .unwrap();
+        assert_eq!(result.success_count, 1);
+
+        let updates = updates.lock().unwrap();
+        let last = updates.last().expect("final progress update");
+        assert_eq!(last.operation_id.as_deref(), Some("copy-1"));
+        assert_eq!(last.files_done, 1);
+        assert_eq!(last.total_files, 1);
+        assert_eq!(last.bytes_done, 4096);
+        assert_eq!(last.total_bytes, 4096);
+    }
+
+    #[test]
+    fn test_copy_files_cancelled() {
+        let source_dir = TempDir::new().unwrap();
+        let source_file = source_dir.path().join("data.bin");
+        std::fs::write(&source_file, "content").unwrap();
+        let target_dir = TempDir::new().unwrap();
+
+        let context = OperationContext::new();
+        context.cancel_token.cancel();
+
+        let result = copy_paths(
+            &[source_file.to_string_lossy().to_string()],
+            &target_dir.path().to_string_lossy(),
+            &CopyOptions::default(),
+            &context,
+        )
+        .unwrap();
+
+        assert!(result.cancelled);
+        assert_eq!(result.success_count, 0);
+        assert!(!target_dir.path().join("data.bin").exists());
+    }
+
+    #[test]
+    fn test_copy_files_verify() {
+        let source_dir = TempDir::new().unwrap();
+        let source_file = source_dir.path().join("evidence.bin");
+        std::fs::write(&source_file, "abc").unwrap();
+        let target_dir = TempDir::new().unwrap();
+
+        let result = copy_paths(
+            &[source_file.to_string_lossy().to_string()],
+            &target_dir.path().to_string_lossy(),
+            &CopyOptions {
+                verify: true,
+                ..CopyOptions::default()
+            },
+            &OperationContext::new(),
+        )
+        .unwrap();
+
+        assert_eq!(result.success_count, 1);
+        assert_eq!(result.verified_files.len(), 1);
+        let record = &result.verified_files[0];
+        assert!(record.matched);
+        assert_eq!(record.algorithm, "sha256");
+        assert_eq!(
+            record.source_digest,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(record.source_digest, record.target_digest);
+    }
+
+    #[test]
+    fn test_verify_copy_mismatch() {
+        let target_dir = TempDir::new().unwrap();
+        let dest_file = target_dir.path().join("copy.bin");
+        std::fs::write(&dest_file, "abd").unwrap();
+
+        let mut hasher = Sha256::new();
+        hasher.update(b"abc");
+        let record = verify_copy("/source/copy.bin", &dest_file, hasher).unwrap();
+
+        assert!(!record.matched);
+        assert_ne!(record.source_digest, record.target_digest);
+    }
+
+    #[test]
+    fn test_delete_files_dry_run() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("plan.txt");
+        File::create(&file_path).unwrap();
+
+        let context = OperationContext {
+            dry_run: true,
+            ..OperationContext::new()
+        };
+        let result = delete_paths(
+            &[
+                file_path.to_string_lossy().to_string(),
+                "/nonexistent/plan.txt".to_string(),
+            ],
+            &context,
+        );
+
+        assert!(result.dry_run);
+        assert_eq!(result.success_count, 1);
+        assert_eq!(result.failed_files[0].reason, FailureReason::PathNotFound);
+        assert!(file_path.exists());
+    }
+
+    #[test]
+    fn test_copy_files_dry_run() {
+        let source_dir = TempDir::new().unwrap();
+        let source_file = source_dir.path().join("plan.txt");
+        std::fs::write(&source_file, "content").unwrap();
+        let target_dir = TempDir::new().unwrap();
+
+        let context = OperationContext {
+            dry_run: true,
+            ..OperationContext::new()
+        };
+        let result = copy_paths(
+            &[source_file.to_string_lossy().to_string()],
+            &target_dir.path().to_string_lossy(),
+            &CopyOptions::default(),
+            &context,
+        )
+        .unwrap();
+
+        assert!(result.dry_run);
+        assert_eq!(result.success_count, 1);
+        let planned = result.processed_files[0].target_path.as_deref().unwrap();
+        assert!(planned.ends_with("plan.txt"));
+        assert!(!target_dir.path().join("plan.txt").exists());
+    }
+
+    #[test]
+    fn test_check_copy_plan_insufficient_space() {
+        let source_dir = TempDir::new().unwrap();
+        let source_file = source_dir.path().join("big.bin");
+        std::fs::write(&source_file, vec![0u8; 100]).unwrap();
+
+        let mut remaining_space = Some(50);
+        let failed = check_copy_plan(&source_file, true, &mut remaining_space).unwrap_err();
+        assert_eq!(failed.reason, FailureReason::InsufficientSpace);
+
+        let mut remaining_space = Some(150);
+        assert!(check_copy_plan(&source_file, true, &mut remaining_space).is_ok());
+        assert_eq!(remaining_space, Some(50));
+    }
+
+    #[test]
+    fn test_unique_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("report.pdf");
+        assert_eq!(unique_path(&path), path);
+
+        std::fs::write(&path, "v1").unwrap();
+        assert_eq!(unique_path(&path), temp_dir.path().join("report (1).pdf"));
+
+        std::fs::write(temp_dir.path().join("report (1).pdf"), "v2").unwrap();
+        assert_eq!(unique_path(&path), temp_dir.path().join("report (2).pdf"));
+    }
+
     #[test]
     fn test_categorize_io_error_permission_denied() {
         let error = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "Access denied");
@@ -0,0 +1,503 @@
+//! Persistent operation journal and undo support
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use super::file_ops::{
+    categorize_io_error, move_path, FailedEntry, FailureReason, FileOperationResult,
+    OperationContext, OperationType, OperationWarning, ProcessedFile,
+};
+use super::json_store::JsonStore;
+use super::quarantine;
+use super::trash_bin::{record_trashed, restore_original_paths};
+use crate::models::decode_path;
+
+/// File name of the operation journal in the app data folder
+const JOURNAL_FILE: &str = "operation_journal.json";
+
+/// Maximum number of entries kept in the journal (oldest are dropped first)
+const MAX_JOURNAL_ENTRIES: usize = 1000;
+
+/// Slack allowed between a copy's modification time and the time it was
+/// recorded (some file systems round timestamps up, FAT by up to 2 seconds)
+const COPY_MTIME_TOLERANCE: Duration = Duration::from_secs(2);
+
+/// Counter that keeps journal IDs unique within the same millisecond
+static JOURNAL_SEQUENCE: AtomicU32 = AtomicU32::new(0);
+
+/// A recorded file operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalEntry {
+    /// Unique journal entry ID
+    pub id: String,
+
+    /// Time the operation finished (ISO 8601 format)
+    pub recorded_at: String,
+
+    /// Full operation result, including per-file source and destination
+    pub result: FileOperationResult,
+
+    /// Journal ID of the undo operation, if this entry has been undone
+    pub undone_by: Option<String>,
+}
+
+/// Persistent list of recorded file operations
+///
+/// Loaded and saved through the `JsonStore<Journal>` in managed state.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Journal {
+    pub entries: Vec<JournalEntry>,
+}
+
+impl Journal {
+    /// Create the store that guards the journal file
+    pub fn store() -> JsonStore<Self> {
+        JsonStore::new("journal")
+    }
+
+    /// Append an operation result and return the new entry ID
+    pub fn append(&mut self, result: &FileOperationResult) -> String {
+        let now = Utc::now();
+        let sequence = JOURNAL_SEQUENCE.fetch_add(1, Ordering::Relaxed);
+        let id = format!("{}-{}", now.timestamp_millis(), sequence);
+
+        self.entries.push(JournalEntry {
+            id: id.clone(),
+            recorded_at: now.to_rfc3339(),
+            result: result.clone(),
+            undone_by: None,
+        });
+
+        if self.entries.len() > MAX_JOURNAL_ENTRIES {
+            let excess = self.entries.len() - MAX_JOURNAL_ENTRIES;
+            self.entries.drain(..excess);
+        }
+
+        id
+    }
+
+    /// Find an entry by ID
+    pub fn find_mut(&mut self, id: &str) -> Option<&mut JournalEntry> {
+        self.entries.iter_mut().find(|entry| entry.id == id)
+    }
+}
+
+/// List journal entries, most recent first
+///
+/// # Arguments
+/// * `limit` - Maximum number of entries to return (default: all)
+#[tauri::command]
+pub async fn list_journal_entries(
+    app_handle: AppHandle,
+    journals: State<'_, JsonStore<Journal>>,
+    limit: Option<usize>,
+) -> Result<Vec<JournalEntry>, String> {
+    let journal = journals.open(&journal_path(&app_handle)?)?;
+
+    Ok(journal
+        .entries
+        .iter()
+        .rev()
+        .take(limit.unwrap_or(usize::MAX))
+        .cloned()
+        .collect())
+}
+
+/// Reverse a recorded operation where possible
+///
+/// Copies, restored and extracted files are deleted the way `delete_files`
+/// deletes, to the trash or the quarantine as configured. Copies are only
+/// deleted while their source still exists and the copy has not been changed
+/// since. Moves are moved back, and deleted or quarantined files are restored
+/// from the trash or the quarantine folder. Files that cannot be undone are
+/// reported as `NotUndoable` failures.
+///
+/// The journal stays locked from checking the entry until it is marked as
+/// undone, so concurrent calls cannot undo the same entry twice.
+///
+/// # Arguments
+/// * `journal_id` - ID of the journal entry to undo
+///
+/// # Returns
+/// * `Ok(FileOperationResult)` - Result of the undo, itself recorded in the journal
+/// * `Err(String)` - Error message if the entry is missing or already undone
+#[tauri::command]
+pub async fn undo_operation(
+    app_handle: AppHandle,
+    journals: State<'_, JsonStore<Journal>>,
+    journal_id: String,
+) -> Result<FileOperationResult, String> {
+    let mut journal = journals.open(&journal_path(&app_handle)?)?;
+
+    let entry = journal
+        .entries
+        .iter()
+        .find(|entry| entry.id == journal_id)
+        .cloned()
+        .ok_or_else(|| format!("Journal entry not found: {}", journal_id))?;
+    if let Some(ref undone_by) = entry.undone_by {
+        return Err(format!("Operation already undone by {}", undone_by));
+    }
+
+    let original = &entry.result;
+    let mut result = match original.operation {
+        OperationType::Copy => {
+            let (paths, mut kept) = removable_copies(&original.processed_files, &entry.recorded_at);
+            let mut result =
+                quarantine::delete_with_settings(&app_handle, &paths, &OperationContext::new())?;
+            record_trashed(&app_handle, &mut result);
+            result.failed_files.append(&mut kept);
+            result.failed_count = result.failed_files.len() as u32;
+            result
+        }
+        OperationType::Move => undo_move(&original.processed_files),
+        OperationType::Delete => undo_delete(&app_handle, &original.processed_files)?,
+        OperationType::Restore | OperationType::Extract => {
+            let paths: Vec<String> = original
+                .processed_files
+                .iter()
+                .filter_map(|file| file.target_path.clone())
+                .collect();
+            let mut result =
+                quarantine::delete_with_settings(&app_handle, &paths, &OperationContext::new())?;
+            record_trashed(&app_handle, &mut result);
+            result
+        }
+        OperationType::Quarantine => {
+            let paths: Vec<String> = original
+                .processed_files
+                .iter()
+                .map(|file| file.source_path.clone())
+                .collect();
+            quarantine::restore_original_paths(&app_handle, &paths)?
+        }
+        OperationType::Purge => not_undoable(
+            OperationType::Purge,
+            &original.processed_files,
+            "Permanently deleted files cannot be restored",
+        ),
+        OperationType::Shred => not_undoable(
+            OperationType::Shred,
+            &original.processed_files,
+            "Securely deleted files cannot be restored",
+        ),
+        OperationType::Archive => not_undoable(
+            OperationType::Archive,
+            &original.processed_files,
+            "Archive creation does not change the archived files",
+        ),
+    };
+
+    let id = journal.append(&result);
+    if let Some(entry) = journal.find_mut(&journal_id) {
+        entry.undone_by = Some(id.clone());
+    }
+    match journal.save() {
+        Ok(()) => result.journal_id = Some(id),
+        Err(message) => result.warnings.push(OperationWarning {
+            path: JOURNAL_FILE.to_string(),
+            message,
+        }),
+    }
+
+    Ok(result)
+}
+
+/// Append an operation result to the journal and store its ID in the result
+///
+/// Journal write failures are reported as warnings on the result.
+pub(super) fn record_operation(app_handle: &AppHandle, result: &mut FileOperationResult) {
+    let journals = app_handle.state::<JsonStore<Journal>>();
+    let recorded = journal_path(app_handle).and_then(|path| {
+        let mut journal = journals.open(&path)?;
+        let id = journal.append(result);
+        journal.save().map(|()| id)
+    });
+
+    match recorded {
+        Ok(id) => result.journal_id = Some(id),
+        Err(message) => result.warnings.push(OperationWarning {
+            path: JOURNAL_FILE.to_string(),
+            message,
+        }),
+    }
+}
+
+/// Restore deleted files from the trash, or from the quarantine for files
+/// that were quarantined instead (those have a target path)
+fn undo_delete(
+    app_handle: &AppHandle,
+    files: &[ProcessedFile],
+) -> Result<FileOperationResult, String> {
+    let (quarantined, trashed): (Vec<&ProcessedFile>, Vec<&ProcessedFile>) =
+        files.iter().partition(|file| file.target_path.is_some());
+    let source_paths = |files: Vec<&ProcessedFile>| -> Vec<String> {
+        files
+            .into_iter()
+            .map(|file| file.source_path.clone())
+            .collect()
+    };
+
+    if trashed.is_empty() {
+        return quarantine::restore_original_paths(app_handle, &source_paths(quarantined));
+    }
+    let mut result = restore_original_paths(app_handle, &source_paths(trashed))?;
+    if !quarantined.is_empty() {
+        let restored = quarantine::restore_original_paths(app_handle, &source_paths(quarantined))?;
+        result.processed_files.extend(restored.processed_files);
+        result.failed_files.extend(restored.failed_files);
+        result.warnings.extend(restored.warnings);
+        result.success_count = result.processed_files.len() as u32;
+        result.failed_count = result.failed_files.len() as u32;
+    }
+    Ok(result)
+}
+
+/// Pick the copies an undo may move to the trash
+///
+/// Copies are kept, and reported as `NotUndoable`, if their source no longer
+/// exists or if they were changed after the copy was recorded: a size that
+/// differs from the source or a modification time after `recorded_at`.
+fn removable_copies(files: &[ProcessedFile], recorded_at: &str) -> (Vec<String>, Vec<FailedEntry>) {
+    let recorded_at = DateTime::parse_from_rfc3339(recorded_at)
+        .ok()
+        .map(|time| SystemTime::from(time) + COPY_MTIME_TOLERANCE);
+    let mut paths = Vec::new();
+    let mut kept = Vec::new();
+
+    for file in files {
+        let Some(ref target) = file.target_path else {
+            continue;
+        };
+        let keep = |error_message: String| FailedEntry {
+            path: target.clone(),
+            reason: FailureReason::NotUndoable,
+            error_message,
+        };
+
+        let Ok(source) = std::fs::metadata(decode_path(&file.source_path)) else {
+            kept.push(keep(format!(
+                "Source no longer exists, keeping copy: {}",
+                file.source_path
+            )));
+            continue;
+        };
+        // A missing copy is reported by the delete itself
+        if let Ok(copy) = std::fs::symlink_metadata(decode_path(target)) {
+            let resized = copy.is_file() && source.is_file() && copy.len() != source.len();
+            let modified = copy
+                .modified()
+                .ok()
+                .zip(recorded_at)
+                .is_some_and(|(modified, recorded_at)| modified > recorded_at);
+            if resized || modified {
+                kept.push(keep(format!(
+                    "Copy was changed after it was made, keeping it: {}",
+                    target
+                )));
+                continue;
+            }
+        }
+        paths.push(target.clone());
+    }
+
+    (paths, kept)
+}
+
+/// Move files back to their source paths, copying across file systems
+fn undo_move(files: &[ProcessedFile]) -> FileOperationResult {
+    let start_time = Instant::now();
+    let mut failed_files: Vec<FailedEntry> = Vec::new();
+    let mut processed_files: Vec<ProcessedFile> = Vec::new();
+
+    for file in files {
+        let Some(ref target) = file.target_path else {
+            continue;
+        };
+
+        let source = &decode_path(&file.source_path);
+        if source.symlink_metadata().is_ok() {
+            failed_files.push(FailedEntry {
+                path: target.clone(),
+                reason: FailureReason::FileExists,
+                error_message: format!("File already exists: {}", file.source_path),
+            });
+            continue;
+        }
+
+        let moved = source
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| move_path(&decode_path(target), source));
+        match moved {
+            Ok(()) => processed_files.push(ProcessedFile {
+                source_path: target.clone(),
+                target_path: Some(file.source_path.clone()),
+            }),
+            Err(e) => {
+                let (reason, message) = categorize_io_error(&e);
+                failed_files.push(FailedEntry {
+                    path: target.clone(),
+                    reason,
+                    error_message: message,
+                });
+            }
+        }
+    }
+
+    undo_result(
+        OperationType::Move,
+        start_time,
+        failed_files,
+        processed_files,
+    )
+}
+
+/// Report every file of an operation as not undoable
+fn not_undoable(
+    operation: OperationType,
+    files: &[ProcessedFile],
+    message: &str,
+) -> FileOperationResult {
+    let failed_files = files
+        .iter()
+        .map(|file| FailedEntry {
+            path: file.source_path.clone(),
+            reason: FailureReason::NotUndoable,
+            error_message: message.to_string(),
+        })
+        .collect();
+
+    undo_result(operation, Instant::now(), failed_files, Vec::new())
+}
+
+fn undo_result(
+    operation: OperationType,
+    start_time: Instant,
+    failed_files: Vec<FailedEntry>,
+    processed_files: Vec<ProcessedFile>,
+) -> FileOperationResult {
+    FileOperationResult {
+        operation,
+        success_count: processed_files.len() as u32,
+        failed_count: failed_files.len() as u32,
+        failed_files,
+        duration_ms: start_time.elapsed().as_millis() as u64,
+        cancelled: false,
+        verified_files: Vec::new(),
+        warnings: Vec::new(),
+        processed_files,
+        journal_id: None,
+        dry_run: false,
+    }
+}
+
+fn journal_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map(|dir| dir.join(JOURNAL_FILE))
+        .map_err(|e| format!("Failed to resolve app data folder: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn processed(source: &Path, target: &Path) -> ProcessedFile {
+        ProcessedFile {
+            source_path: source.to_string_lossy().to_string(),
+            target_path: Some(target.to_string_lossy().to_string()),
+        }
+    }
+
+    #[test]
+    fn test_journal_append_and_reload() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(JOURNAL_FILE);
+        let store = Journal::store();
+
+        let mut journal = store.open(&path).unwrap();
+        let result = undo_result(OperationType::Copy, Instant::now(), Vec::new(), Vec::new());
+        let first = journal.append(&result);
+        let second = journal.append(&result);
+        assert_ne!(first, second);
+        journal.save().unwrap();
+        drop(journal);
+
+        let mut loaded = store.open(&path).unwrap();
+        assert_eq!(loaded.entries.len(), 2);
+        assert!(loaded.find_mut(&first).is_some());
+    }
+
+    #[test]
+    fn test_undo_copy_keeps_copy_without_source() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source.txt");
+        let copy = temp_dir.path().join("copy.txt");
+        let orphan_source = temp_dir.path().join("gone.txt");
+        let orphan_copy = temp_dir.path().join("gone copy.txt");
+        std::fs::write(&source, "data").unwrap();
+        std::fs::write(&copy, "data").unwrap();
+        std::fs::write(&orphan_copy, "data").unwrap();
+
+        let (paths, kept) = removable_copies(
+            &[
+                processed(&source, &copy),
+                processed(&orphan_source, &orphan_copy),
+            ],
+            &Utc::now().to_rfc3339(),
+        );
+
+        assert_eq!(paths, vec![copy.to_string_lossy().to_string()]);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].reason, FailureReason::NotUndoable);
+        assert_eq!(kept[0].path, orphan_copy.to_string_lossy());
+    }
+
+    #[test]
+    fn test_undo_copy_keeps_changed_copy() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source.txt");
+        let resized = temp_dir.path().join("resized.txt");
+        let touched = temp_dir.path().join("touched.txt");
+        std::fs::write(&source, "data").unwrap();
+        std::fs::write(&resized, "data, edited").unwrap();
+        std::fs::write(&touched, "data").unwrap();
+        let recorded_at = Utc::now() - chrono::Duration::hours(1);
+
+        let (paths, kept) = removable_copies(
+            &[processed(&source, &resized), processed(&source, &touched)],
+            &recorded_at.to_rfc3339(),
+        );
+
+        assert!(paths.is_empty());
+        assert_eq!(kept.len(), 2);
+        assert!(kept
+            .iter()
+            .all(|failed| failed.reason == FailureReason::NotUndoable));
+    }
+
+    #[test]
+    fn test_undo_move() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("original").join("photo.jpg");
+        let target = temp_dir.path().join("photo.jpg");
+        std::fs::write(&target, "jpeg").unwrap();
+
+        let result = undo_move(&[processed(&source, &target)]);
+
+        assert_eq!(result.success_count, 1);
+        assert!(source.exists());
+        assert!(!target.exists());
+    }
+}
@@ -0,0 +1,149 @@
+//! JSON files in the app data folder that are updated under a lock
+//!
+//! Commands run concurrently, so every read-modify-write of a shared file (the
+//! operation journal, the trash log and quarantine manifests) goes through a
+//! `JsonStore` kept in Tauri managed state. The store's lock is held from loading the file
+//! until the returned `JsonFile` is dropped, and files are written to a
+//! temporary file first and renamed over the old one, so a crash never
+//! leaves a half-written file behind.
+
+use std::io::Write;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Serializes access to one kind of JSON file
+#[derive(Debug)]
+pub struct JsonStore<T> {
+    /// Name of the file contents used in error messages, e.g. "journal"
+    name: &'static str,
+    lock: Mutex<()>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Serialize + DeserializeOwned + Default> JsonStore<T> {
+    /// Create a store for files described as `name` in error messages
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            lock: Mutex::new(()),
+            marker: PhantomData,
+        }
+    }
+
+    /// Lock the store and load the file at `path`
+    ///
+    /// A missing file loads as the default value. A file that cannot be read
+    /// or parsed is an error, so that saving never replaces data that could
+    /// not be loaded.
+    pub fn open(&self, path: &Path) -> Result<JsonFile<'_, T>, String> {
+        let guard = self.lock.lock().expect("json store lock poisoned");
+
+        let value = match std::fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| format!("Failed to parse {}: {}", self.name, e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => T::default(),
+            Err(e) => return Err(format!("Failed to read {}: {}", self.name, e)),
+        };
+
+        Ok(JsonFile {
+            value,
+            path: path.to_path_buf(),
+            name: self.name,
+            _guard: guard,
+        })
+    }
+}
+
+/// Contents of a file loaded from a `JsonStore`
+///
+/// Other updates of the same store wait until this is dropped.
+#[derive(Debug)]
+pub struct JsonFile<'a, T> {
+    value: T,
+    path: PathBuf,
+    name: &'static str,
+    _guard: MutexGuard<'a, ()>,
+}
+
+impl<T: Serialize> JsonFile<'_, T> {
+    /// Write the contents back through a temporary file
+    pub fn save(&self) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create folder for {}: {}", self.name, e))?;
+        }
+        let content = serde_json::to_string(&self.value)
+            .map_err(|e| format!("Failed to serialize {}: {}", self.name, e))?;
+
+        let mut temp_name = std::ffi::OsString::from(".");
+        temp_name.push(self.path.file_name().unwrap_or_default());
+        temp_name.push(".tmp");
+        let temp_path = self.path.with_file_name(temp_name);
+
+        let written = std::fs::File::create(&temp_path).and_then(|mut file| {
+            file.write_all(content.as_bytes())?;
+            file.sync_all()
+        });
+        if let Err(e) = written.and_then(|()| std::fs::rename(&temp_path, &self.path)) {
+            let _ = std::fs::remove_file(&temp_path);
+            return Err(format!("Failed to write {}: {}", self.name, e));
+        }
+        Ok(())
+    }
+}
+
+impl<T> Deref for JsonFile<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for JsonFile<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_open_missing_and_save() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("data").join("list.json");
+        let store: JsonStore<Vec<String>> = JsonStore::new("list");
+
+        let mut file = store.open(&path).unwrap();
+        assert!(file.is_empty());
+        file.push("entry".to_string());
+        file.save().unwrap();
+        drop(file);
+
+        assert_eq!(*store.open(&path).unwrap(), vec!["entry".to_string()]);
+        assert_eq!(
+            std::fs::read_dir(path.parent().unwrap()).unwrap().count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_open_damaged_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("list.json");
+        std::fs::write(&path, "[\"entry\", ").unwrap();
+        let store: JsonStore<Vec<String>> = JsonStore::new("list");
+
+        let error = store.open(&path).unwrap_err();
+        assert!(error.starts_with("Failed to parse list"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[\"entry\", ");
+    }
+}
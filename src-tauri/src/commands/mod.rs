@@ -1,7 +1,34 @@
 //! Tauri command handlers
 
+mod analysis;
+mod archive;
+mod attributes;
 mod file_ops;
+mod journal;
+mod json_store;
+mod operation_registry;
+mod organize;
+mod preflight;
+mod quarantine;
+mod rename;
 mod scan;
+mod secure_delete;
+mod trash_bin;
 
+pub use analysis::{
+    check_portability, find_empty_items, find_stale_files, find_symlink_issues, scan_secrets,
+    security_audit, size_hierarchy, top_n, treemap_layout, ScanCache,
+};
+pub use archive::{create_archive, extract_archive};
 pub use file_ops::{copy_files, delete_files};
+pub use journal::{list_journal_entries, undo_operation, Journal};
+pub use operation_registry::{cancel_operation, OperationRegistry};
+pub use organize::organize_files;
+pub use quarantine::{
+    get_quarantine_settings, list_quarantined_items, purge_quarantined_items, quarantine_files,
+    restore_quarantined_items, set_quarantine_settings, QuarantineManifest,
+};
+pub use rename::rename_files;
 pub use scan::scan_folder;
+pub use secure_delete::{prepare_secure_delete, secure_delete_files, SecureDeleteConfirmations};
+pub use trash_bin::{list_trashed_items, purge_trashed_items, restore_trashed_items, TrashLog};
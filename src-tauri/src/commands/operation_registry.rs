@@ -0,0 +1,158 @@
+//! Registry of running file operations for cancellation
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tauri::State;
+
+/// Shared flag used to request cancellation of a running operation
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Create a new token that has not been cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Check whether cancellation has been requested
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Whether both tokens belong to the same operation
+    pub fn same_as(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Tracks cancellation tokens of running operations by operation ID
+///
+/// Registered as Tauri managed state so that `cancel_operation` can reach
+/// operations started by other commands.
+#[derive(Debug, Default)]
+pub struct OperationRegistry {
+    operations: Mutex<HashMap<String, CancellationToken>>,
+}
+
+impl OperationRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an operation and return its cancellation token
+    ///
+    /// Reusing the ID of a running operation makes the new operation the one
+    /// reached by `cancel`; the old one keeps its token but can no longer be
+    /// cancelled by ID.
+    pub fn register(&self, operation_id: &str) -> CancellationToken {
+        let token = CancellationToken::new();
+        self.operations
+            .lock()
+            .expect("operation registry lock poisoned")
+            .insert(operation_id.to_string(), token.clone());
+        token
+    }
+
+    /// Remove a finished operation from the registry
+    ///
+    /// Nothing is removed if the ID has since been registered again by
+    /// another operation.
+    pub fn unregister(&self, operation_id: &str, token: &CancellationToken) {
+        let mut operations = self
+            .operations
+            .lock()
+            .expect("operation registry lock poisoned");
+        if operations
+            .get(operation_id)
+            .is_some_and(|registered| registered.same_as(token))
+        {
+            operations.remove(operation_id);
+        }
+    }
+
+    /// Request cancellation of an operation
+    ///
+    /// Returns `false` if no operation with this ID is running.
+    pub fn cancel(&self, operation_id: &str) -> bool {
+        match self
+            .operations
+            .lock()
+            .expect("operation registry lock poisoned")
+            .get(operation_id)
+        {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Cancel a running file operation
+///
+/// The operation stops before its next file (or next chunk for copies)
+/// and returns a partial result with `cancelled` set.
+///
+/// # Arguments
+/// * `operation_id` - ID passed to the operation when it was started
+///
+/// # Returns
+/// * `Ok(true)` - Cancellation was requested
+/// * `Ok(false)` - No running operation has this ID
+#[tauri::command]
+pub fn cancel_operation(
+    registry: State<'_, OperationRegistry>,
+    operation_id: String,
+) -> Result<bool, String> {
+    Ok(registry.cancel(&operation_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_registered_operation() {
+        let registry = OperationRegistry::new();
+        let token = registry.register("op-1");
+
+        assert!(!token.is_cancelled());
+        assert!(registry.cancel("op-1"));
+        assert!(token.is_cancelled());
+    }
+
+    #[test]
+    fn test_cancel_unknown_operation() {
+        let registry = OperationRegistry::new();
+        assert!(!registry.cancel("missing"));
+
+        let token = registry.register("op-1");
+        registry.unregister("op-1", &token);
+        assert!(!registry.cancel("op-1"));
+    }
+
+    #[test]
+    fn test_reused_id_keeps_newer_operation() {
+        let registry = OperationRegistry::new();
+        let first = registry.register("op-1");
+        let second = registry.register("op-1");
+
+        // The first operation finishing must not drop the second one
+        registry.unregister("op-1", &first);
+        assert!(registry.cancel("op-1"));
+        assert!(second.is_cancelled());
+        assert!(!first.is_cancelled());
+
+        registry.unregister("op-1", &second);
+        assert!(!registry.cancel("op-1"));
+    }
+}
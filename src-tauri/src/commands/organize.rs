@@ -0,0 +1,693 @@
+//! Sorting files into a folder structure by category and date
+//!
+//! A folder pattern is a relative path with tokens in braces:
+//!
+//! * `{category}` - file category (`document`, `image`, `video`, `audio`, `other`)
+//! * `{yyyy}`, `{mm}`, `{dd}` - year, month and day of the modification date
+//! * `{ext}` - file extension
+//!
+//! Path segments that render empty (e.g. `{ext}` of a file without extension)
+//! are dropped, as are `.` and `..`, so files always stay inside the target.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use super::file_ops::{
+    categorize_io_error, categorize_trash_error, check_copy_plan, check_delete_plan,
+    copy_single_file, move_path, numbered_path, operation_context, ConflictPolicy, CopyOptions,
+    CopyOutcome, FailedEntry, FailureReason, FileOperationResult, OperationContext, OperationType,
+    OperationWarning, ProcessedFile, ProgressTracker,
+};
+use super::journal::record_operation;
+use super::operation_registry::OperationRegistry;
+use super::preflight::{available_space, check_copy_target, is_writable, PreflightError};
+use super::trash_bin::{finish_result, record_trashed};
+use crate::models::{decode_path, encode_path, FileCategory, FileEntry};
+
+/// Whether organized files are moved or copied
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OrganizeMode {
+    #[default]
+    Move,
+    Copy,
+}
+
+/// Options for organizing files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrganizeOptions {
+    /// Folder pattern relative to the target, e.g. `{category}/{yyyy}/{mm}`
+    pub pattern: String,
+
+    /// Move or copy the files (default: move)
+    #[serde(default)]
+    pub mode: OrganizeMode,
+
+    /// What to do when a file with the same name is already there (default: skip)
+    #[serde(default)]
+    pub conflict_policy: ConflictPolicy,
+}
+
+/// Move or copy scanned files into folders built from a pattern
+///
+/// Folders are created as needed below `target_folder`, which must exist.
+/// Folder entries are skipped with a warning. With the `overwrite` policy the
+/// existing file is moved to the trash first; those files are journaled as a
+/// separate delete operation, so they can be restored after undoing this one.
+/// Files organized earlier in the same batch are never overwritten but renamed.
+///
+/// Progress events are emitted through the `operation_progress` event channel.
+/// If `operation_id` is given, the operation can be stopped with `cancel_operation`.
+///
+/// # Arguments
+/// * `entries` - Scanned files to organize
+/// * `target_folder` - Root folder of the new structure
+/// * `options` - Folder pattern, move/copy mode and conflict policy
+/// * `operation_id` - Optional ID used for progress events and cancellation
+/// * `dry_run` - Only validate and return the planned result (default: false)
+///
+/// # Returns
+/// * `Ok(FileOperationResult)` - Operation result with the new path of every file
+/// * `Err(PreflightError)` - Reason the operation was rejected before it started
+#[tauri::command]
+pub async fn organize_files(
+    app_handle: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    entries: Vec<FileEntry>,
+    target_folder: String,
+    options: OrganizeOptions,
+    operation_id: Option<String>,
+    dry_run: Option<bool>,
+) -> Result<FileOperationResult, PreflightError> {
+    let context = operation_context(
+        &app_handle,
+        &registry,
+        operation_id,
+        dry_run.unwrap_or(false),
+    );
+    let mut overwritten = Vec::new();
+    let result = organize_entries(
+        &entries,
+        &decode_path(&target_folder),
+        &options,
+        &context,
+        &mut overwritten,
+    );
+
+    if let Some(ref id) = context.operation_id {
+        registry.unregister(id, &context.cancel_token);
+    }
+
+    let mut result = result?;
+    if !result.dry_run {
+        if !overwritten.is_empty() {
+            // Journaled before the organize entry, so undo restores them once
+            // the organized files are out of the way again
+            let mut trashed = finish_result(
+                OperationType::Delete,
+                Instant::now(),
+                Vec::new(),
+                overwritten,
+                false,
+            );
+            record_trashed(&app_handle, &mut trashed);
+            record_operation(&app_handle, &mut trashed);
+            result.warnings.extend(trashed.warnings);
+            result.warnings.push(OperationWarning {
+                path: target_folder.clone(),
+                message: format!(
+                    "{} overwritten file(s) moved to the trash",
+                    trashed.success_count
+                ),
+            });
+        }
+        record_operation(&app_handle, &mut result);
+    }
+    Ok(result)
+}
+
+/// Organize entries below `target`, or only plan it in a dry run
+///
+/// Existing files trashed to make room are added to `overwritten`.
+pub fn organize_entries(
+    entries: &[FileEntry],
+    target: &Path,
+    options: &OrganizeOptions,
+    context: &OperationContext,
+    overwritten: &mut Vec<ProcessedFile>,
+) -> Result<FileOperationResult, PreflightError> {
+    let start_time = Instant::now();
+
+    let pattern = parse_pattern(&options.pattern)
+        .map_err(|message| PreflightError::new(FailureReason::InvalidName, message))?;
+
+    let files: Vec<&FileEntry> = entries.iter().filter(|entry| !entry.is_directory).collect();
+    let total_bytes: u64 = files.iter().map(|entry| entry.size).sum();
+    check_target(target, options.mode, total_bytes, context.dry_run)?;
+
+    let operation = match options.mode {
+        OrganizeMode::Move => OperationType::Move,
+        OrganizeMode::Copy => OperationType::Copy,
+    };
+    let mut tracker =
+        ProgressTracker::new(context, operation.clone(), files.len() as u32, total_bytes);
+
+    let mut failed_files: Vec<FailedEntry> = Vec::new();
+    let mut processed_files: Vec<ProcessedFile> = Vec::new();
+    let mut warnings: Vec<OperationWarning> = Vec::new();
+    let mut verified_files = Vec::new();
+    let mut planned: HashSet<PathBuf> = HashSet::new();
+    let mut cancelled = false;
+
+    let copy_options = CopyOptions::default();
+    let mut remaining_space = if context.dry_run {
+        available_space(target).ok()
+    } else {
+        None
+    };
+
+    for entry in entries {
+        if entry.is_directory {
+            warnings.push(OperationWarning {
+                path: entry.path.clone(),
+                message: "Folders are not organized, skipped".to_string(),
+            });
+            continue;
+        }
+        if context.cancel_token.is_cancelled() {
+            cancelled = true;
+            break;
+        }
+        tracker.start_file(&entry.path);
+
+        let source = &decode_path(&entry.path);
+        let folder = target.join(render_pattern(&pattern, entry));
+        let outcome = resolve_destination(
+            source,
+            &folder.join(source.file_name().unwrap_or_default()),
+            options.conflict_policy,
+            &planned,
+        )
+        .and_then(|dest| {
+            if context.dry_run {
+                check_plan(source, &folder, options.mode, &mut remaining_space)?;
+                return Ok((dest, CopyOutcome::Completed));
+            }
+
+            prepare_destination(&folder, &dest, &entry.path, overwritten)?;
+            let outcome = match options.mode {
+                OrganizeMode::Move => move_entry(entry, &dest, &mut tracker)?,
+                OrganizeMode::Copy => copy_single_file(
+                    &entry.path,
+                    &dest,
+                    &copy_options,
+                    &mut tracker,
+                    &mut verified_files,
+                    &mut warnings,
+                )?,
+            };
+            Ok((dest, outcome))
+        });
+
+        match outcome {
+            Ok((dest, CopyOutcome::Completed)) => {
+                processed_files.push(ProcessedFile {
+                    source_path: entry.path.clone(),
+                    target_path: Some(encode_path(&dest)),
+                });
+                planned.insert(dest);
+            }
+            Ok((_, CopyOutcome::Cancelled)) => {
+                cancelled = true;
+                break;
+            }
+            Err(failed) => failed_files.push(failed),
+        }
+        tracker.finish_file();
+    }
+    tracker.report(true);
+
+    Ok(FileOperationResult {
+        operation,
+        success_count: processed_files.len() as u32,
+        failed_count: failed_files.len() as u32,
+        failed_files,
+        duration_ms: start_time.elapsed().as_millis() as u64,
+        cancelled,
+        verified_files,
+        warnings,
+        processed_files,
+        journal_id: None,
+        dry_run: context.dry_run,
+    })
+}
+
+/// Part of a parsed folder pattern
+#[derive(Debug, PartialEq)]
+enum PatternToken {
+    Literal(String),
+    Category,
+    Year,
+    Month,
+    Day,
+    Extension,
+}
+
+/// Parse a folder pattern, rejecting unknown tokens
+fn parse_pattern(pattern: &str) -> Result<Vec<PatternToken>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = pattern;
+
+    while let Some(start) = rest.find('{') {
+        if start > 0 {
+            tokens.push(PatternToken::Literal(rest[..start].to_string()));
+        }
+        let Some(end) = rest[start..].find('}') else {
+            return Err(format!("Unclosed token in pattern: {}", &rest[start..]));
+        };
+        let token = match &rest[start + 1..start + end] {
+            "category" => PatternToken::Category,
+            "yyyy" => PatternToken::Year,
+            "mm" => PatternToken::Month,
+            "dd" => PatternToken::Day,
+            "ext" => PatternToken::Extension,
+            other => return Err(format!("Unknown pattern token: {{{}}}", other)),
+        };
+        tokens.push(token);
+        rest = &rest[start + end + 1..];
+    }
+    if !rest.is_empty() {
+        tokens.push(PatternToken::Literal(rest.to_string()));
+    }
+
+    Ok(tokens)
+}
+
+/// Render the folder pattern for an entry as a relative path
+fn render_pattern(pattern: &[PatternToken], entry: &FileEntry) -> PathBuf {
+    let modified = DateTime::parse_from_rfc3339(&entry.modified_at)
+        .ok()
+        .map(|time| time.with_timezone(&Local));
+    let date = |format: &str| {
+        modified.map_or_else(
+            || "unknown".to_string(),
+            |time| time.format(format).to_string(),
+        )
+    };
+
+    let mut rendered = String::new();
+    for token in pattern {
+        match token {
+            PatternToken::Literal(text) => rendered.push_str(text),
+            PatternToken::Category => rendered.push_str(category_name(&entry.category)),
+            PatternToken::Year => rendered.push_str(&date("%Y")),
+            PatternToken::Month => rendered.push_str(&date("%m")),
+            PatternToken::Day => rendered.push_str(&date("%d")),
+            PatternToken::Extension => rendered.push_str(&entry.extension),
+        }
+    }
+
+    rendered
+        .split(['/', '\\'])
+        .filter(|segment| !matches!(*segment, "" | "." | ".."))
+        .collect()
+}
+
+fn category_name(category: &FileCategory) -> &'static str {
+    match category {
+        FileCategory::Document => "document",
+        FileCategory::Image => "image",
+        FileCategory::Video => "video",
+        FileCategory::Audio => "audio",
+        FileCategory::Folder => "folder",
+        FileCategory::Other => "other",
+    }
+}
+
+/// Check the target folder, and for copies the free space on it
+fn check_target(
+    target: &Path,
+    mode: OrganizeMode,
+    total_bytes: u64,
+    dry_run: bool,
+) -> Result<(), PreflightError> {
+    let required_bytes = match mode {
+        OrganizeMode::Copy if !dry_run => total_bytes,
+        _ => 0,
+    };
+    check_copy_target(target, required_bytes).or_else(|e| match e.reason {
+        // A dry run reports permission problems per file instead
+        FailureReason::PermissionDenied if dry_run => Ok(()),
+        _ => Err(e),
+    })
+}
+
+/// Apply the conflict policy to a destination, counting files planned earlier
+/// in the same batch as existing
+fn resolve_destination(
+    source: &Path,
+    dest: &Path,
+    conflict_policy: ConflictPolicy,
+    planned: &HashSet<PathBuf>,
+) -> Result<PathBuf, FailedEntry> {
+    let source_str = encode_path(source);
+    if source.symlink_metadata().is_err() {
+        return Err(FailedEntry {
+            error_message: format!("File not found: {}", source_str),
+            path: source_str,
+            reason: FailureReason::PathNotFound,
+        });
+    }
+    if source == dest {
+        return Err(FailedEntry {
+            error_message: "File is already in place".to_string(),
+            path: source_str,
+            reason: FailureReason::SameFolder,
+        });
+    }
+
+    let taken = |path: &Path| planned.contains(path) || path.symlink_metadata().is_ok();
+    if !taken(dest) {
+        return Ok(dest.to_path_buf());
+    }
+
+    match conflict_policy {
+        ConflictPolicy::Skip => Err(FailedEntry {
+            error_message: format!("File already exists: {}", dest.display()),
+            path: source_str,
+            reason: FailureReason::FileExists,
+        }),
+        // Only files that were there before are replaced, never one organized
+        // earlier in this batch
+        ConflictPolicy::Overwrite if !planned.contains(dest) => Ok(dest.to_path_buf()),
+        ConflictPolicy::Overwrite | ConflictPolicy::Rename => Ok(numbered_path(dest, taken)),
+    }
+}
+
+fn move_entry(
+    entry: &FileEntry,
+    dest: &Path,
+    tracker: &mut ProgressTracker<'_>,
+) -> Result<CopyOutcome, FailedEntry> {
+    move_path(&decode_path(&entry.path), dest).map_err(|e| io_failed(&entry.path, &e))?;
+    tracker.add_bytes(entry.size);
+    Ok(CopyOutcome::Completed)
+}
+
+/// Create the destination folder and trash a file that is to be overwritten
+fn prepare_destination(
+    folder: &Path,
+    dest: &Path,
+    source_path: &str,
+    overwritten: &mut Vec<ProcessedFile>,
+) -> Result<(), FailedEntry> {
+    std::fs::create_dir_all(folder).map_err(|e| io_failed(source_path, &e))?;
+
+    if dest.symlink_metadata().is_ok() {
+        trash::delete(dest).map_err(|e| {
+            let (reason, message) = categorize_trash_error(&e);
+            FailedEntry {
+                path: source_path.to_string(),
+                reason,
+                error_message: message,
+            }
+        })?;
+        overwritten.push(ProcessedFile {
+            source_path: encode_path(dest),
+            target_path: None,
+        });
+    }
+    Ok(())
+}
+
+/// Check permissions (and space, when copying) for a planned file
+fn check_plan(
+    source: &Path,
+    folder: &Path,
+    mode: OrganizeMode,
+    remaining_space: &mut Option<u64>,
+) -> Result<(), FailedEntry> {
+    let folder_writable = folder
+        .ancestors()
+        .find(|dir| dir.exists())
+        .is_some_and(is_writable);
+
+    match mode {
+        OrganizeMode::Copy => check_copy_plan(source, folder_writable, remaining_space),
+        OrganizeMode::Move => {
+            let failed = |reason: FailureReason, error_message: String| FailedEntry {
+                path: encode_path(source),
+                reason,
+                error_message,
+            };
+            check_delete_plan(source).map_err(|(reason, message)| failed(reason, message))?;
+            if !folder_writable {
+                return Err(failed(
+                    FailureReason::PermissionDenied,
+                    "No write permission on target folder".to_string(),
+                ));
+            }
+            Ok(())
+        }
+    }
+}
+
+fn io_failed(path: &str, error: &std::io::Error) -> FailedEntry {
+    let (reason, message) = categorize_io_error(error);
+    FailedEntry {
+        path: path.to_string(),
+        reason,
+        error_message: message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn file_entry(path: &Path, category: FileCategory, modified_at: &str) -> FileEntry {
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        FileEntry::new(
+            path.to_string_lossy().to_string(),
+            name,
+            false,
+            4,
+            modified_at.to_string(),
+            category,
+            extension,
+            1,
+            path.parent().unwrap().to_string_lossy().to_string(),
+        )
+    }
+
+    fn options(
+        pattern: &str,
+        mode: OrganizeMode,
+        conflict_policy: ConflictPolicy,
+    ) -> OrganizeOptions {
+        OrganizeOptions {
+            pattern: pattern.to_string(),
+            mode,
+            conflict_policy,
+        }
+    }
+
+    #[test]
+    fn test_render_pattern() {
+        let pattern = parse_pattern("{category}/{yyyy}/{mm}/../{ext}").unwrap();
+        let entry = file_entry(
+            Path::new("/downloads/photo.JPG"),
+            FileCategory::Image,
+            "2023-06-15T12:00:00+00:00",
+        );
+
+        assert_eq!(
+            render_pattern(&pattern, &entry),
+            PathBuf::from("image/2023/06/jpg")
+        );
+        assert!(parse_pattern("{unknown}").is_err());
+        assert!(parse_pattern("{category").is_err());
+    }
+
+    #[test]
+    fn test_organize_move() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("report.pdf");
+        let target = temp_dir.path().join("sorted");
+        std::fs::write(&source, "data").unwrap();
+        std::fs::create_dir(&target).unwrap();
+
+        let entries = vec![file_entry(
+            &source,
+            FileCategory::Document,
+            "2024-01-15T12:00:00+00:00",
+        )];
+        let result = organize_entries(
+            &entries,
+            &target,
+            &options(
+                "{category}/{yyyy}",
+                OrganizeMode::Move,
+                ConflictPolicy::Skip,
+            ),
+            &OperationContext::new(),
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        assert_eq!(result.operation, OperationType::Move);
+        assert_eq!(result.success_count, 1);
+        assert!(!source.exists());
+        assert!(target
+            .join("document")
+            .join("2024")
+            .join("report.pdf")
+            .exists());
+    }
+
+    #[test]
+    fn test_organize_copy_with_rename() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("sorted");
+        let first = temp_dir.path().join("a").join("song.mp3");
+        let second = temp_dir.path().join("b").join("song.mp3");
+        for path in [&first, &second] {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "song").unwrap();
+        }
+        std::fs::create_dir(&target).unwrap();
+
+        let entries = vec![
+            file_entry(&first, FileCategory::Audio, "2024-01-15T12:00:00+00:00"),
+            file_entry(&second, FileCategory::Audio, "2024-01-15T12:00:00+00:00"),
+        ];
+        let organize_options = options("{category}", OrganizeMode::Copy, ConflictPolicy::Rename);
+
+        // The dry run already accounts for the name clash within the batch
+        let dry_run_context = OperationContext {
+            dry_run: true,
+            ..OperationContext::default()
+        };
+        let plan = organize_entries(
+            &entries,
+            &target,
+            &organize_options,
+            &dry_run_context,
+            &mut Vec::new(),
+        )
+        .unwrap();
+        assert_eq!(plan.success_count, 2);
+        assert!(plan.processed_files[1]
+            .target_path
+            .as_deref()
+            .unwrap()
+            .ends_with("song (1).mp3"));
+        assert!(!target.join("audio").exists());
+
+        let result = organize_entries(
+            &entries,
+            &target,
+            &organize_options,
+            &OperationContext::new(),
+            &mut Vec::new(),
+        )
+        .unwrap();
+        assert_eq!(result.success_count, 2);
+        assert!(first.exists());
+        assert!(target.join("audio").join("song.mp3").exists());
+        assert!(target.join("audio").join("song (1).mp3").exists());
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    fn test_organize_overwrite_keeps_batch_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("sorted");
+        let first = temp_dir.path().join("a").join("notes.txt");
+        let second = temp_dir.path().join("b").join("notes.txt");
+        for path in [&first, &second] {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, path.to_string_lossy().as_bytes()).unwrap();
+        }
+        std::fs::create_dir_all(target.join("other")).unwrap();
+        std::fs::write(target.join("other").join("notes.txt"), "old").unwrap();
+
+        let entries = vec![
+            file_entry(&first, FileCategory::Other, ""),
+            file_entry(&second, FileCategory::Other, ""),
+        ];
+        let mut overwritten = Vec::new();
+        let result = organize_entries(
+            &entries,
+            &target,
+            &options("{category}", OrganizeMode::Move, ConflictPolicy::Overwrite),
+            &OperationContext::new(),
+            &mut overwritten,
+        )
+        .unwrap();
+
+        // Only the file that was there before is replaced and reported
+        assert_eq!(result.success_count, 2);
+        assert_eq!(overwritten.len(), 1);
+        assert_eq!(
+            overwritten[0].source_path,
+            encode_path(&target.join("other").join("notes.txt"))
+        );
+        assert_eq!(
+            std::fs::read_to_string(target.join("other").join("notes.txt")).unwrap(),
+            first.to_string_lossy()
+        );
+        assert_eq!(
+            std::fs::read_to_string(target.join("other").join("notes (1).txt")).unwrap(),
+            second.to_string_lossy()
+        );
+    }
+
+    #[test]
+    fn test_organize_skip_conflict() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("notes.txt");
+        let target = temp_dir.path().join("sorted");
+        std::fs::write(&source, "new").unwrap();
+        std::fs::create_dir_all(target.join("other")).unwrap();
+        std::fs::write(target.join("other").join("notes.txt"), "old").unwrap();
+
+        let entries = vec![file_entry(&source, FileCategory::Other, "")];
+        let result = organize_entries(
+            &entries,
+            &target,
+            &options("{category}", OrganizeMode::Move, ConflictPolicy::Skip),
+            &OperationContext::new(),
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        assert_eq!(result.failed_files[0].reason, FailureReason::FileExists);
+        assert!(source.exists());
+    }
+
+    #[test]
+    fn test_organize_missing_target() {
+        let temp_dir = TempDir::new().unwrap();
+        let result = organize_entries(
+            &[],
+            &temp_dir.path().join("missing"),
+            &options("{category}", OrganizeMode::Move, ConflictPolicy::Skip),
+            &OperationContext::new(),
+            &mut Vec::new(),
+        );
+
+        assert_eq!(result.unwrap_err().reason, FailureReason::TargetNotFound);
+    }
+}
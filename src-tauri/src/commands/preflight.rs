@@ -0,0 +1,155 @@
+//! Permission and free-space checks that do not modify the filesystem
+
+use std::fmt;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::file_ops::FailureReason;
+
+/// Reason an operation was rejected before any file was touched
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreflightError {
+    pub reason: FailureReason,
+    pub message: String,
+    /// Bytes the operation needs on the target filesystem, if relevant
+    pub required_bytes: Option<u64>,
+    /// Bytes available on the target filesystem, if relevant
+    pub available_bytes: Option<u64>,
+}
+
+impl PreflightError {
+    pub(super) fn new(reason: FailureReason, message: String) -> Self {
+        Self {
+            reason,
+            message,
+            required_bytes: None,
+            available_bytes: None,
+        }
+    }
+}
+
+impl fmt::Display for PreflightError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Check that a target folder exists, is writable and has room for `required_bytes`
+///
+/// The free-space check is skipped if the filesystem cannot report it.
+pub(super) fn check_copy_target(target: &Path, required_bytes: u64) -> Result<(), PreflightError> {
+    if !target.exists() {
+        return Err(PreflightError::new(
+            FailureReason::TargetNotFound,
+            format!("Target folder not found: {}", target.display()),
+        ));
+    }
+    if !target.is_dir() {
+        return Err(PreflightError::new(
+            FailureReason::TargetNotFound,
+            format!("Target path is not a directory: {}", target.display()),
+        ));
+    }
+    if !is_writable(target) {
+        return Err(PreflightError::new(
+            FailureReason::PermissionDenied,
+            format!("No write permission on target folder: {}", target.display()),
+        ));
+    }
+
+    if let Ok(available) = available_space(target) {
+        if required_bytes > available {
+            return Err(PreflightError {
+                reason: FailureReason::InsufficientSpace,
+                message: format!(
+                    "Not enough free space on target: need {} bytes, {} available",
+                    required_bytes, available
+                ),
+                required_bytes: Some(required_bytes),
+                available_bytes: Some(available),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Check whether the current user may create or remove entries in a folder
+#[cfg(unix)]
+pub(super) fn is_writable(path: &Path) -> bool {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let Ok(c_path) = CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    // SAFETY: c_path is a valid NUL-terminated string that outlives the call
+    unsafe { libc::access(c_path.as_ptr(), libc::W_OK) == 0 }
+}
+
+/// Check whether the current user may create or remove entries in a folder
+#[cfg(not(unix))]
+pub(super) fn is_writable(path: &Path) -> bool {
+    std::fs::metadata(path)
+        .map(|metadata| !metadata.permissions().readonly())
+        .unwrap_or(false)
+}
+
+/// Check whether the current user may read a file
+#[cfg(unix)]
+pub(super) fn is_readable(path: &Path) -> bool {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let Ok(c_path) = CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    // SAFETY: c_path is a valid NUL-terminated string that outlives the call
+    unsafe { libc::access(c_path.as_ptr(), libc::R_OK) == 0 }
+}
+
+/// Check whether the current user may read a file
+#[cfg(not(unix))]
+pub(super) fn is_readable(path: &Path) -> bool {
+    std::fs::metadata(path).is_ok()
+}
+
+/// Free space available to the current user on the filesystem containing `path`
+pub(super) fn available_space(path: &Path) -> std::io::Result<u64> {
+    fs4::available_space(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_temp_dir_is_writable() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(is_writable(temp_dir.path()));
+        assert!(!is_writable(&temp_dir.path().join("missing")));
+    }
+
+    #[test]
+    fn test_check_copy_target() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(check_copy_target(temp_dir.path(), 0).is_ok());
+
+        let missing = check_copy_target(&temp_dir.path().join("missing"), 0).unwrap_err();
+        assert_eq!(missing.reason, FailureReason::TargetNotFound);
+
+        let full = check_copy_target(temp_dir.path(), u64::MAX).unwrap_err();
+        assert_eq!(full.reason, FailureReason::InsufficientSpace);
+        assert_eq!(full.required_bytes, Some(u64::MAX));
+        assert!(full.available_bytes.is_some());
+    }
+
+    #[test]
+    fn test_available_space() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(available_space(temp_dir.path()).is_ok());
+    }
+}
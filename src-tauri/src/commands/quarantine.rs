@@ -0,0 +1,1077 @@
+//! Quarantine folder used instead of the system trash
+//!
+//! On machines without a working desktop trash (headless systems, removable
+//! drives, network mounts) files can be moved into a quarantine folder instead.
+//! Each item is stored as `<quarantine>/<id>/<original path>` and recorded in a
+//! manifest inside the quarantine folder, so the folder stays self-describing
+//! even when it lives on a removable drive.
+//!
+//! `delete_files` uses the quarantine according to the settings: for every
+//! delete, or only for files the system trash fails on.
+
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Instant;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use super::file_ops::{
+    categorize_io_error, check_delete_plan, delete_paths, move_path, operation_context,
+    unique_path, ConflictPolicy, FailedEntry, FailureReason, FileOperationResult, OperationContext,
+    OperationType, OperationWarning, ProcessedFile, ProgressTracker,
+};
+use super::journal::record_operation;
+use super::json_store::{JsonFile, JsonStore};
+use super::operation_registry::OperationRegistry;
+use super::preflight::is_writable;
+use super::trash_bin::finish_result;
+use crate::models::{decode_path, encode_path};
+
+/// File name of the quarantine settings in the app data folder
+const SETTINGS_FILE: &str = "quarantine_settings.json";
+
+/// File name of the manifest inside the quarantine folder
+const MANIFEST_FILE: &str = "quarantine_manifest.json";
+
+/// Name of the default quarantine folder in the app data folder
+const DEFAULT_FOLDER_NAME: &str = "quarantine";
+
+/// Default number of days items are kept before they are purged
+const DEFAULT_RETENTION_DAYS: u32 = 30;
+
+/// Counter that keeps item IDs unique within the same millisecond
+static ITEM_SEQUENCE: AtomicU32 = AtomicU32::new(0);
+
+/// User-configurable quarantine settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct QuarantineSettings {
+    /// Quarantine folder (default: `quarantine` in the app data folder)
+    pub folder: Option<String>,
+
+    /// Days after which quarantined items are purged automatically (0 = never)
+    pub retention_days: u32,
+
+    /// Quarantine files passed to `delete_files` instead of using the system trash
+    pub delete_to_quarantine: bool,
+
+    /// Quarantine files the system trash fails on, e.g. on mounts without a trash
+    pub fallback_to_quarantine: bool,
+}
+
+impl Default for QuarantineSettings {
+    fn default() -> Self {
+        Self {
+            folder: None,
+            retention_days: DEFAULT_RETENTION_DAYS,
+            delete_to_quarantine: false,
+            fallback_to_quarantine: true,
+        }
+    }
+}
+
+impl QuarantineSettings {
+    /// Load the settings, using defaults if the file is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the settings to disk
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create data folder: {}", e))?;
+        }
+        let content = serde_json::to_string(self)
+            .map_err(|e| format!("Failed to serialize quarantine settings: {}", e))?;
+        std::fs::write(path, content)
+            .map_err(|e| format!("Failed to write quarantine settings: {}", e))
+    }
+}
+
+/// An item stored in the quarantine folder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantinedItem {
+    /// Unique item ID (also the name of the item's folder in the quarantine)
+    pub id: String,
+
+    /// Path the item was quarantined from
+    pub original_path: String,
+
+    /// Location of the item, relative to the quarantine folder
+    pub stored_path: String,
+
+    /// Time the item was quarantined (ISO 8601 format)
+    pub quarantined_at: String,
+
+    pub is_directory: bool,
+
+    /// Size in bytes (files only)
+    pub size: Option<u64>,
+}
+
+/// List of quarantined items, stored inside the quarantine folder
+///
+/// Loaded and saved through the `JsonStore<QuarantineManifest>` in managed state.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantineManifest {
+    pub items: Vec<QuarantinedItem>,
+}
+
+impl QuarantineManifest {
+    /// Create the store that guards the manifests of quarantine folders
+    pub fn store() -> JsonStore<Self> {
+        JsonStore::new("quarantine manifest")
+    }
+
+    /// Path of the manifest of a quarantine folder
+    pub fn path(folder: &Path) -> PathBuf {
+        folder.join(MANIFEST_FILE)
+    }
+}
+
+/// Get the current quarantine settings
+#[tauri::command]
+pub async fn get_quarantine_settings(app_handle: AppHandle) -> Result<QuarantineSettings, String> {
+    Ok(QuarantineSettings::load(&settings_path(&app_handle)?))
+}
+
+/// Change the quarantine folder, retention period or use for deletes
+///
+/// Items already quarantined stay in the previous folder.
+#[tauri::command]
+pub async fn set_quarantine_settings(
+    app_handle: AppHandle,
+    settings: QuarantineSettings,
+) -> Result<(), String> {
+    settings.save(&settings_path(&app_handle)?)
+}
+
+/// Move files into the quarantine folder instead of the system trash
+///
+/// Expired items are purged first according to the retention setting.
+/// Progress events are emitted through the `operation_progress` event channel.
+/// If `operation_id` is given, the operation can be stopped with `cancel_operation`.
+///
+/// # Arguments
+/// * `paths` - Vector of file paths to quarantine
+/// * `operation_id` - Optional ID used for progress events and cancellation
+/// * `dry_run` - Only validate and return the planned result (default: false)
+///
+/// # Returns
+/// * `Ok(FileOperationResult)` - Operation result with success/failure counts
+/// * `Err(String)` - Error message if the quarantine folder cannot be resolved
+#[tauri::command]
+pub async fn quarantine_files(
+    app_handle: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    manifests: State<'_, JsonStore<QuarantineManifest>>,
+    paths: Vec<String>,
+    operation_id: Option<String>,
+    dry_run: Option<bool>,
+) -> Result<FileOperationResult, String> {
+    let settings = QuarantineSettings::load(&settings_path(&app_handle)?);
+    let folder = quarantine_folder(&app_handle, &settings)?;
+
+    let context = operation_context(
+        &app_handle,
+        &registry,
+        operation_id,
+        dry_run.unwrap_or(false),
+    );
+    let quarantined = quarantine_into(
+        &manifests,
+        &folder,
+        settings.retention_days,
+        &paths,
+        &context,
+    );
+
+    if let Some(ref id) = context.operation_id {
+        registry.unregister(id, &context.cancel_token);
+    }
+
+    let mut result = quarantined?;
+    if !result.dry_run {
+        record_operation(&app_handle, &mut result);
+    }
+    Ok(result)
+}
+
+/// Delete paths for `delete_files`, using the quarantine as configured
+///
+/// The result is always a `Delete` result. Files that went to the quarantine
+/// have their location in the quarantine folder as target path; trashed files
+/// have none.
+pub(super) fn delete_with_settings(
+    app_handle: &AppHandle,
+    paths: &[String],
+    context: &OperationContext,
+) -> Result<FileOperationResult, String> {
+    let settings = QuarantineSettings::load(&settings_path(app_handle)?);
+    let folder = quarantine_folder(app_handle, &settings);
+    let manifests = app_handle.state::<JsonStore<QuarantineManifest>>();
+
+    if settings.delete_to_quarantine {
+        let mut result = quarantine_into(
+            &manifests,
+            &folder?,
+            settings.retention_days,
+            paths,
+            context,
+        )?;
+        result.operation = OperationType::Delete;
+        return Ok(result);
+    }
+
+    let mut result = delete_paths(paths, context);
+    if settings.fallback_to_quarantine {
+        match folder {
+            Ok(folder) => quarantine_trash_failures(
+                &manifests,
+                &folder,
+                settings.retention_days,
+                &mut result,
+                context,
+            ),
+            Err(message) => result.warnings.push(OperationWarning {
+                path: SETTINGS_FILE.to_string(),
+                message,
+            }),
+        }
+    }
+    Ok(result)
+}
+
+/// Retry files of a delete result that the system trash failed on in the quarantine
+///
+/// Only failures of unknown kind are retried; permission problems, locked
+/// and missing files would fail in the quarantine as well. Quarantined files
+/// are moved from the failures to the processed files with a warning.
+pub fn quarantine_trash_failures(
+    manifests: &JsonStore<QuarantineManifest>,
+    folder: &Path,
+    retention_days: u32,
+    result: &mut FileOperationResult,
+    context: &OperationContext,
+) {
+    if result.dry_run || result.cancelled {
+        return;
+    }
+    let (retry, failed): (Vec<FailedEntry>, Vec<FailedEntry>) =
+        std::mem::take(&mut result.failed_files)
+            .into_iter()
+            .partition(|failed| failed.reason == FailureReason::Unknown);
+    result.failed_files = failed;
+    if retry.is_empty() {
+        return;
+    }
+
+    let paths: Vec<String> = retry.iter().map(|failed| failed.path.clone()).collect();
+    let quarantined = match quarantine_into(manifests, folder, retention_days, &paths, context) {
+        Ok(quarantined) => quarantined,
+        Err(message) => {
+            result.warnings.push(OperationWarning {
+                path: encode_path(&QuarantineManifest::path(folder)),
+                message,
+            });
+            result.failed_files.extend(retry);
+            result.failed_count = result.failed_files.len() as u32;
+            return;
+        }
+    };
+
+    result.warnings.extend(
+        quarantined
+            .processed_files
+            .iter()
+            .map(|file| OperationWarning {
+                path: file.source_path.clone(),
+                message: "System trash failed, moved to quarantine instead".to_string(),
+            }),
+    );
+    result.warnings.extend(quarantined.warnings);
+    result.processed_files.extend(quarantined.processed_files);
+    for mut failed in quarantined.failed_files {
+        if let Some(trash_failure) = retry.iter().find(|retried| retried.path == failed.path) {
+            failed.error_message = format!(
+                "{}; quarantine failed too: {}",
+                trash_failure.error_message, failed.error_message
+            );
+        }
+        result.failed_files.push(failed);
+    }
+    result.cancelled = quarantined.cancelled;
+    result.success_count = result.processed_files.len() as u32;
+    result.failed_count = result.failed_files.len() as u32;
+}
+
+/// Purge expired items, quarantine the paths and save the manifest
+fn quarantine_into(
+    manifests: &JsonStore<QuarantineManifest>,
+    folder: &Path,
+    retention_days: u32,
+    paths: &[String],
+    context: &OperationContext,
+) -> Result<FileOperationResult, String> {
+    let mut manifest = manifests.open(&QuarantineManifest::path(folder))?;
+    if !context.dry_run {
+        purge_expired(folder, &mut manifest, retention_days, Utc::now());
+    }
+    let mut result = quarantine_paths(folder, &mut manifest, paths, context);
+    if !result.dry_run {
+        save_manifest(&manifest, folder, &mut result);
+    }
+    Ok(result)
+}
+
+/// List quarantined items, newest first
+///
+/// Expired items are purged before listing.
+#[tauri::command]
+pub async fn list_quarantined_items(
+    app_handle: AppHandle,
+    manifests: State<'_, JsonStore<QuarantineManifest>>,
+) -> Result<Vec<QuarantinedItem>, String> {
+    let settings = QuarantineSettings::load(&settings_path(&app_handle)?);
+    let folder = quarantine_folder(&app_handle, &settings)?;
+
+    let mut manifest = manifests.open(&QuarantineManifest::path(&folder))?;
+    let mut result = purge_expired(&folder, &mut manifest, settings.retention_days, Utc::now());
+    if result.success_count > 0 {
+        save_manifest(&manifest, &folder, &mut result);
+    }
+
+    let mut items = manifest.items.clone();
+    items.sort_by(|a, b| b.quarantined_at.cmp(&a.quarantined_at));
+    Ok(items)
+}
+
+/// Move quarantined items back to their original paths
+///
+/// With the `overwrite` policy, an item occupying the original path is itself
+/// moved into the quarantine and journaled as a separate quarantine operation.
+///
+/// # Arguments
+/// * `ids` - IDs of the items to restore, as returned by `list_quarantined_items`
+/// * `conflict_policy` - What to do when the original path is occupied (default: skip)
+/// * `dry_run` - Only validate and return the planned result (default: false)
+#[tauri::command]
+pub async fn restore_quarantined_items(
+    app_handle: AppHandle,
+    manifests: State<'_, JsonStore<QuarantineManifest>>,
+    ids: Vec<String>,
+    conflict_policy: Option<ConflictPolicy>,
+    dry_run: Option<bool>,
+) -> Result<FileOperationResult, String> {
+    let settings = QuarantineSettings::load(&settings_path(&app_handle)?);
+    let folder = quarantine_folder(&app_handle, &settings)?;
+
+    let mut manifest = manifests.open(&QuarantineManifest::path(&folder))?;
+    let known_ids: HashSet<String> = manifest.items.iter().map(|item| item.id.clone()).collect();
+    let mut result = restore_items(
+        &folder,
+        &mut manifest,
+        &ids,
+        conflict_policy.unwrap_or_default(),
+        dry_run.unwrap_or(false),
+    );
+    if result.dry_run {
+        return Ok(result);
+    }
+
+    save_manifest(&manifest, &folder, &mut result);
+    // Items added by the restore are files that were in the way
+    let displaced: Vec<ProcessedFile> = manifest
+        .items
+        .iter()
+        .filter(|item| !known_ids.contains(&item.id))
+        .map(|item| ProcessedFile {
+            source_path: item.original_path.clone(),
+            target_path: Some(encode_path(&folder.join(decode_path(&item.stored_path)))),
+        })
+        .collect();
+    // Undo locks the journal before the manifest, so never the other way round
+    drop(manifest);
+
+    if !displaced.is_empty() {
+        // Journaled before the restore entry, so undo brings them back once
+        // the restored files are out of the way again
+        let mut quarantined = finish_result(
+            OperationType::Quarantine,
+            Instant::now(),
+            Vec::new(),
+            displaced,
+            false,
+        );
+        record_operation(&app_handle, &mut quarantined);
+        result.warnings.extend(quarantined.warnings);
+        result.warnings.push(OperationWarning {
+            path: encode_path(&folder),
+            message: format!(
+                "{} existing file(s) moved to the quarantine",
+                quarantined.success_count
+            ),
+        });
+    }
+    record_operation(&app_handle, &mut result);
+    Ok(result)
+}
+
+/// Permanently remove quarantined items
+///
+/// # Arguments
+/// * `ids` - IDs of the items to purge, as returned by `list_quarantined_items`
+/// * `dry_run` - Only validate and return the planned result (default: false)
+#[tauri::command]
+pub async fn purge_quarantined_items(
+    app_handle: AppHandle,
+    manifests: State<'_, JsonStore<QuarantineManifest>>,
+    ids: Vec<String>,
+    dry_run: Option<bool>,
+) -> Result<FileOperationResult, String> {
+    let settings = QuarantineSettings::load(&settings_path(&app_handle)?);
+    let folder = quarantine_folder(&app_handle, &settings)?;
+
+    let mut manifest = manifests.open(&QuarantineManifest::path(&folder))?;
+    let mut result = purge_items(&folder, &mut manifest, &ids, dry_run.unwrap_or(false));
+    if !result.dry_run {
+        save_manifest(&manifest, &folder, &mut result);
+        // Undo locks the journal before the manifest, so never the other way round
+        drop(manifest);
+        record_operation(&app_handle, &mut result);
+    }
+    Ok(result)
+}
+
+/// Restore the most recently quarantined item for each of the given original paths
+///
+/// Used to undo quarantine operations. Occupied original paths are skipped.
+pub(super) fn restore_original_paths(
+    app_handle: &AppHandle,
+    paths: &[String],
+) -> Result<FileOperationResult, String> {
+    let settings = QuarantineSettings::load(&settings_path(app_handle)?);
+    let folder = quarantine_folder(app_handle, &settings)?;
+    let manifests = app_handle.state::<JsonStore<QuarantineManifest>>();
+    let mut manifest = manifests.open(&QuarantineManifest::path(&folder))?;
+
+    let ids: Vec<String> = paths
+        .iter()
+        .map(|path| {
+            manifest
+                .items
+                .iter()
+                .filter(|item| &item.original_path == path)
+                .max_by(|a, b| a.quarantined_at.cmp(&b.quarantined_at))
+                .map_or_else(|| path.clone(), |item| item.id.clone())
+        })
+        .collect();
+
+    let mut result = restore_items(&folder, &mut manifest, &ids, ConflictPolicy::Skip, false);
+    save_manifest(&manifest, &folder, &mut result);
+    Ok(result)
+}
+
+/// Move paths into the quarantine folder and add them to the manifest
+///
+/// In a dry run only permissions are checked and the planned locations reported.
+pub fn quarantine_paths(
+    folder: &Path,
+    manifest: &mut QuarantineManifest,
+    paths: &[String],
+    context: &OperationContext,
+) -> FileOperationResult {
+    let start_time = Instant::now();
+
+    let total_bytes = paths
+        .iter()
+        .filter_map(|path| std::fs::symlink_metadata(decode_path(path)).ok())
+        .filter(std::fs::Metadata::is_file)
+        .map(|metadata| metadata.len())
+        .sum();
+    let mut tracker = ProgressTracker::new(
+        context,
+        OperationType::Quarantine,
+        paths.len() as u32,
+        total_bytes,
+    );
+
+    let mut failed_files: Vec<FailedEntry> = Vec::new();
+    let mut processed_files: Vec<ProcessedFile> = Vec::new();
+    let mut cancelled = false;
+
+    for path_str in paths {
+        if context.cancel_token.is_cancelled() {
+            cancelled = true;
+            break;
+        }
+        tracker.start_file(path_str);
+
+        match quarantine_path(folder, path_str, context.dry_run) {
+            Ok(item) => {
+                processed_files.push(ProcessedFile {
+                    source_path: item.original_path.clone(),
+                    target_path: Some(encode_path(&folder.join(decode_path(&item.stored_path)))),
+                });
+                tracker.add_bytes(item.size.unwrap_or(0));
+                if !context.dry_run {
+                    manifest.items.push(item);
+                }
+            }
+            Err(failed) => failed_files.push(failed),
+        }
+        tracker.finish_file();
+    }
+    tracker.report(true);
+
+    let mut result = finish_result(
+        OperationType::Quarantine,
+        start_time,
+        failed_files,
+        processed_files,
+        context.dry_run,
+    );
+    result.cancelled = cancelled;
+    result
+}
+
+/// Move quarantined items back to their original paths
+///
+/// IDs that are not in the manifest are reported as `PathNotFound`.
+pub fn restore_items(
+    folder: &Path,
+    manifest: &mut QuarantineManifest,
+    ids: &[String],
+    conflict_policy: ConflictPolicy,
+    dry_run: bool,
+) -> FileOperationResult {
+    let start_time = Instant::now();
+
+    let mut failed_files: Vec<FailedEntry> = Vec::new();
+    let mut processed_files: Vec<ProcessedFile> = Vec::new();
+    let mut restored_ids: HashSet<String> = HashSet::new();
+
+    for id in ids {
+        let Some(item) = manifest.items.iter().find(|item| &item.id == id).cloned() else {
+            failed_files.push(not_in_quarantine(id));
+            continue;
+        };
+
+        match restore_item(folder, manifest, &item, conflict_policy, dry_run) {
+            Ok(restored_path) => {
+                processed_files.push(ProcessedFile {
+                    source_path: item.original_path.clone(),
+                    target_path: Some(encode_path(&restored_path)),
+                });
+                restored_ids.insert(item.id);
+            }
+            Err(failed) => failed_files.push(failed),
+        }
+    }
+
+    if !dry_run {
+        manifest
+            .items
+            .retain(|item| !restored_ids.contains(&item.id));
+    }
+
+    finish_result(
+        OperationType::Restore,
+        start_time,
+        failed_files,
+        processed_files,
+        dry_run,
+    )
+}
+
+/// Permanently remove quarantined items
+///
+/// IDs that are not in the manifest are reported as `PathNotFound`.
+pub fn purge_items(
+    folder: &Path,
+    manifest: &mut QuarantineManifest,
+    ids: &[String],
+    dry_run: bool,
+) -> FileOperationResult {
+    let start_time = Instant::now();
+
+    let mut failed_files: Vec<FailedEntry> = Vec::new();
+    let mut processed_files: Vec<ProcessedFile> = Vec::new();
+    let mut purged_ids: HashSet<String> = HashSet::new();
+
+    for id in ids {
+        let Some(item) = manifest.items.iter().find(|item| &item.id == id) else {
+            failed_files.push(not_in_quarantine(id));
+            continue;
+        };
+
+        let purged = if dry_run {
+            Ok(())
+        } else {
+            remove_item_folder(folder, &item.id)
+        };
+        match purged {
+            Ok(()) => {
+                processed_files.push(ProcessedFile {
+                    source_path: item.original_path.clone(),
+                    target_path: None,
+                });
+                purged_ids.insert(item.id.clone());
+            }
+            Err(e) => {
+                let (reason, message) = categorize_io_error(&e);
+                failed_files.push(FailedEntry {
+                    path: item.original_path.clone(),
+                    reason,
+                    error_message: message,
+                });
+            }
+        }
+    }
+
+    if !dry_run {
+        manifest.items.retain(|item| !purged_ids.contains(&item.id));
+    }
+
+    finish_result(
+        OperationType::Purge,
+        start_time,
+        failed_files,
+        processed_files,
+        dry_run,
+    )
+}
+
+/// Purge items quarantined longer than the retention period (0 keeps items forever)
+pub fn purge_expired(
+    folder: &Path,
+    manifest: &mut QuarantineManifest,
+    retention_days: u32,
+    now: DateTime<Utc>,
+) -> FileOperationResult {
+    let expired: Vec<String> = if retention_days == 0 {
+        Vec::new()
+    } else {
+        let cutoff = now - Duration::days(i64::from(retention_days));
+        manifest
+            .items
+            .iter()
+            .filter(|item| {
+                DateTime::parse_from_rfc3339(&item.quarantined_at)
+                    .is_ok_and(|time| time.with_timezone(&Utc) < cutoff)
+            })
+            .map(|item| item.id.clone())
+            .collect()
+    };
+
+    purge_items(folder, manifest, &expired, false)
+}
+
+/// Move a single path into the quarantine and return its manifest entry
+fn quarantine_path(
+    folder: &Path,
+    path_str: &str,
+    dry_run: bool,
+) -> Result<QuarantinedItem, FailedEntry> {
+    let failed = |reason: FailureReason, error_message: String| FailedEntry {
+        path: path_str.to_string(),
+        reason,
+        error_message,
+    };
+
+    let path = std::path::absolute(decode_path(path_str))
+        .map_err(|e| failed(FailureReason::PathNotFound, e.to_string()))?;
+    let Ok(metadata) = std::fs::symlink_metadata(&path) else {
+        return Err(failed(
+            FailureReason::PathNotFound,
+            format!("File not found: {}", path_str),
+        ));
+    };
+    if path.starts_with(folder) || folder.starts_with(&path) {
+        return Err(failed(
+            FailureReason::SameFolder,
+            format!("Path overlaps the quarantine folder: {}", path_str),
+        ));
+    }
+
+    let id = next_item_id();
+    let stored_path = Path::new(&id).join(relative_original_path(&path));
+    let item = QuarantinedItem {
+        id,
+        original_path: encode_path(&path),
+        stored_path: encode_path(&stored_path),
+        quarantined_at: Utc::now().to_rfc3339(),
+        is_directory: metadata.is_dir(),
+        size: metadata.is_file().then_some(metadata.len()),
+    };
+
+    if dry_run {
+        check_delete_plan(&path).map_err(|(reason, message)| failed(reason, message))?;
+        let existing_parent = folder.ancestors().find(|dir| dir.exists());
+        if let Some(parent) = existing_parent.filter(|parent| !is_writable(parent)) {
+            return Err(failed(
+                FailureReason::PermissionDenied,
+                format!("No write permission on folder: {}", parent.display()),
+            ));
+        }
+        return Ok(item);
+    }
+
+    let dest = folder.join(&stored_path);
+    dest.parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| move_path(&path, &dest))
+        .map_err(|e| {
+            let _ = remove_item_folder(folder, &item.id);
+            let (reason, message) = categorize_io_error(&e);
+            failed(reason, message)
+        })?;
+
+    Ok(item)
+}
+
+/// Move one quarantined item back and return the path it was restored to
+fn restore_item(
+    folder: &Path,
+    manifest: &mut QuarantineManifest,
+    item: &QuarantinedItem,
+    conflict_policy: ConflictPolicy,
+    dry_run: bool,
+) -> Result<PathBuf, FailedEntry> {
+    let failed = |reason: FailureReason, error_message: String| FailedEntry {
+        path: item.original_path.clone(),
+        reason,
+        error_message,
+    };
+
+    let stored = folder.join(decode_path(&item.stored_path));
+    if stored.symlink_metadata().is_err() {
+        return Err(failed(
+            FailureReason::PathNotFound,
+            format!("Quarantined item is missing: {}", stored.display()),
+        ));
+    }
+
+    let original_path = decode_path(&item.original_path);
+    let restored_path = if original_path.symlink_metadata().is_err() {
+        original_path
+    } else {
+        match conflict_policy {
+            ConflictPolicy::Skip => {
+                return Err(failed(
+                    FailureReason::FileExists,
+                    format!("File already exists: {}", item.original_path),
+                ))
+            }
+            ConflictPolicy::Overwrite => {
+                if !dry_run {
+                    let existing = quarantine_path(folder, &item.original_path, false)?;
+                    manifest.items.push(existing);
+                }
+                original_path
+            }
+            ConflictPolicy::Rename => unique_path(&original_path),
+        }
+    };
+
+    if dry_run {
+        return Ok(restored_path);
+    }
+
+    restored_path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| move_path(&stored, &restored_path))
+        .map_err(|e| {
+            let (reason, message) = categorize_io_error(&e);
+            failed(reason, message)
+        })?;
+    let _ = remove_item_folder(folder, &item.id);
+
+    Ok(restored_path)
+}
+
+/// Remove the folder holding a quarantined item
+fn remove_item_folder(folder: &Path, id: &str) -> std::io::Result<()> {
+    match std::fs::remove_dir_all(folder.join(id)) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+/// Turn an absolute path into a relative one that keeps its folder structure
+///
+/// Windows drive prefixes become a plain folder, e.g. `C:\Users\a.txt` is
+/// stored as `C/Users/a.txt`.
+fn relative_original_path(path: &Path) -> PathBuf {
+    let mut relative = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Prefix(prefix) => {
+                let name: String = prefix
+                    .as_os_str()
+                    .to_string_lossy()
+                    .chars()
+                    .filter(|c| !matches!(c, ':' | '\\' | '/' | '?' | '.'))
+                    .collect();
+                if !name.is_empty() {
+                    relative.push(name);
+                }
+            }
+            Component::Normal(name) => relative.push(name),
+            Component::RootDir | Component::CurDir | Component::ParentDir => {}
+        }
+    }
+    relative
+}
+
+fn next_item_id() -> String {
+    let sequence = ITEM_SEQUENCE.fetch_add(1, Ordering::Relaxed);
+    format!("{}-{}", Utc::now().timestamp_millis(), sequence)
+}
+
+fn not_in_quarantine(id: &str) -> FailedEntry {
+    FailedEntry {
+        path: id.to_string(),
+        reason: FailureReason::PathNotFound,
+        error_message: format!("Item not found in quarantine: {}", id),
+    }
+}
+
+/// Save the manifest, reporting failures as a warning on the result
+fn save_manifest(
+    manifest: &JsonFile<'_, QuarantineManifest>,
+    folder: &Path,
+    result: &mut FileOperationResult,
+) {
+    if let Err(message) = manifest.save() {
+        result.warnings.push(OperationWarning {
+            path: encode_path(&QuarantineManifest::path(folder)),
+            message,
+        });
+    }
+}
+
+fn quarantine_folder(
+    app_handle: &AppHandle,
+    settings: &QuarantineSettings,
+) -> Result<PathBuf, String> {
+    match settings.folder {
+        Some(ref folder) => std::path::absolute(decode_path(folder))
+            .map_err(|e| format!("Invalid quarantine folder: {}", e)),
+        None => app_handle
+            .path()
+            .app_data_dir()
+            .map(|dir| dir.join(DEFAULT_FOLDER_NAME))
+            .map_err(|e| format!("Failed to resolve app data folder: {}", e)),
+    }
+}
+
+fn settings_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map(|dir| dir.join(SETTINGS_FILE))
+        .map_err(|e| format!("Failed to resolve app data folder: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn quarantine_one(folder: &Path, manifest: &mut QuarantineManifest, path: &Path) {
+        let paths = vec![path.to_string_lossy().to_string()];
+        let result = quarantine_paths(folder, manifest, &paths, &OperationContext::new());
+        assert_eq!(result.success_count, 1);
+    }
+
+    #[test]
+    fn test_quarantine_and_restore() {
+        let temp_dir = TempDir::new().unwrap();
+        let folder = temp_dir.path().join("quarantine");
+        let source = temp_dir.path().join("docs").join("report.pdf");
+        std::fs::create_dir(source.parent().unwrap()).unwrap();
+        std::fs::write(&source, "report").unwrap();
+
+        let mut manifest = QuarantineManifest::default();
+        quarantine_one(&folder, &mut manifest, &source);
+
+        assert!(!source.exists());
+        let item = &manifest.items[0];
+        assert!(item.stored_path.ends_with("report.pdf"));
+        assert!(Path::new(&item.stored_path).starts_with(&item.id));
+        assert!(folder.join(&item.stored_path).exists());
+
+        let ids = vec![item.id.clone()];
+        let result = restore_items(&folder, &mut manifest, &ids, ConflictPolicy::Skip, false);
+        assert_eq!(result.success_count, 1);
+        assert_eq!(std::fs::read_to_string(&source).unwrap(), "report");
+        assert!(manifest.items.is_empty());
+        assert!(!folder.join(&ids[0]).exists());
+    }
+
+    #[test]
+    fn test_restore_with_conflict() {
+        let temp_dir = TempDir::new().unwrap();
+        let folder = temp_dir.path().join("quarantine");
+        let source = temp_dir.path().join("notes.txt");
+        std::fs::write(&source, "old").unwrap();
+
+        let mut manifest = QuarantineManifest::default();
+        quarantine_one(&folder, &mut manifest, &source);
+        std::fs::write(&source, "new").unwrap();
+        let ids = vec![manifest.items[0].id.clone()];
+
+        let skipped = restore_items(&folder, &mut manifest, &ids, ConflictPolicy::Skip, false);
+        assert_eq!(skipped.failed_files[0].reason, FailureReason::FileExists);
+
+        let restored = restore_items(
+            &folder,
+            &mut manifest,
+            &ids,
+            ConflictPolicy::Overwrite,
+            false,
+        );
+        assert_eq!(restored.success_count, 1);
+        assert_eq!(std::fs::read_to_string(&source).unwrap(), "old");
+        // The file that was in the way is now quarantined itself
+        assert_eq!(manifest.items.len(), 1);
+        assert_eq!(
+            std::fs::read_to_string(folder.join(&manifest.items[0].stored_path)).unwrap(),
+            "new"
+        );
+    }
+
+    #[test]
+    fn test_purge_expired() {
+        let temp_dir = TempDir::new().unwrap();
+        let folder = temp_dir.path().join("quarantine");
+        let old_file = temp_dir.path().join("old.txt");
+        let new_file = temp_dir.path().join("new.txt");
+        std::fs::write(&old_file, "old").unwrap();
+        std::fs::write(&new_file, "new").unwrap();
+
+        let mut manifest = QuarantineManifest::default();
+        quarantine_one(&folder, &mut manifest, &old_file);
+        quarantine_one(&folder, &mut manifest, &new_file);
+        manifest.items[0].quarantined_at = (Utc::now() - Duration::days(40)).to_rfc3339();
+        let old_id = manifest.items[0].id.clone();
+
+        let result = purge_expired(&folder, &mut manifest, 30, Utc::now());
+        assert_eq!(result.success_count, 1);
+        assert_eq!(manifest.items.len(), 1);
+        assert!(!folder.join(old_id).exists());
+
+        let kept = purge_expired(&folder, &mut manifest, 0, Utc::now() + Duration::days(1000));
+        assert_eq!(kept.success_count, 0);
+    }
+
+    #[test]
+    fn test_quarantine_dry_run() {
+        let temp_dir = TempDir::new().unwrap();
+        let folder = temp_dir.path().join("quarantine");
+        let source = temp_dir.path().join("keep.txt");
+        std::fs::write(&source, "keep").unwrap();
+
+        let context = OperationContext {
+            dry_run: true,
+            ..OperationContext::default()
+        };
+        let mut manifest = QuarantineManifest::default();
+        let paths = vec![source.to_string_lossy().to_string()];
+        let result = quarantine_paths(&folder, &mut manifest, &paths, &context);
+
+        assert_eq!(result.success_count, 1);
+        assert!(result.dry_run);
+        assert!(source.exists());
+        assert!(manifest.items.is_empty());
+        assert!(!folder.exists());
+    }
+
+    #[test]
+    fn test_manifest_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = QuarantineManifest::path(temp_dir.path());
+        let store = QuarantineManifest::store();
+
+        let mut manifest = store.open(&path).unwrap();
+        manifest.items.push(QuarantinedItem {
+            id: "1-0".to_string(),
+            original_path: "/data/a.txt".to_string(),
+            stored_path: "1-0/data/a.txt".to_string(),
+            quarantined_at: Utc::now().to_rfc3339(),
+            is_directory: false,
+            size: Some(3),
+        });
+        manifest.save().unwrap();
+        drop(manifest);
+
+        let loaded = store.open(&path).unwrap();
+        assert_eq!(loaded.items.len(), 1);
+        assert_eq!(loaded.items[0].original_path, "/data/a.txt");
+    }
+
+    #[test]
+    fn test_quarantine_trash_failures() {
+        let temp_dir = TempDir::new().unwrap();
+        let folder = temp_dir.path().join("quarantine");
+        let unavailable = temp_dir.path().join("on-mount.txt");
+        let denied = temp_dir.path().join("denied.txt");
+        std::fs::write(&unavailable, "data").unwrap();
+        std::fs::write(&denied, "data").unwrap();
+
+        let failed = |path: &Path, reason: FailureReason| FailedEntry {
+            path: path.to_string_lossy().to_string(),
+            reason,
+            error_message: "trash failed".to_string(),
+        };
+        let mut result = finish_result(
+            OperationType::Delete,
+            Instant::now(),
+            vec![
+                failed(&unavailable, FailureReason::Unknown),
+                failed(&denied, FailureReason::PermissionDenied),
+            ],
+            Vec::new(),
+            false,
+        );
+
+        let store = QuarantineManifest::store();
+        quarantine_trash_failures(&store, &folder, 30, &mut result, &OperationContext::new());
+
+        assert_eq!(result.operation, OperationType::Delete);
+        assert_eq!(result.success_count, 1);
+        assert_eq!(result.failed_count, 1);
+        assert_eq!(
+            result.failed_files[0].reason,
+            FailureReason::PermissionDenied
+        );
+        assert_eq!(result.warnings.len(), 1);
+        assert!(!unavailable.exists());
+        assert!(denied.exists());
+
+        let target = result.processed_files[0].target_path.as_deref().unwrap();
+        assert_eq!(std::fs::read_to_string(target).unwrap(), "data");
+        let manifest = store.open(&QuarantineManifest::path(&folder)).unwrap();
+        assert_eq!(manifest.items.len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_relative_original_path() {
+        assert_eq!(
+            relative_original_path(Path::new("/home/user/a.txt")),
+            PathBuf::from("home/user/a.txt")
+        );
+    }
+}
@@ -0,0 +1,675 @@
+//! Batch renaming with name templates
+//!
+//! A template is plain text with tokens in braces:
+//!
+//! * `{name}` - original name without extension
+//! * `{ext}` - original extension without the dot
+//! * `{counter}` or `{counter:3}` - running number, optionally zero-padded
+//! * `{date}` or `{date:%Y%m%d}` - modification date (default format `%Y-%m-%d`)
+//! * `{parent}` - name of the containing folder
+//! * `{1}`, `{2}`, ... - capture groups of the optional `pattern`
+//!
+//! `{{` and `}}` produce literal braces.
+
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::file_ops::{
+    categorize_io_error, unique_path, FailedEntry, FailureReason, FileOperationResult,
+    OperationType, OperationWarning, ProcessedFile,
+};
+use super::journal::record_operation;
+use super::trash_bin::finish_result;
+use crate::models::{decode_path, encode_os_str, encode_path};
+
+/// Date format used by `{date}` without an explicit format
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Options for batch renaming
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameOptions {
+    /// Name template, e.g. `{parent}_{counter:3}.{ext}`
+    pub template: String,
+
+    /// Regular expression matched against the original file name; its capture
+    /// groups are available as `{1}`, `{2}`, ... Files that do not match are
+    /// left unchanged.
+    #[serde(default)]
+    pub pattern: Option<String>,
+
+    /// First value of `{counter}` (default: 1)
+    #[serde(default = "default_counter_start")]
+    pub counter_start: u32,
+}
+
+fn default_counter_start() -> u32 {
+    1
+}
+
+/// Rename files according to a template, all or nothing
+///
+/// Run with `dry_run` first to get the preview: `processed_files` maps every
+/// source to its new path and collisions are listed in `failed_files`. A real
+/// run only starts if the whole batch is free of collisions; if any rename
+/// fails, the renames already done are rolled back. Completed batches are
+/// recorded in the journal as a move and can be undone.
+///
+/// # Arguments
+/// * `paths` - Files or folders to rename, in counter order
+/// * `options` - Name template, optional capture pattern and counter start
+/// * `dry_run` - Only return the preview (default: false)
+///
+/// # Returns
+/// * `Ok(FileOperationResult)` - Rename mapping, collisions and failures
+/// * `Err(String)` - Error message if the template or pattern is invalid
+#[tauri::command]
+pub async fn rename_files(
+    app_handle: AppHandle,
+    paths: Vec<String>,
+    options: RenameOptions,
+    dry_run: Option<bool>,
+) -> Result<FileOperationResult, String> {
+    let mut result = rename_paths(&paths, &options, dry_run.unwrap_or(false))?;
+    if !result.dry_run && result.success_count > 0 {
+        record_operation(&app_handle, &mut result);
+    }
+    Ok(result)
+}
+
+/// Plan and (unless `dry_run`) apply a batch rename
+pub fn rename_paths(
+    paths: &[String],
+    options: &RenameOptions,
+    dry_run: bool,
+) -> Result<FileOperationResult, String> {
+    let start_time = Instant::now();
+
+    let template = parse_template(&options.template)?;
+    let pattern = options
+        .pattern
+        .as_deref()
+        .map(Regex::new)
+        .transpose()
+        .map_err(|e| format!("Invalid pattern: {}", e))?;
+
+    let mut warnings = Vec::new();
+    let (plans, failed_files) = plan_renames(
+        paths,
+        &template,
+        pattern.as_ref(),
+        options.counter_start,
+        &mut warnings,
+    );
+
+    let (processed_files, failed_files) = if dry_run {
+        (
+            plans.iter().map(RenamePlan::to_processed).collect(),
+            failed_files,
+        )
+    } else if !failed_files.is_empty() {
+        let mut failed_files = failed_files;
+        failed_files.extend(plans.iter().map(|plan| {
+            aborted(
+                &plan.source,
+                "Not renamed because other files in the batch collide",
+            )
+        }));
+        (Vec::new(), failed_files)
+    } else {
+        match apply_renames(&plans) {
+            Ok(()) => (
+                plans.iter().map(RenamePlan::to_processed).collect(),
+                Vec::new(),
+            ),
+            Err(failed_files) => (Vec::new(), failed_files),
+        }
+    };
+
+    let mut result = finish_result(
+        OperationType::Move,
+        start_time,
+        failed_files,
+        processed_files,
+        dry_run,
+    );
+    result.warnings = warnings;
+    Ok(result)
+}
+
+/// A single planned rename
+struct RenamePlan {
+    source: PathBuf,
+    target: PathBuf,
+}
+
+impl RenamePlan {
+    fn to_processed(&self) -> ProcessedFile {
+        ProcessedFile {
+            source_path: encode_path(&self.source),
+            target_path: Some(encode_path(&self.target)),
+        }
+    }
+}
+
+/// Part of a parsed name template
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Literal(String),
+    Name,
+    Extension,
+    Counter(usize),
+    Date(String),
+    Parent,
+    Capture(usize),
+}
+
+/// Parse a template into segments, rejecting unknown tokens
+fn parse_template(template: &str) -> Result<Vec<Segment>, String> {
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '{' => {
+                let mut token = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => token.push(c),
+                        None => return Err(format!("Unclosed token in template: {{{}", token)),
+                    }
+                }
+                if !literal.is_empty() {
+                    segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                }
+                segments.push(parse_token(&token)?);
+            }
+            '}' => {
+                return Err("Unmatched '}' in template (use '}}' for a literal brace)".to_string())
+            }
+            _ => literal.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        segments.push(Segment::Literal(literal));
+    }
+
+    if segments.is_empty() {
+        return Err("Template is empty".to_string());
+    }
+    Ok(segments)
+}
+
+fn parse_token(token: &str) -> Result<Segment, String> {
+    let (name, argument) = match token.split_once(':') {
+        Some((name, argument)) => (name, Some(argument)),
+        None => (token, None),
+    };
+
+    match (name, argument) {
+        ("name", None) => Ok(Segment::Name),
+        ("ext", None) => Ok(Segment::Extension),
+        ("parent", None) => Ok(Segment::Parent),
+        ("counter", None) => Ok(Segment::Counter(0)),
+        ("counter", Some(width)) => width
+            .parse()
+            .map(Segment::Counter)
+            .map_err(|_| format!("Invalid counter width: {}", width)),
+        ("date", format) => {
+            let format = format.unwrap_or(DEFAULT_DATE_FORMAT);
+            if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+                return Err(format!("Invalid date format: {}", format));
+            }
+            Ok(Segment::Date(format.to_string()))
+        }
+        (index, None) if !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()) => index
+            .parse()
+            .map(Segment::Capture)
+            .map_err(|_| format!("Invalid capture group: {}", index)),
+        _ => Err(format!("Unknown template token: {{{}}}", token)),
+    }
+}
+
+/// Work out the new path of every source and detect collisions
+fn plan_renames(
+    paths: &[String],
+    template: &[Segment],
+    pattern: Option<&Regex>,
+    counter_start: u32,
+    warnings: &mut Vec<OperationWarning>,
+) -> (Vec<RenamePlan>, Vec<FailedEntry>) {
+    let mut plans = Vec::new();
+    let mut failed_files = Vec::new();
+    let mut counter = counter_start;
+
+    for path_str in paths {
+        let source = decode_path(path_str);
+        let failed = |reason: FailureReason, error_message: String| FailedEntry {
+            path: path_str.clone(),
+            reason,
+            error_message,
+        };
+
+        let Ok(metadata) = std::fs::symlink_metadata(&source) else {
+            failed_files.push(failed(
+                FailureReason::PathNotFound,
+                format!("File not found: {}", path_str),
+            ));
+            continue;
+        };
+        let file_name = source
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let captures = match pattern {
+            Some(pattern) => match pattern.captures(&file_name) {
+                Some(captures) => captures
+                    .iter()
+                    .map(|group| group.map_or(String::new(), |group| group.as_str().to_string()))
+                    .collect(),
+                None => {
+                    warnings.push(OperationWarning {
+                        path: path_str.clone(),
+                        message: "Name does not match the pattern, left unchanged".to_string(),
+                    });
+                    continue;
+                }
+            },
+            None => Vec::new(),
+        };
+
+        let modified = metadata.modified().ok().map(DateTime::<Local>::from);
+        let new_name = render(template, &source, counter, modified, &captures);
+        counter += 1;
+
+        let text = new_name.to_string_lossy();
+        if text.is_empty() || text.contains(['/', '\\']) || text == ".." {
+            failed_files.push(failed(
+                FailureReason::InvalidName,
+                format!("Invalid file name: {:?}", new_name),
+            ));
+            continue;
+        }
+
+        let target = source.with_file_name(&new_name);
+        if target != source {
+            plans.push(RenamePlan { source, target });
+        }
+    }
+
+    failed_files.extend(find_collisions(&mut plans));
+    (plans, failed_files)
+}
+
+/// Remove plans whose target is taken and return them as failures
+///
+/// A target is taken if another plan has the same target, or if it exists and
+/// is neither one of the renamed sources nor the source itself (a case-only
+/// rename on a case-insensitive filesystem).
+fn find_collisions(plans: &mut Vec<RenamePlan>) -> Vec<FailedEntry> {
+    let sources: HashSet<PathBuf> = plans.iter().map(|plan| plan.source.clone()).collect();
+    let mut target_counts: HashMap<PathBuf, usize> = HashMap::new();
+    for plan in plans.iter() {
+        *target_counts.entry(plan.target.clone()).or_default() += 1;
+    }
+
+    let mut failed_files = Vec::new();
+    plans.retain(|plan| {
+        let message = if target_counts[&plan.target] > 1 {
+            format!(
+                "Name collides with another renamed file: {}",
+                plan.target.display()
+            )
+        } else if plan.target.symlink_metadata().is_ok()
+            && !sources.contains(&plan.target)
+            && !same_file(&plan.source, &plan.target)
+        {
+            format!("File already exists: {}", plan.target.display())
+        } else {
+            return true;
+        };
+
+        failed_files.push(FailedEntry {
+            path: encode_path(&plan.source),
+            reason: FailureReason::FileExists,
+            error_message: message,
+        });
+        false
+    });
+
+    failed_files
+}
+
+/// Rename every source to its target, rolling back on the first failure
+///
+/// Sources are first moved to temporary names so that chains and swaps
+/// (`a -> b`, `b -> a`) work. Targets are checked again right before each
+/// rename, so a file created there after the collision check is never replaced.
+fn apply_renames(plans: &[RenamePlan]) -> Result<(), Vec<FailedEntry>> {
+    let mut parked: Vec<(&RenamePlan, PathBuf)> = Vec::new();
+    for (index, plan) in plans.iter().enumerate() {
+        let mut temp_name = OsString::from(".");
+        temp_name.push(plan.source.file_name().unwrap_or_default());
+        temp_name.push(format!(".renaming-{}", index));
+        let temp = unique_path(&plan.source.with_file_name(temp_name));
+
+        if let Err(e) = std::fs::rename(&plan.source, &temp) {
+            for (done, temp) in parked.iter().rev() {
+                let _ = std::fs::rename(temp, &done.source);
+            }
+            return Err(rollback_failures(plans, plan, &e));
+        }
+        parked.push((plan, temp));
+    }
+
+    for (index, (plan, temp)) in parked.iter().enumerate() {
+        let renamed = if plan.target.symlink_metadata().is_ok() {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("File already exists: {}", plan.target.display()),
+            ))
+        } else {
+            std::fs::rename(temp, &plan.target)
+        };
+        if let Err(e) = renamed {
+            for (done, temp) in parked[..index].iter().rev() {
+                let _ = std::fs::rename(&done.target, temp);
+            }
+            for (done, temp) in parked.iter().rev() {
+                let _ = std::fs::rename(temp, &done.source);
+            }
+            return Err(rollback_failures(plans, plan, &e));
+        }
+    }
+
+    Ok(())
+}
+
+/// Report the failed rename and mark every other one as rolled back
+fn rollback_failures(
+    plans: &[RenamePlan],
+    failed_plan: &RenamePlan,
+    error: &std::io::Error,
+) -> Vec<FailedEntry> {
+    let (reason, message) = categorize_io_error(error);
+    plans
+        .iter()
+        .map(|plan| {
+            if std::ptr::eq(plan, failed_plan) {
+                FailedEntry {
+                    path: encode_path(&plan.source),
+                    reason: reason.clone(),
+                    error_message: message.clone(),
+                }
+            } else {
+                aborted(
+                    &plan.source,
+                    &format!(
+                        "Rolled back because renaming {} failed",
+                        failed_plan.source.display()
+                    ),
+                )
+            }
+        })
+        .collect()
+}
+
+fn aborted(path: &Path, message: &str) -> FailedEntry {
+    FailedEntry {
+        path: encode_path(path),
+        reason: FailureReason::Aborted,
+        error_message: message.to_string(),
+    }
+}
+
+/// Render the template for one file
+///
+/// Name parts taken from the source path are kept as they are, so names that
+/// are not valid Unicode survive the rename.
+fn render(
+    template: &[Segment],
+    source: &Path,
+    counter: u32,
+    modified: Option<DateTime<Local>>,
+    captures: &[String],
+) -> OsString {
+    let is_dir = source.is_dir();
+    let stem = if is_dir {
+        source.file_name()
+    } else {
+        source.file_stem()
+    };
+    let extension = if is_dir { None } else { source.extension() };
+
+    let mut name = OsString::new();
+    for segment in template {
+        match segment {
+            Segment::Literal(text) => name.push(text),
+            Segment::Name => name.push(stem.unwrap_or_default()),
+            Segment::Extension => name.push(extension.unwrap_or_default()),
+            Segment::Counter(width) => {
+                name.push(format!("{:0width$}", counter, width = width));
+            }
+            Segment::Date(format) => {
+                if let Some(modified) = modified {
+                    name.push(modified.format(format).to_string());
+                }
+            }
+            Segment::Parent => name.push(
+                source
+                    .parent()
+                    .and_then(Path::file_name)
+                    .unwrap_or_default(),
+            ),
+            Segment::Capture(index) => {
+                name.push(captures.get(*index).map_or("", String::as_str));
+            }
+        }
+    }
+
+    // `{name}.{ext}` on a file without extension would end in a dot
+    trim_trailing_dots(name)
+}
+
+/// Remove trailing dots from a name, which ends in valid text if it ends in a dot
+fn trim_trailing_dots(name: OsString) -> OsString {
+    match name.into_string() {
+        Ok(text) => OsString::from(text.trim_end_matches('.')),
+        Err(name) => {
+            let mut encoded = encode_os_str(&name);
+            while encoded.ends_with('.') {
+                encoded.pop();
+            }
+            decode_path(&encoded).into_os_string()
+        }
+    }
+}
+
+/// Whether two paths refer to the same file
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn options(template: &str, pattern: Option<&str>) -> RenameOptions {
+        RenameOptions {
+            template: template.to_string(),
+            pattern: pattern.map(str::to_string),
+            counter_start: 1,
+        }
+    }
+
+    fn create_files(dir: &Path, names: &[&str]) -> Vec<String> {
+        names
+            .iter()
+            .map(|name| {
+                let path = dir.join(name);
+                std::fs::write(&path, name).unwrap();
+                path.to_string_lossy().to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_template() {
+        let segments = parse_template("{name}_{counter:3}.{ext}").unwrap();
+        assert_eq!(segments[0], Segment::Name);
+        assert_eq!(segments[2], Segment::Counter(3));
+        assert_eq!(segments[4], Segment::Extension);
+
+        assert!(parse_template("{unknown}").is_err());
+        assert!(parse_template("{name").is_err());
+        assert!(parse_template("{counter:x}").is_err());
+        assert_eq!(
+            parse_template("{{literal}}").unwrap(),
+            vec![Segment::Literal("{literal}".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_rename_with_template() {
+        let temp_dir = TempDir::new().unwrap();
+        let paths = create_files(temp_dir.path(), &["IMG_0001.JPG", "IMG_0002.JPG"]);
+
+        let result = rename_paths(
+            &paths,
+            &options("holiday_{counter:3}_{1}.{ext}", Some(r"IMG_(\d+)")),
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(result.success_count, 2);
+        assert!(temp_dir.path().join("holiday_001_0001.JPG").exists());
+        assert!(temp_dir.path().join("holiday_002_0002.JPG").exists());
+        assert!(!temp_dir.path().join("IMG_0001.JPG").exists());
+    }
+
+    #[test]
+    fn test_preview_does_not_rename() {
+        let temp_dir = TempDir::new().unwrap();
+        let paths = create_files(temp_dir.path(), &["a.txt"]);
+
+        let result = rename_paths(&paths, &options("{parent}-{name}.{ext}", None), true).unwrap();
+
+        assert!(result.dry_run);
+        let parent = temp_dir.path().file_name().unwrap().to_string_lossy();
+        let expected = temp_dir.path().join(format!("{}-a.txt", parent));
+        assert_eq!(
+            result.processed_files[0].target_path.as_deref(),
+            Some(expected.to_string_lossy().as_ref())
+        );
+        assert!(Path::new(&paths[0]).exists());
+    }
+
+    #[test]
+    fn test_collisions_abort_batch() {
+        let temp_dir = TempDir::new().unwrap();
+        let paths = create_files(temp_dir.path(), &["a.txt", "b.txt", "c.txt", "c.md"]);
+
+        // a and b both become "same.txt"
+        let duplicate = rename_paths(&paths[..2], &options("same.{ext}", None), false).unwrap();
+        assert_eq!(duplicate.success_count, 0);
+        assert_eq!(duplicate.failed_files[0].reason, FailureReason::FileExists);
+
+        // c would overwrite an existing file, which also stops a from being renamed
+        let batch = vec![paths[0].clone(), paths[2].clone()];
+        let existing = rename_paths(&batch, &options("{name}.md", None), false).unwrap();
+        assert_eq!(existing.success_count, 0);
+        assert_eq!(existing.failed_files.len(), 2);
+        assert!(existing
+            .failed_files
+            .iter()
+            .any(|failed| failed.reason == FailureReason::Aborted));
+        assert!(Path::new(&paths[0]).exists());
+        assert!(Path::new(&paths[2]).exists());
+    }
+
+    #[test]
+    fn test_swap_names() {
+        let temp_dir = TempDir::new().unwrap();
+        let paths = create_files(temp_dir.path(), &["1.txt", "2.txt"]);
+
+        let result = rename_paths(
+            &[paths[1].clone(), paths[0].clone()],
+            &options("{counter}.{ext}", None),
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(result.success_count, 2);
+        assert_eq!(
+            std::fs::read_to_string(temp_dir.path().join("1.txt")).unwrap(),
+            "2.txt"
+        );
+        assert_eq!(
+            std::fs::read_to_string(temp_dir.path().join("2.txt")).unwrap(),
+            "1.txt"
+        );
+    }
+
+    #[test]
+    fn test_rollback_on_failure() {
+        let temp_dir = TempDir::new().unwrap();
+        let paths = create_files(temp_dir.path(), &["a.txt", "b.txt"]);
+        let plans = vec![
+            RenamePlan {
+                source: PathBuf::from(&paths[0]),
+                target: temp_dir.path().join("renamed.txt"),
+            },
+            RenamePlan {
+                source: PathBuf::from(&paths[1]),
+                target: temp_dir.path().join("missing").join("b.txt"),
+            },
+        ];
+
+        let failed = apply_renames(&plans).unwrap_err();
+
+        assert_eq!(failed.len(), 2);
+        assert_eq!(failed[0].reason, FailureReason::Aborted);
+        assert!(Path::new(&paths[0]).exists());
+        assert!(Path::new(&paths[1]).exists());
+        assert!(!temp_dir.path().join("renamed.txt").exists());
+    }
+
+    #[test]
+    fn test_rename_keeps_file_created_at_target() {
+        let temp_dir = TempDir::new().unwrap();
+        let paths = create_files(temp_dir.path(), &["a.txt", "late.txt"]);
+        let plans = vec![RenamePlan {
+            source: PathBuf::from(&paths[0]),
+            target: PathBuf::from(&paths[1]),
+        }];
+
+        let failed = apply_renames(&plans).unwrap_err();
+
+        assert_eq!(failed[0].reason, FailureReason::FileExists);
+        assert_eq!(std::fs::read_to_string(&paths[0]).unwrap(), "a.txt");
+        assert_eq!(std::fs::read_to_string(&paths[1]).unwrap(), "late.txt");
+    }
+}
@@ -1,8 +1,9 @@
 //! Scan command handler
 
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, State};
 
+use super::analysis::ScanCache;
 use crate::models::{ScanProgress, ScanResult};
 use crate::scanner::{scan_directory_with_progress, ScanOptions};
 
@@ -13,8 +14,26 @@ use crate::scanner::{scan_directory_with_progress, ScanOptions};
 ///
 /// Progress events are emitted through the `scan_progress` event channel
 /// at regular intervals (at least 10 times per second) during scanning.
+///
+/// With `expand_archives`, the members of ZIP and tar archives are listed as
+/// virtual entries below the archive (e.g. `backup.zip!/docs/a.pdf`).
+///
+/// `size_buckets` sets the lower bounds (in bytes) of the file-size
+/// distribution in the stats; power-of-two buckets are used by default.
+///
+/// With `include_ownership`, entries carry their owner, group and permission
+/// bits (Unix only) and the stats add up usage per owner.
+///
+/// The result is kept as the last scan for `treemap_layout` and `size_hierarchy`.
 #[tauri::command]
-pub async fn scan_folder(app_handle: AppHandle, path: String) -> Result<ScanResult, String> {
+pub async fn scan_folder(
+    app_handle: AppHandle,
+    scans: State<'_, ScanCache>,
+    path: String,
+    expand_archives: Option<bool>,
+    size_buckets: Option<Vec<u64>>,
+    include_ownership: Option<bool>,
+) -> Result<ScanResult, String> {
     // Validate path
     if path.is_empty() {
         return Err("Path cannot be empty".to_string());
@@ -35,7 +54,12 @@ pub async fn scan_folder(app_handle: AppHandle, path: String) -> Result<ScanResu
     };
 
     // Perform the scan with progress updates
-    let options = ScanOptions::new();
+    let mut options = ScanOptions::new()
+        .with_expand_archives(expand_archives.unwrap_or(false))
+        .with_ownership(include_ownership.unwrap_or(false));
+    if let Some(bounds) = size_buckets {
+        options = options.with_size_buckets(bounds);
+    }
     let result = scan_directory_with_progress(&path, &options, Some(Box::new(progress_callback)))?;
 
     // Emit completion progress
@@ -45,6 +69,7 @@ pub async fn scan_folder(app_handle: AppHandle, path: String) -> Result<ScanResu
             .with_progress(100.0),
     );
 
+    scans.store(result.clone());
     Ok(result)
 }
 
@@ -0,0 +1,623 @@
+//! Permanent deletion with overwriting of file contents
+//!
+//! Overwriting only reaches the original data blocks on filesystems that write
+//! in place. On SSDs (wear levelling) and copy-on-write or journaling
+//! filesystems, old copies of the data may survive; full-disk encryption is the
+//! reliable option there.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs::{File, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+use walkdir::WalkDir;
+
+use super::file_ops::{
+    categorize_io_error, check_delete_plan, operation_context, FailedEntry, FailureReason,
+    FileOperationResult, OperationContext, OperationType, ProcessedFile, ProgressTracker,
+};
+use super::journal::record_operation;
+use super::operation_registry::OperationRegistry;
+use super::preflight::is_writable;
+use super::trash_bin::finish_result;
+use crate::models::{decode_path, encode_path};
+
+/// Buffer size used when overwriting file contents
+const OVERWRITE_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Upper limit for overwrite passes (the historical Gutmann method uses 35)
+const MAX_PASSES: u32 = 35;
+
+/// Time a confirmation token stays valid after `prepare_secure_delete`
+const CONFIRMATION_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Options for secure deletion
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SecureDeleteOptions {
+    /// Number of overwrite passes per file (1 to 35)
+    pub passes: u32,
+
+    /// Remove folders once all files inside them have been deleted
+    pub remove_empty_dirs: bool,
+}
+
+impl Default for SecureDeleteOptions {
+    fn default() -> Self {
+        Self {
+            passes: 3,
+            remove_empty_dirs: false,
+        }
+    }
+}
+
+/// Planned secure deletion, returned by `prepare_secure_delete`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecureDeletePlan {
+    /// Token that must be passed to `secure_delete_files` to confirm the deletion
+    pub confirmation_token: String,
+
+    /// Dry-run result listing every file and folder that would be deleted
+    pub result: FileOperationResult,
+}
+
+/// A planned deletion waiting for confirmation
+#[derive(Debug)]
+struct PendingConfirmation {
+    paths: Vec<String>,
+    options: SecureDeleteOptions,
+    expires_at: Instant,
+}
+
+/// Confirmation tokens issued by `prepare_secure_delete`
+///
+/// Registered as Tauri managed state. Tokens are random, expire after
+/// `CONFIRMATION_TTL` and can be used only once.
+#[derive(Debug, Default)]
+pub struct SecureDeleteConfirmations {
+    pending: Mutex<HashMap<String, PendingConfirmation>>,
+}
+
+impl SecureDeleteConfirmations {
+    /// Create an empty set of confirmations
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Issue a token confirming the deletion of exactly these paths with these options
+    pub fn issue(&self, paths: &[String], options: &SecureDeleteOptions) -> Result<String, String> {
+        self.issue_at(paths, options, Instant::now())
+    }
+
+    /// Use up a token, which must be known, unexpired and issued for these paths and options
+    pub fn consume(
+        &self,
+        token: &str,
+        paths: &[String],
+        options: &SecureDeleteOptions,
+    ) -> Result<(), String> {
+        self.consume_at(token, paths, options, Instant::now())
+    }
+
+    fn issue_at(
+        &self,
+        paths: &[String],
+        options: &SecureDeleteOptions,
+        now: Instant,
+    ) -> Result<String, String> {
+        let mut nonce = [0u8; 16];
+        getrandom::fill(&mut nonce)
+            .map_err(|e| format!("Failed to generate confirmation token: {}", e))?;
+        let token = nonce.iter().fold(String::new(), |mut token, byte| {
+            let _ = write!(token, "{:02x}", byte);
+            token
+        });
+
+        let mut pending = self
+            .pending
+            .lock()
+            .expect("secure delete confirmations lock poisoned");
+        pending.retain(|_, confirmation| confirmation.expires_at > now);
+        pending.insert(
+            token.clone(),
+            PendingConfirmation {
+                paths: paths.to_vec(),
+                options: options.clone(),
+                expires_at: now + CONFIRMATION_TTL,
+            },
+        );
+        Ok(token)
+    }
+
+    fn consume_at(
+        &self,
+        token: &str,
+        paths: &[String],
+        options: &SecureDeleteOptions,
+        now: Instant,
+    ) -> Result<(), String> {
+        // Any attempt uses the token up, so it cannot be retried with other paths
+        let confirmation = self
+            .pending
+            .lock()
+            .expect("secure delete confirmations lock poisoned")
+            .remove(token)
+            .ok_or_else(|| {
+                "Unknown or already used confirmation token; call prepare_secure_delete first"
+                    .to_string()
+            })?;
+
+        if confirmation.expires_at <= now {
+            return Err(
+                "Confirmation token has expired; call prepare_secure_delete again".to_string(),
+            );
+        }
+        if confirmation.paths != paths || confirmation.options != *options {
+            return Err(
+                "Confirmation token does not match these paths and options; call prepare_secure_delete again"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Plan a secure deletion and return the token needed to confirm it
+///
+/// The token is random, tied to the exact paths and options, valid for five
+/// minutes and accepted only once; any change requires a new plan.
+///
+/// # Arguments
+/// * `paths` - Files or folders to delete permanently
+/// * `options` - Overwrite passes and folder handling (default: 3 passes, keep folders)
+#[tauri::command]
+pub async fn prepare_secure_delete(
+    confirmations: State<'_, SecureDeleteConfirmations>,
+    paths: Vec<String>,
+    options: Option<SecureDeleteOptions>,
+) -> Result<SecureDeletePlan, String> {
+    let options = options.unwrap_or_default();
+    validate_options(&options)?;
+
+    let context = OperationContext {
+        dry_run: true,
+        ..OperationContext::default()
+    };
+    let result = secure_delete_paths(&paths, &options, &context);
+    Ok(SecureDeletePlan {
+        confirmation_token: confirmations.issue(&paths, &options)?,
+        result,
+    })
+}
+
+/// Permanently delete files by overwriting their contents before unlinking them
+///
+/// Unlike `delete_files`, nothing is moved to the trash and the operation cannot
+/// be undone. The call is rejected unless `confirmation_token` is an unexpired,
+/// unused token returned by `prepare_secure_delete` for the same paths and options.
+///
+/// Folders are processed recursively; symbolic links are removed without
+/// touching their targets. Cancellation takes effect between files, so a file
+/// is never left half overwritten.
+///
+/// # Arguments
+/// * `paths` - Files or folders to delete permanently
+/// * `options` - Overwrite passes and folder handling (default: 3 passes, keep folders)
+/// * `confirmation_token` - Token from `prepare_secure_delete`
+/// * `operation_id` - Optional ID used for progress events and cancellation
+///
+/// # Returns
+/// * `Ok(FileOperationResult)` - Per-file result of the deletion
+/// * `Err(String)` - Error message if the options are invalid or not confirmed
+#[tauri::command]
+pub async fn secure_delete_files(
+    app_handle: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    confirmations: State<'_, SecureDeleteConfirmations>,
+    paths: Vec<String>,
+    options: Option<SecureDeleteOptions>,
+    confirmation_token: String,
+    operation_id: Option<String>,
+) -> Result<FileOperationResult, String> {
+    let options = options.unwrap_or_default();
+    validate_options(&options)?;
+    confirmations.consume(&confirmation_token, &paths, &options)?;
+
+    let context = operation_context(&app_handle, &registry, operation_id, false);
+    let mut result = secure_delete_paths(&paths, &options, &context);
+
+    if let Some(ref id) = context.operation_id {
+        registry.unregister(id, &context.cancel_token);
+    }
+
+    record_operation(&app_handle, &mut result);
+    Ok(result)
+}
+
+/// Overwrite and delete files and folders, reporting every file separately
+///
+/// In a dry run only permissions are checked and the affected entries listed.
+pub fn secure_delete_paths(
+    paths: &[String],
+    options: &SecureDeleteOptions,
+    context: &OperationContext,
+) -> FileOperationResult {
+    let start_time = Instant::now();
+
+    let mut failed_files: Vec<FailedEntry> = Vec::new();
+    let mut processed_files: Vec<ProcessedFile> = Vec::new();
+    let entries = collect_entries(paths, &mut failed_files);
+
+    let file_count = entries.iter().filter(|entry| !entry.is_dir).count();
+    let total_bytes: u64 = entries.iter().map(|entry| entry.size).sum();
+    let mut tracker = ProgressTracker::new(
+        context,
+        OperationType::Shred,
+        file_count as u32,
+        total_bytes * u64::from(options.passes),
+    );
+
+    let mut cancelled = false;
+    for entry in entries {
+        if context.cancel_token.is_cancelled() {
+            cancelled = true;
+            break;
+        }
+
+        let path_str = encode_path(&entry.path);
+        let deleted = if entry.is_dir {
+            if !options.remove_empty_dirs {
+                continue;
+            }
+            remove_dir(&entry.path, context.dry_run)
+        } else {
+            tracker.start_file(&path_str);
+            let deleted = if context.dry_run {
+                check_shred_plan(&entry.path)
+            } else {
+                shred_file(&entry.path, options.passes, &mut tracker)
+                    .map_err(|e| categorize_io_error(&e))
+            };
+            tracker.finish_file();
+            deleted
+        };
+
+        match deleted {
+            Ok(true) => processed_files.push(ProcessedFile {
+                source_path: path_str,
+                target_path: None,
+            }),
+            Ok(false) => {}
+            Err((reason, message)) => failed_files.push(FailedEntry {
+                path: path_str,
+                reason,
+                error_message: message,
+            }),
+        }
+    }
+    tracker.report(true);
+
+    let mut result = finish_result(
+        OperationType::Shred,
+        start_time,
+        failed_files,
+        processed_files,
+        context.dry_run,
+    );
+    result.cancelled = cancelled;
+    result
+}
+
+/// A file, link or folder to be deleted
+struct ShredEntry {
+    path: PathBuf,
+    is_dir: bool,
+    size: u64,
+}
+
+/// Expand the given paths into files and folders, children before their parents
+fn collect_entries(paths: &[String], failed_files: &mut Vec<FailedEntry>) -> Vec<ShredEntry> {
+    let mut entries = Vec::new();
+
+    for path_str in paths {
+        let path = decode_path(path_str);
+        if std::fs::symlink_metadata(&path).is_err() {
+            failed_files.push(FailedEntry {
+                path: path_str.clone(),
+                reason: FailureReason::PathNotFound,
+                error_message: format!("File not found: {}", path_str),
+            });
+            continue;
+        }
+
+        for dir_entry in WalkDir::new(&path)
+            .follow_links(false)
+            .follow_root_links(false)
+            .contents_first(true)
+        {
+            match dir_entry
+                .and_then(|dir_entry| dir_entry.metadata().map(|metadata| (dir_entry, metadata)))
+            {
+                Ok((dir_entry, metadata)) => entries.push(ShredEntry {
+                    path: dir_entry.into_path(),
+                    is_dir: metadata.is_dir(),
+                    size: if metadata.is_file() {
+                        metadata.len()
+                    } else {
+                        0
+                    },
+                }),
+                Err(e) => {
+                    let path = e.path().map_or_else(|| path_str.clone(), encode_path);
+                    let (reason, message) = e
+                        .io_error()
+                        .map_or((FailureReason::Unknown, e.to_string()), categorize_io_error);
+                    failed_files.push(FailedEntry {
+                        path,
+                        reason,
+                        error_message: message,
+                    });
+                }
+            }
+        }
+    }
+
+    entries
+}
+
+/// Check that a file could be overwritten and removed, without touching it
+fn check_shred_plan(path: &Path) -> Result<bool, (FailureReason, String)> {
+    let is_symlink = std::fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink());
+    if !is_symlink && !is_writable(path) {
+        return Err((
+            FailureReason::PermissionDenied,
+            format!("No write permission on file: {}", path.display()),
+        ));
+    }
+    check_delete_plan(path).map(|()| true)
+}
+
+/// Remove a folder if it is empty, returning whether it was (or would be) removed
+///
+/// Folders that still contain entries, e.g. because a file failed, are kept.
+fn remove_dir(path: &Path, dry_run: bool) -> Result<bool, (FailureReason, String)> {
+    if dry_run {
+        return check_delete_plan(path).map(|()| true);
+    }
+
+    match std::fs::remove_dir(path) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::DirectoryNotEmpty => Ok(false),
+        Err(e) => Err(categorize_io_error(&e)),
+    }
+}
+
+/// Overwrite a file's contents `passes` times, truncate it and unlink it
+///
+/// Passes alternate between zeros and ones; the final pass writes random data.
+/// Symbolic links are removed without touching their targets.
+fn shred_file(
+    path: &Path,
+    passes: u32,
+    tracker: &mut ProgressTracker<'_>,
+) -> std::io::Result<bool> {
+    if std::fs::symlink_metadata(path)?.file_type().is_symlink() {
+        std::fs::remove_file(path)?;
+        return Ok(true);
+    }
+
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    let len = file.metadata()?.len();
+    let mut random = XorShift::seeded();
+
+    for pass in 0..passes {
+        let fill = if pass + 1 == passes {
+            None
+        } else if pass % 2 == 0 {
+            Some(0x00)
+        } else {
+            Some(0xFF)
+        };
+        overwrite_pass(&mut file, len, fill, &mut random, tracker)?;
+    }
+
+    file.set_len(0)?;
+    file.sync_all()?;
+    drop(file);
+    std::fs::remove_file(path)?;
+    Ok(true)
+}
+
+/// Write one pass over the whole file, with a fixed byte or random data
+fn overwrite_pass(
+    file: &mut File,
+    len: u64,
+    fill: Option<u8>,
+    random: &mut XorShift,
+    tracker: &mut ProgressTracker<'_>,
+) -> std::io::Result<()> {
+    let mut buffer = vec![fill.unwrap_or(0); OVERWRITE_BUFFER_SIZE];
+    file.seek(SeekFrom::Start(0))?;
+
+    let mut remaining = len;
+    while remaining > 0 {
+        let chunk = remaining.min(OVERWRITE_BUFFER_SIZE as u64) as usize;
+        if fill.is_none() {
+            random.fill(&mut buffer[..chunk]);
+        }
+        file.write_all(&buffer[..chunk])?;
+        tracker.add_bytes(chunk as u64);
+        remaining -= chunk as u64;
+    }
+
+    file.sync_all()
+}
+
+/// Small xorshift generator for overwrite data (not for cryptographic use)
+struct XorShift(u64);
+
+impl XorShift {
+    fn seeded() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_nanos() as u64);
+        Self(nanos | 1)
+    }
+
+    fn fill(&mut self, buffer: &mut [u8]) {
+        for chunk in buffer.chunks_mut(8) {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            chunk.copy_from_slice(&self.0.to_le_bytes()[..chunk.len()]);
+        }
+    }
+}
+
+fn validate_options(options: &SecureDeleteOptions) -> Result<(), String> {
+    if options.passes == 0 || options.passes > MAX_PASSES {
+        return Err(format!(
+            "Number of passes must be between 1 and {}, got {}",
+            MAX_PASSES, options.passes
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_secure_delete_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("secret.txt");
+        std::fs::write(&file, "secret data").unwrap();
+
+        let paths = vec![file.to_string_lossy().to_string()];
+        let result = secure_delete_paths(
+            &paths,
+            &SecureDeleteOptions::default(),
+            &OperationContext::new(),
+        );
+
+        assert_eq!(result.operation, OperationType::Shred);
+        assert_eq!(result.success_count, 1);
+        assert!(!file.exists());
+    }
+
+    #[test]
+    fn test_secure_delete_folder() {
+        let temp_dir = TempDir::new().unwrap();
+        let folder = temp_dir.path().join("laptop");
+        std::fs::create_dir_all(folder.join("docs")).unwrap();
+        std::fs::write(folder.join("a.txt"), "a").unwrap();
+        std::fs::write(folder.join("docs").join("b.txt"), "b").unwrap();
+        let paths = vec![folder.to_string_lossy().to_string()];
+
+        // Without folder removal only the files go
+        let options = SecureDeleteOptions {
+            passes: 1,
+            remove_empty_dirs: false,
+        };
+        let result = secure_delete_paths(&paths, &options, &OperationContext::new());
+        assert_eq!(result.success_count, 2);
+        assert!(folder.join("docs").exists());
+
+        let options = SecureDeleteOptions {
+            passes: 1,
+            remove_empty_dirs: true,
+        };
+        let result = secure_delete_paths(&paths, &options, &OperationContext::new());
+        assert_eq!(result.success_count, 2);
+        assert!(!folder.exists());
+    }
+
+    #[test]
+    fn test_secure_delete_dry_run() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("keep.txt");
+        std::fs::write(&file, "keep").unwrap();
+
+        let context = OperationContext {
+            dry_run: true,
+            ..OperationContext::default()
+        };
+        let paths = vec![
+            file.to_string_lossy().to_string(),
+            temp_dir
+                .path()
+                .join("missing")
+                .to_string_lossy()
+                .to_string(),
+        ];
+        let result = secure_delete_paths(&paths, &SecureDeleteOptions::default(), &context);
+
+        assert!(result.dry_run);
+        assert_eq!(result.success_count, 1);
+        assert_eq!(result.failed_files[0].reason, FailureReason::PathNotFound);
+        assert!(file.exists());
+    }
+
+    #[test]
+    fn test_overwrite_pass() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("data.bin");
+        std::fs::write(&path, "abcdef").unwrap();
+
+        let context = OperationContext::new();
+        let mut tracker = ProgressTracker::new(&context, OperationType::Shred, 1, 6);
+        let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+        overwrite_pass(
+            &mut file,
+            6,
+            Some(0xFF),
+            &mut XorShift::seeded(),
+            &mut tracker,
+        )
+        .unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), vec![0xFF; 6]);
+    }
+
+    #[test]
+    fn test_confirmation_token() {
+        let confirmations = SecureDeleteConfirmations::new();
+        let paths = vec!["/data/a.txt".to_string()];
+        let options = SecureDeleteOptions::default();
+        let token = confirmations.issue(&paths, &options).unwrap();
+        assert_eq!(token.len(), 32);
+        assert_ne!(token, confirmations.issue(&paths, &options).unwrap());
+
+        assert!(confirmations.consume("wrong", &paths, &options).is_err());
+        assert!(confirmations.consume(&token, &paths, &options).is_ok());
+        // Tokens are single use
+        assert!(confirmations.consume(&token, &paths, &options).is_err());
+
+        let more_passes = SecureDeleteOptions {
+            passes: 7,
+            ..SecureDeleteOptions::default()
+        };
+        let token = confirmations.issue(&paths, &options).unwrap();
+        assert!(confirmations.consume(&token, &paths, &more_passes).is_err());
+        assert!(confirmations.consume(&token, &paths, &options).is_err());
+
+        let now = Instant::now();
+        let token = confirmations.issue_at(&paths, &options, now).unwrap();
+        assert!(confirmations
+            .consume_at(&token, &paths, &options, now + CONFIRMATION_TTL)
+            .is_err());
+        assert!(validate_options(&SecureDeleteOptions {
+            passes: 0,
+            ..SecureDeleteOptions::default()
+        })
+        .is_err());
+    }
+}
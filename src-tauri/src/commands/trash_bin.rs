@@ -0,0 +1,688 @@
+//! Trash browser command handlers (list, restore, purge)
+//!
+//! Listing and restoring trash items is only supported by the `trash` crate on
+//! Windows and freedesktop (Linux/BSD) systems; other platforms get an error.
+
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use trash::{TrashItem, TrashItemSize};
+
+use super::file_ops::{
+    categorize_io_error, categorize_trash_error, unique_path, ConflictPolicy, FailedEntry,
+    FailureReason, FileOperationResult, OperationType, OperationWarning, ProcessedFile,
+};
+use super::journal::record_operation;
+use super::json_store::{JsonFile, JsonStore};
+use super::preflight::is_writable;
+use crate::models::{encode_os_str, encode_path};
+
+/// File name of the log of paths this application moved to the trash
+const TRASH_LOG_FILE: &str = "trash_log.json";
+
+/// An item in the system trash that was deleted by this application
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashedItem {
+    /// Platform-specific trash item identifier
+    pub id: String,
+
+    /// File name
+    pub name: String,
+
+    /// Path the item was deleted from
+    pub original_path: String,
+
+    /// Deletion time (ISO 8601 format)
+    pub deleted_at: String,
+
+    /// Whether this item is a directory
+    pub is_directory: bool,
+
+    /// File size in bytes (None for directories)
+    pub size: Option<u64>,
+}
+
+/// Persistent record of paths this application moved to the trash
+///
+/// Loaded and saved through the `JsonStore<TrashLog>` in managed state.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashLog {
+    pub deleted_paths: HashSet<String>,
+}
+
+impl TrashLog {
+    /// Create the store that guards the trash log file
+    pub fn store() -> JsonStore<Self> {
+        JsonStore::new("trash log")
+    }
+}
+
+/// List trash items that were deleted by this application
+#[tauri::command]
+pub async fn list_trashed_items(
+    app_handle: AppHandle,
+    logs: State<'_, JsonStore<TrashLog>>,
+) -> Result<Vec<TrashedItem>, String> {
+    let log = logs.open(&trash_log_path(&app_handle)?)?;
+
+    Ok(app_trash_items(&log)?.iter().map(to_trashed_item).collect())
+}
+
+/// Restore trash items to their original paths
+///
+/// # Arguments
+/// * `ids` - IDs of the items to restore, as returned by `list_trashed_items`
+/// * `conflict_policy` - What to do when the original path is occupied (default: skip)
+/// * `dry_run` - Only validate and return the planned result (default: false)
+///
+/// # Returns
+/// * `Ok(FileOperationResult)` - Operation result with success/failure counts
+/// * `Err(String)` - Error message if the trash could not be read
+#[tauri::command]
+pub async fn restore_trashed_items(
+    app_handle: AppHandle,
+    logs: State<'_, JsonStore<TrashLog>>,
+    ids: Vec<String>,
+    conflict_policy: Option<ConflictPolicy>,
+    dry_run: Option<bool>,
+) -> Result<FileOperationResult, String> {
+    let mut log = logs.open(&trash_log_path(&app_handle)?)?;
+
+    let (items, mut failed_files) = select_items(&log, &ids)?;
+    let mut result = restore_items(
+        items,
+        conflict_policy.unwrap_or_default(),
+        dry_run.unwrap_or(false),
+    );
+    result.failed_files.append(&mut failed_files);
+    result.failed_count = result.failed_files.len() as u32;
+
+    if !result.dry_run {
+        forget_processed(&mut log, &mut result);
+        // Undo locks the journal before the trash log, so never the other way round
+        drop(log);
+        record_operation(&app_handle, &mut result);
+    }
+    Ok(result)
+}
+
+/// Permanently remove trash items
+///
+/// # Arguments
+/// * `ids` - IDs of the items to purge, as returned by `list_trashed_items`
+/// * `dry_run` - Only validate and return the planned result (default: false)
+///
+/// # Returns
+/// * `Ok(FileOperationResult)` - Operation result with success/failure counts
+/// * `Err(String)` - Error message if the trash could not be read
+#[tauri::command]
+pub async fn purge_trashed_items(
+    app_handle: AppHandle,
+    logs: State<'_, JsonStore<TrashLog>>,
+    ids: Vec<String>,
+    dry_run: Option<bool>,
+) -> Result<FileOperationResult, String> {
+    let mut log = logs.open(&trash_log_path(&app_handle)?)?;
+
+    let (items, mut failed_files) = select_items(&log, &ids)?;
+    let mut result = purge_items(items, dry_run.unwrap_or(false));
+    result.failed_files.append(&mut failed_files);
+    result.failed_count = result.failed_files.len() as u32;
+
+    if !result.dry_run {
+        forget_processed(&mut log, &mut result);
+        // Undo locks the journal before the trash log, so never the other way round
+        drop(log);
+        record_operation(&app_handle, &mut result);
+    }
+    Ok(result)
+}
+
+/// Remember paths that were moved to the trash by a delete operation
+///
+/// Files with a target path went to the quarantine instead and are skipped.
+pub(super) fn record_trashed(app_handle: &AppHandle, result: &mut FileOperationResult) {
+    let trashed: Vec<String> = result
+        .processed_files
+        .iter()
+        .filter(|file| file.target_path.is_none())
+        .map(|file| file.source_path.clone())
+        .collect();
+    if trashed.is_empty() {
+        return;
+    }
+
+    let logs = app_handle.state::<JsonStore<TrashLog>>();
+    let saved = trash_log_path(app_handle).and_then(|log_path| {
+        let mut log = logs.open(&log_path)?;
+        log.deleted_paths.extend(trashed);
+        log.save()
+    });
+
+    if let Err(message) = saved {
+        result.warnings.push(OperationWarning {
+            path: TRASH_LOG_FILE.to_string(),
+            message,
+        });
+    }
+}
+
+/// Restore the most recently trashed item for each of the given original paths
+///
+/// Used to undo delete operations. Paths without a matching trash item are
+/// reported as `PathNotFound`; occupied original paths are skipped.
+pub(super) fn restore_original_paths(
+    app_handle: &AppHandle,
+    paths: &[String],
+) -> Result<FileOperationResult, String> {
+    let logs = app_handle.state::<JsonStore<TrashLog>>();
+    let mut log = logs.open(&trash_log_path(app_handle)?)?;
+
+    let mut latest: HashMap<String, TrashItem> = HashMap::new();
+    for item in app_trash_items(&log)? {
+        let original_path = encode_path(&item.original_path());
+        match latest.get(&original_path) {
+            Some(existing) if existing.time_deleted >= item.time_deleted => {}
+            _ => {
+                latest.insert(original_path, item);
+            }
+        }
+    }
+
+    let mut items = Vec::new();
+    let mut failed_files = Vec::new();
+    for path in paths {
+        match latest.remove(path) {
+            Some(item) => items.push(item),
+            None => failed_files.push(FailedEntry {
+                path: path.clone(),
+                reason: FailureReason::PathNotFound,
+                error_message: format!("Item not found in trash: {}", path),
+            }),
+        }
+    }
+
+    let mut result = restore_items(items, ConflictPolicy::Skip, false);
+    result.failed_files.append(&mut failed_files);
+    result.failed_count = result.failed_files.len() as u32;
+
+    forget_processed(&mut log, &mut result);
+    Ok(result)
+}
+
+/// Restore trash items one by one, applying the conflict policy
+///
+/// In a dry run only the resulting paths and predicted failures are reported.
+pub fn restore_items(
+    items: Vec<TrashItem>,
+    conflict_policy: ConflictPolicy,
+    dry_run: bool,
+) -> FileOperationResult {
+    let start_time = Instant::now();
+
+    let mut failed_files: Vec<FailedEntry> = Vec::new();
+    let mut processed_files: Vec<ProcessedFile> = Vec::new();
+
+    for item in items {
+        let original_path = encode_path(&item.original_path());
+        let restored = if dry_run {
+            plan_restore(&item, conflict_policy)
+        } else {
+            restore_item(item, conflict_policy)
+        };
+        match restored {
+            Ok(restored_path) => processed_files.push(ProcessedFile {
+                source_path: original_path,
+                target_path: Some(encode_path(&restored_path)),
+            }),
+            Err(failed) => failed_files.push(failed),
+        }
+    }
+
+    finish_result(
+        OperationType::Restore,
+        start_time,
+        failed_files,
+        processed_files,
+        dry_run,
+    )
+}
+
+/// Permanently remove trash items one by one
+///
+/// In a dry run the items are only listed.
+pub fn purge_items(items: Vec<TrashItem>, dry_run: bool) -> FileOperationResult {
+    let start_time = Instant::now();
+
+    let mut failed_files: Vec<FailedEntry> = Vec::new();
+    let mut processed_files: Vec<ProcessedFile> = Vec::new();
+
+    for item in items {
+        let original_path = encode_path(&item.original_path());
+        let purged = if dry_run {
+            Ok(())
+        } else {
+            platform::purge(item)
+        };
+        match purged {
+            Ok(()) => processed_files.push(ProcessedFile {
+                source_path: original_path,
+                target_path: None,
+            }),
+            Err(e) => {
+                let (reason, message) = categorize_trash_error(&e);
+                failed_files.push(FailedEntry {
+                    path: original_path,
+                    reason,
+                    error_message: message,
+                });
+            }
+        }
+    }
+
+    finish_result(
+        OperationType::Purge,
+        start_time,
+        failed_files,
+        processed_files,
+        dry_run,
+    )
+}
+
+/// Determine where an item would be restored to, without restoring it
+fn plan_restore(item: &TrashItem, conflict_policy: ConflictPolicy) -> Result<PathBuf, FailedEntry> {
+    let original_path = item.original_path();
+    let failed = |reason: FailureReason, error_message: String| FailedEntry {
+        path: encode_path(&original_path),
+        reason,
+        error_message,
+    };
+
+    if item.original_parent.exists() && !is_writable(&item.original_parent) {
+        return Err(failed(
+            FailureReason::PermissionDenied,
+            format!(
+                "No write permission on folder: {}",
+                item.original_parent.display()
+            ),
+        ));
+    }
+
+    if original_path.symlink_metadata().is_err() {
+        return Ok(original_path);
+    }
+
+    match conflict_policy {
+        ConflictPolicy::Skip => Err(failed(
+            FailureReason::FileExists,
+            format!("File already exists: {}", original_path.display()),
+        )),
+        ConflictPolicy::Overwrite => Ok(original_path),
+        ConflictPolicy::Rename => Ok(unique_path(&original_path)),
+    }
+}
+
+/// Restore a single item and return the path it was restored to
+fn restore_item(item: TrashItem, conflict_policy: ConflictPolicy) -> Result<PathBuf, FailedEntry> {
+    let original_path = item.original_path();
+    let failed = |reason: FailureReason, error_message: String| FailedEntry {
+        path: encode_path(&original_path),
+        reason,
+        error_message,
+    };
+    let trash_failed = |e: &trash::Error| {
+        let (reason, message) = categorize_trash_error(e);
+        failed(reason, message)
+    };
+    let io_failed = |e: &std::io::Error| {
+        let (reason, message) = categorize_io_error(e);
+        failed(reason, message)
+    };
+
+    if original_path.symlink_metadata().is_err() {
+        platform::restore(item).map_err(|e| trash_failed(&e))?;
+        return Ok(original_path);
+    }
+
+    match conflict_policy {
+        ConflictPolicy::Skip => Err(failed(
+            FailureReason::FileExists,
+            format!("File already exists: {}", original_path.display()),
+        )),
+        ConflictPolicy::Overwrite => {
+            trash::delete(&original_path).map_err(|e| trash_failed(&e))?;
+            platform::restore(item).map_err(|e| trash_failed(&e))?;
+            Ok(original_path)
+        }
+        ConflictPolicy::Rename => {
+            // The trash can only restore to the original path, so park the
+            // existing file, restore, rename the restored copy and move the
+            // existing file back.
+            let mut parked_name = OsString::from(".");
+            parked_name.push(original_path.file_name().unwrap_or_default());
+            parked_name.push(".restoring");
+            let parked_path = unique_path(&original_path.with_file_name(parked_name));
+            std::fs::rename(&original_path, &parked_path).map_err(|e| io_failed(&e))?;
+
+            if let Err(e) = platform::restore(item) {
+                let _ = std::fs::rename(&parked_path, &original_path);
+                return Err(trash_failed(&e));
+            }
+
+            let renamed_path = unique_path(&original_path);
+            if let Err(e) = std::fs::rename(&original_path, &renamed_path) {
+                // Keep the restored item in place and un-park the existing
+                // file under a new name instead, never over the restored one
+                let (reason, message) = categorize_io_error(&e);
+                let moved_path = unique_path(&original_path);
+                let existing_path = match std::fs::rename(&parked_path, &moved_path) {
+                    Ok(()) => moved_path,
+                    Err(_) => parked_path,
+                };
+                return Err(failed(
+                    reason,
+                    format!(
+                        "{}; restored item left at {}, existing file moved to {}",
+                        message,
+                        original_path.display(),
+                        existing_path.display()
+                    ),
+                ));
+            }
+            if let Err(e) = std::fs::rename(&parked_path, &original_path) {
+                let (reason, message) = categorize_io_error(&e);
+                return Err(failed(
+                    reason,
+                    format!(
+                        "{}; restored item moved to {}, existing file left at {}",
+                        message,
+                        renamed_path.display(),
+                        parked_path.display()
+                    ),
+                ));
+            }
+            Ok(renamed_path)
+        }
+    }
+}
+
+/// Resolve item IDs to trash items deleted by this application
+///
+/// IDs that are not found are returned as failed entries.
+fn select_items(
+    log: &TrashLog,
+    ids: &[String],
+) -> Result<(Vec<TrashItem>, Vec<FailedEntry>), String> {
+    let mut wanted: HashSet<&str> = ids.iter().map(String::as_str).collect();
+
+    let items: Vec<TrashItem> = app_trash_items(log)?
+        .into_iter()
+        .filter(|item| wanted.remove(encode_os_str(&item.id).as_str()))
+        .collect();
+
+    let missing = wanted
+        .into_iter()
+        .map(|id| FailedEntry {
+            path: id.to_string(),
+            reason: FailureReason::PathNotFound,
+            error_message: format!("Item not found in trash: {}", id),
+        })
+        .collect();
+
+    Ok((items, missing))
+}
+
+/// List trash items whose original path is in the log
+fn app_trash_items(log: &TrashLog) -> Result<Vec<TrashItem>, String> {
+    let items = platform::list().map_err(|e| format!("Failed to read trash: {}", e))?;
+
+    Ok(items
+        .into_iter()
+        .filter(|item| {
+            log.deleted_paths
+                .contains(&encode_path(&item.original_path()))
+        })
+        .collect())
+}
+
+/// Drop restored or purged paths from the log
+///
+/// Paths that still have items in the trash are kept: an earlier delete of the
+/// same path, or the file an `Overwrite` restore moved out of the way. If the
+/// trash cannot be read, the log is left unchanged.
+fn forget_processed(log: &mut JsonFile<'_, TrashLog>, result: &mut FileOperationResult) {
+    let Ok(items) = platform::list() else {
+        return;
+    };
+    let remaining: HashSet<String> = items
+        .iter()
+        .map(|item| encode_path(&item.original_path()))
+        .collect();
+    for file in &result.processed_files {
+        if !remaining.contains(&file.source_path) {
+            log.deleted_paths.remove(&file.source_path);
+        }
+    }
+
+    if let Err(message) = log.save() {
+        result.warnings.push(OperationWarning {
+            path: TRASH_LOG_FILE.to_string(),
+            message,
+        });
+    }
+}
+
+fn to_trashed_item(item: &TrashItem) -> TrashedItem {
+    let size = platform::metadata(item).map(|metadata| metadata.size);
+
+    TrashedItem {
+        id: encode_os_str(&item.id),
+        name: item.name.to_string_lossy().to_string(),
+        original_path: encode_path(&item.original_path()),
+        deleted_at: DateTime::from_timestamp(item.time_deleted, 0)
+            .map(|time| time.to_rfc3339())
+            .unwrap_or_default(),
+        is_directory: matches!(size, Some(TrashItemSize::Entries(_))),
+        size: size.and_then(|size| size.size()),
+    }
+}
+
+/// Build the result of a per-item operation that has no progress or warnings
+pub(super) fn finish_result(
+    operation: OperationType,
+    start_time: Instant,
+    failed_files: Vec<FailedEntry>,
+    processed_files: Vec<ProcessedFile>,
+    dry_run: bool,
+) -> FileOperationResult {
+    FileOperationResult {
+        operation,
+        success_count: processed_files.len() as u32,
+        failed_count: failed_files.len() as u32,
+        failed_files,
+        duration_ms: start_time.elapsed().as_millis() as u64,
+        cancelled: false,
+        verified_files: Vec::new(),
+        warnings: Vec::new(),
+        processed_files,
+        journal_id: None,
+        dry_run,
+    }
+}
+
+fn trash_log_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map(|dir| dir.join(TRASH_LOG_FILE))
+        .map_err(|e| format!("Failed to resolve app data folder: {}", e))
+}
+
+/// Thin wrappers over the platform-limited part of the `trash` crate
+#[cfg(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+))]
+mod platform {
+    use trash::os_limited;
+    use trash::{TrashItem, TrashItemMetadata};
+
+    pub fn list() -> Result<Vec<TrashItem>, trash::Error> {
+        os_limited::list()
+    }
+
+    pub fn restore(item: TrashItem) -> Result<(), trash::Error> {
+        os_limited::restore_all([item])
+    }
+
+    pub fn purge(item: TrashItem) -> Result<(), trash::Error> {
+        os_limited::purge_all([item])
+    }
+
+    pub fn metadata(item: &TrashItem) -> Option<TrashItemMetadata> {
+        os_limited::metadata(item).ok()
+    }
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+)))]
+mod platform {
+    use trash::{TrashItem, TrashItemMetadata};
+
+    fn unsupported() -> trash::Error {
+        trash::Error::Unknown {
+            description: "Trash browsing is not supported on this platform".to_string(),
+        }
+    }
+
+    pub fn list() -> Result<Vec<TrashItem>, trash::Error> {
+        Err(unsupported())
+    }
+
+    pub fn restore(_item: TrashItem) -> Result<(), trash::Error> {
+        Err(unsupported())
+    }
+
+    pub fn purge(_item: TrashItem) -> Result<(), trash::Error> {
+        Err(unsupported())
+    }
+
+    pub fn metadata(_item: &TrashItem) -> Option<TrashItemMetadata> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_trash_log_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let log_path = temp_dir.path().join("data").join(TRASH_LOG_FILE);
+        let store = TrashLog::store();
+
+        let mut log = store.open(&log_path).unwrap();
+        assert!(log.deleted_paths.is_empty());
+
+        log.deleted_paths.insert("/home/user/photo.jpg".to_string());
+        log.save().unwrap();
+        drop(log);
+
+        let loaded = store.open(&log_path).unwrap();
+        assert!(loaded.deleted_paths.contains("/home/user/photo.jpg"));
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    fn test_restore_with_conflict() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("restore_me.txt");
+        std::fs::write(&file_path, "original").unwrap();
+        trash::delete(&file_path).unwrap();
+        std::fs::write(&file_path, "replacement").unwrap();
+
+        let mut log = TrashLog::default();
+        log.deleted_paths
+            .insert(file_path.to_string_lossy().to_string());
+
+        let planned = restore_items(app_trash_items(&log).unwrap(), ConflictPolicy::Rename, true);
+        assert!(planned.dry_run);
+        assert_eq!(
+            planned.processed_files[0].target_path.as_deref(),
+            Some(
+                temp_dir
+                    .path()
+                    .join("restore_me (1).txt")
+                    .to_string_lossy()
+                    .as_ref()
+            )
+        );
+
+        let skipped = restore_items(app_trash_items(&log).unwrap(), ConflictPolicy::Skip, false);
+        assert_eq!(skipped.failed_count, 1);
+        assert_eq!(skipped.failed_files[0].reason, FailureReason::FileExists);
+
+        let renamed = restore_items(
+            app_trash_items(&log).unwrap(),
+            ConflictPolicy::Rename,
+            false,
+        );
+        assert_eq!(renamed.success_count, 1);
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "replacement");
+        assert_eq!(
+            std::fs::read_to_string(temp_dir.path().join("restore_me (1).txt")).unwrap(),
+            "original"
+        );
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    fn test_overwrite_keeps_displaced_file_listed() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("overwrite_me.txt");
+        std::fs::write(&file_path, "original").unwrap();
+        trash::delete(&file_path).unwrap();
+        std::fs::write(&file_path, "replacement").unwrap();
+
+        let store = TrashLog::store();
+        let mut log = store.open(&temp_dir.path().join(TRASH_LOG_FILE)).unwrap();
+        log.deleted_paths
+            .insert(file_path.to_string_lossy().to_string());
+
+        let mut result = restore_items(
+            app_trash_items(&log).unwrap(),
+            ConflictPolicy::Overwrite,
+            false,
+        );
+        assert_eq!(result.success_count, 1);
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "original");
+
+        forget_processed(&mut log, &mut result);
+        let displaced = app_trash_items(&log).unwrap();
+        assert_eq!(displaced.len(), 1);
+
+        let purged = purge_items(displaced, false);
+        assert_eq!(purged.success_count, 1);
+    }
+}
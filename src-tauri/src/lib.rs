@@ -3,11 +3,21 @@
 //! This application provides a desktop file scanner with the ability to
 //! browse, filter, and manage files across Windows and macOS.
 
+mod analysis;
 mod commands;
 mod models;
 mod scanner;
 
-use commands::{copy_files, delete_files, scan_folder};
+use commands::{
+    cancel_operation, check_portability, copy_files, create_archive, delete_files, extract_archive,
+    find_empty_items, find_stale_files, find_symlink_issues, get_quarantine_settings,
+    list_journal_entries, list_quarantined_items, list_trashed_items, organize_files,
+    prepare_secure_delete, purge_quarantined_items, purge_trashed_items, quarantine_files,
+    rename_files, restore_quarantined_items, restore_trashed_items, scan_folder, scan_secrets,
+    secure_delete_files, security_audit, set_quarantine_settings, size_hierarchy, top_n,
+    treemap_layout, undo_operation, Journal, OperationRegistry, QuarantineManifest, ScanCache,
+    SecureDeleteConfirmations, TrashLog,
+};
 
 /// Greet command for testing IPC communication
 #[tauri::command]
@@ -21,11 +31,44 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .manage(OperationRegistry::new())
+        .manage(SecureDeleteConfirmations::new())
+        .manage(ScanCache::new())
+        .manage(Journal::store())
+        .manage(TrashLog::store())
+        .manage(QuarantineManifest::store())
         .invoke_handler(tauri::generate_handler![
             greet,
             scan_folder,
             delete_files,
-            copy_files
+            copy_files,
+            cancel_operation,
+            list_trashed_items,
+            restore_trashed_items,
+            purge_trashed_items,
+            list_journal_entries,
+            undo_operation,
+            quarantine_files,
+            list_quarantined_items,
+            restore_quarantined_items,
+            purge_quarantined_items,
+            get_quarantine_settings,
+            set_quarantine_settings,
+            prepare_secure_delete,
+            secure_delete_files,
+            rename_files,
+            organize_files,
+            create_archive,
+            extract_archive,
+            find_empty_items,
+            find_symlink_issues,
+            top_n,
+            treemap_layout,
+            size_hierarchy,
+            find_stale_files,
+            security_audit,
+            scan_secrets,
+            check_portability
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -2,6 +2,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use super::path_encoding::{display_path, is_encoded};
+
 /// File category classification
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
@@ -25,11 +27,18 @@ impl Default for FileCategory {
 #[serde(rename_all = "camelCase")]
 pub struct FileEntry {
     /// Full file path (serves as unique identifier)
+    ///
+    /// Paths that are not valid Unicode are encoded losslessly (see
+    /// `models::path_encoding`); pass them back to commands unchanged.
     pub path: String,
 
-    /// File name (including extension)
+    /// File name (including extension), for display
     pub name: String,
 
+    /// Readable form of `path` when it had to be encoded (None otherwise)
+    #[serde(default)]
+    pub display_path: Option<String>,
+
     /// Whether this entry is a directory
     pub is_directory: bool,
 
@@ -39,6 +48,14 @@ pub struct FileEntry {
     /// Last modified time (ISO 8601 format)
     pub modified_at: String,
 
+    /// Last access time (ISO 8601 format, None if the platform does not report it)
+    #[serde(default)]
+    pub accessed_at: Option<String>,
+
+    /// Creation (birth) time (ISO 8601 format, None if the platform does not report it)
+    #[serde(default)]
+    pub created_at: Option<String>,
+
     /// File category classification
     pub category: FileCategory,
 
@@ -50,6 +67,43 @@ pub struct FileEntry {
 
     /// Parent folder path
     pub parent_path: String,
+
+    /// Whether this entry is a member inside an archive rather than a file on disk
+    #[serde(default)]
+    pub is_virtual: bool,
+
+    /// Whether this entry is a symbolic link
+    #[serde(default)]
+    pub is_symlink: bool,
+
+    /// Target of a symbolic link as stored in the link (None if unreadable)
+    #[serde(default)]
+    pub link_target: Option<String>,
+
+    /// Numeric owner id (Unix only, when ownership was requested)
+    #[serde(default)]
+    pub uid: Option<u32>,
+
+    /// Owner user name (None if the uid has no user entry)
+    #[serde(default)]
+    pub owner: Option<String>,
+
+    /// Numeric group id (Unix only, when ownership was requested)
+    #[serde(default)]
+    pub gid: Option<u32>,
+
+    /// Group name (None if the gid has no group entry)
+    #[serde(default)]
+    pub group: Option<String>,
+
+    /// A user or group lookup failed (e.g. the directory service was
+    /// unreachable), so a missing name does not mean a missing account
+    #[serde(default)]
+    pub names_unresolved: bool,
+
+    /// Permission bits including setuid, setgid and sticky (e.g. 0o4755)
+    #[serde(default)]
+    pub mode: Option<u32>,
 }
 
 impl FileEntry {
@@ -65,16 +119,84 @@ impl FileEntry {
         depth: u32,
         parent_path: String,
     ) -> Self {
+        let display_path = is_encoded(&path).then(|| display_path(&path));
         Self {
             path,
             name,
+            display_path,
             is_directory,
             size,
             modified_at,
+            accessed_at: None,
+            created_at: None,
             category,
             extension,
             depth,
             parent_path,
+            is_virtual: false,
+            is_symlink: false,
+            link_target: None,
+            uid: None,
+            owner: None,
+            gid: None,
+            group: None,
+            names_unresolved: false,
+            mode: None,
+        }
+    }
+
+    /// Set the access and creation times
+    pub fn with_times(mut self, accessed_at: Option<String>, created_at: Option<String>) -> Self {
+        self.accessed_at = accessed_at;
+        self.created_at = created_at;
+        self
+    }
+
+    /// Mark the entry as an archive member
+    pub fn with_virtual(mut self, is_virtual: bool) -> Self {
+        self.is_virtual = is_virtual;
+        self
+    }
+
+    /// Mark the entry as a symbolic link pointing to `target`
+    pub fn with_symlink(mut self, target: Option<String>) -> Self {
+        self.is_symlink = true;
+        self.link_target = target;
+        self
+    }
+
+    /// Set the owner id and its user name
+    pub fn with_owner(mut self, uid: u32, owner: Option<String>) -> Self {
+        self.uid = Some(uid);
+        self.owner = owner;
+        self
+    }
+
+    /// Set the group id and its group name
+    pub fn with_group(mut self, gid: u32, group: Option<String>) -> Self {
+        self.gid = Some(gid);
+        self.group = group;
+        self
+    }
+
+    /// Mark the owner or group name as unknown because its lookup failed
+    pub fn with_names_unresolved(mut self, unresolved: bool) -> Self {
+        self.names_unresolved = unresolved;
+        self
+    }
+
+    /// Set the permission bits
+    pub fn with_mode(mut self, mode: u32) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Name identifying the owner: the user name, or the uid if it has none
+    pub fn owner_key(&self) -> Option<String> {
+        match (&self.owner, self.uid) {
+            (Some(owner), _) => Some(owner.clone()),
+            (None, Some(uid)) => Some(uid.to_string()),
+            (None, None) => None,
         }
     }
 }
@@ -111,5 +233,6 @@ mod tests {
         assert!(json.contains("\"path\":\"/path/to/file.txt\""));
         assert!(json.contains("\"isDirectory\":false"));
         assert!(json.contains("\"category\":\"document\""));
+        assert!(json.contains("\"isVirtual\":false"));
     }
 }
@@ -1,9 +1,14 @@
 //! Data models for the file scanner application
 
 mod file_entry;
+mod path_encoding;
 mod scan_result;
 
 pub use file_entry::{FileCategory, FileEntry};
+pub use path_encoding::{
+    append_to_encoded, decode_path, display_path, encode_os_str, encode_path, is_encoded,
+};
 pub use scan_result::{
-    FailedEntry, FailureReason, ScanProgress, ScanResult, ScanStats, ScanStatus,
+    AgeBucket, FailedEntry, FailureReason, GroupStats, ScanProgress, ScanResult, ScanStats,
+    ScanStatus, SizeBucket,
 };
@@ -0,0 +1,238 @@
+//! Lossless string form of file system paths
+//!
+//! Paths are not always valid Unicode: Unix file names are arbitrary bytes and
+//! Windows names may contain unpaired UTF-16 surrogates. Such paths cannot be
+//! sent to the frontend as plain strings without losing information, so they
+//! are encoded instead:
+//!
+//! * Valid Unicode paths are used unchanged.
+//! * Other paths start with a NUL character, which no real path can contain,
+//!   followed by the path with `%` written as `%25` and every byte that is not
+//!   valid UTF-8 written as `%XX` (Unix) or every unpaired surrogate written
+//!   as `%uXXXX` (Windows).
+//!
+//! Every command taking paths decodes them with [`decode_path`], so entries of
+//! a scan can be passed back unchanged.
+
+use std::ffi::{OsStr, OsString};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// First character of an encoded path
+const ENCODED_MARKER: char = '\0';
+
+/// String form of a path that [`decode_path`] turns back into the same path
+pub fn encode_path(path: &Path) -> String {
+    encode_os_str(path.as_os_str())
+}
+
+/// String form of a file name or other OS string, see [`encode_path`]
+pub fn encode_os_str(value: &OsStr) -> String {
+    if let Some(text) = value.to_str() {
+        return text.to_string();
+    }
+
+    let mut encoded = String::from(ENCODED_MARKER);
+    encode_raw(value, &mut encoded);
+    encoded
+}
+
+/// Path for a string produced by [`encode_path`] or typed by the user
+///
+/// Strings without the marker are taken literally. A malformed escape is
+/// kept as written, which yields a path that does not exist.
+pub fn decode_path(encoded: &str) -> PathBuf {
+    match encoded.strip_prefix(ENCODED_MARKER) {
+        Some(raw) => PathBuf::from(decode_raw(raw)),
+        None => PathBuf::from(encoded),
+    }
+}
+
+/// Append plain text to an encoded path, keeping the result decodable
+pub fn append_to_encoded(encoded: &str, suffix: &str) -> String {
+    let mut joined = encoded.to_string();
+    if is_encoded(encoded) {
+        push_escaped(suffix, &mut joined);
+    } else {
+        joined.push_str(suffix);
+    }
+    joined
+}
+
+/// Human-readable form of an encoded path (invalid parts become U+FFFD)
+pub fn display_path(encoded: &str) -> String {
+    match encoded.strip_prefix(ENCODED_MARKER) {
+        Some(_) => decode_path(encoded).to_string_lossy().to_string(),
+        None => encoded.to_string(),
+    }
+}
+
+/// Whether a path string needed encoding
+pub fn is_encoded(path: &str) -> bool {
+    path.starts_with(ENCODED_MARKER)
+}
+
+/// Append valid text with `%` escaped
+fn push_escaped(text: &str, out: &mut String) {
+    for c in text.chars() {
+        if c == '%' {
+            out.push_str("%25");
+        } else {
+            out.push(c);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn encode_raw(value: &OsStr, out: &mut String) {
+    use std::os::unix::ffi::OsStrExt;
+
+    let mut rest = value.as_bytes();
+    while !rest.is_empty() {
+        match std::str::from_utf8(rest) {
+            Ok(text) => {
+                push_escaped(text, out);
+                break;
+            }
+            Err(error) => {
+                let (valid, invalid) = rest.split_at(error.valid_up_to());
+                push_escaped(std::str::from_utf8(valid).unwrap_or_default(), out);
+                let invalid_len = error.error_len().unwrap_or(invalid.len());
+                for byte in &invalid[..invalid_len] {
+                    let _ = write!(out, "%{:02X}", byte);
+                }
+                rest = &invalid[invalid_len..];
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn decode_raw(raw: &str) -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+
+    let mut bytes = Vec::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(index) = rest.find('%') {
+        bytes.extend_from_slice(&rest.as_bytes()[..index]);
+        let escape = &rest[index + 1..];
+        if let Some(byte) = escape
+            .get(..2)
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            bytes.push(byte);
+            rest = &escape[2..];
+        } else {
+            bytes.push(b'%');
+            rest = escape;
+        }
+    }
+    bytes.extend_from_slice(rest.as_bytes());
+    OsString::from_vec(bytes)
+}
+
+#[cfg(windows)]
+fn encode_raw(value: &OsStr, out: &mut String) {
+    use std::os::windows::ffi::OsStrExt;
+
+    for decoded in char::decode_utf16(value.encode_wide()) {
+        match decoded {
+            Ok('%') => out.push_str("%25"),
+            Ok(c) => out.push(c),
+            Err(error) => {
+                let _ = write!(out, "%u{:04X}", error.unpaired_surrogate());
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+fn decode_raw(raw: &str) -> OsString {
+    use std::os::windows::ffi::OsStringExt;
+
+    let mut units: Vec<u16> = Vec::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(index) = rest.find('%') {
+        units.extend(rest[..index].encode_utf16());
+        let escape = &rest[index + 1..];
+        let unit = if let Some(hex) = escape.strip_prefix('u') {
+            hex.get(..4)
+                .and_then(|hex| u16::from_str_radix(hex, 16).ok())
+                .map(|unit| (unit, 5))
+        } else {
+            escape
+                .get(..2)
+                .filter(|hex| *hex == "25")
+                .map(|_| (u16::from(b'%'), 2))
+        };
+        match unit {
+            Some((unit, len)) => {
+                units.push(unit);
+                rest = &escape[len..];
+            }
+            None => {
+                units.push(u16::from(b'%'));
+                rest = escape;
+            }
+        }
+    }
+    units.extend(rest.encode_utf16());
+    OsString::from_wide(&units)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unicode_paths_are_unchanged() {
+        for path in ["/home/user/100% done.txt", "/tmp/caf\u{e9}/日本.pdf", ""] {
+            let encoded = encode_path(Path::new(path));
+            assert_eq!(encoded, path);
+            assert!(!is_encoded(&encoded));
+            assert_eq!(decode_path(&encoded), PathBuf::from(path));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_round_trip() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let raw = OsStr::from_bytes(b"/tmp/r\xe9sum\xe9 100%/\xff\xfe.txt");
+        let encoded = encode_os_str(raw);
+        assert_eq!(encoded, "\0/tmp/r%E9sum%E9 100%25/%FF%FE.txt");
+        assert!(is_encoded(&encoded));
+        assert_eq!(decode_path(&encoded).as_os_str(), raw);
+        assert_eq!(
+            display_path(&encoded),
+            "/tmp/r\u{fffd}sum\u{fffd} 100%/\u{fffd}\u{fffd}.txt"
+        );
+
+        let member = append_to_encoded(&encoded, "!/50%41");
+        assert_eq!(
+            display_path(&member),
+            format!("{}!/50%41", display_path(&encoded))
+        );
+        assert_eq!(
+            append_to_encoded("/tmp/a.zip", "!/50%41"),
+            "/tmp/a.zip!/50%41"
+        );
+
+        // Malformed escapes are kept literally
+        assert_eq!(decode_path("\0/tmp/%zz%4").as_os_str(), "/tmp/%zz%4");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_file_on_disk() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(OsStr::from_bytes(b"bad\x80name"));
+        std::fs::write(&path, b"data").unwrap();
+
+        let decoded = decode_path(&encode_path(&path));
+        assert_eq!(std::fs::read(decoded).unwrap(), b"data");
+    }
+}
@@ -2,6 +2,7 @@
 
 use std::collections::HashMap;
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use super::file_entry::{FileCategory, FileEntry};
@@ -13,6 +14,8 @@ pub enum FailureReason {
     PermissionDenied,
     FileLocked,
     PathNotFound,
+    /// Symbolic link pointing to one of its own ancestors
+    SymlinkLoop,
     Unknown,
 }
 
@@ -40,9 +43,52 @@ impl FailedEntry {
     }
 }
 
-/// Statistics from a scan operation
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+/// Largest size given its own power-of-two bucket (larger files share the last one)
+const MAX_SIZE_BUCKET_BITS: u32 = 40;
+
+/// Upper age limits in days for the age distribution (older files fall into a final bucket)
+const AGE_BUCKET_DAYS: [u32; 7] = [1, 7, 30, 90, 365, 730, 1825];
+
+/// Number of files and their combined size for one group of files
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupStats {
+    pub count: u64,
+    pub total_size: u64,
+}
+
+impl GroupStats {
+    fn add(&mut self, size: u64) {
+        self.count += 1;
+        self.total_size += size;
+    }
+}
+
+/// One bucket of the file-size distribution
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SizeBucket {
+    /// Smallest size in bytes counted here (the bucket ends where the next one starts)
+    pub min_size: u64,
+
+    #[serde(flatten)]
+    pub files: GroupStats,
+}
+
+/// One bucket of the file-age distribution
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
+pub struct AgeBucket {
+    /// Files modified less than this many days ago (None for the oldest bucket)
+    pub max_age_days: Option<u32>,
+
+    #[serde(flatten)]
+    pub files: GroupStats,
+}
+
+/// Statistics from a scan operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
 pub struct ScanStats {
     /// Total number of files (excluding folders)
     pub total_files: u64,
@@ -67,6 +113,77 @@ pub struct ScanStats {
 
     /// Count of other files
     pub other_count: u64,
+
+    /// Number of files inside archives (included in `total_files`)
+    pub virtual_files: u64,
+
+    /// Uncompressed size of files inside archives (included in `total_size`)
+    pub virtual_size: u64,
+
+    /// File count and size per lowercase extension ("" for files without one)
+    pub extensions: HashMap<String, GroupStats>,
+
+    /// File-size distribution, ordered by `min_size`
+    pub size_buckets: Vec<SizeBucket>,
+
+    /// File-age distribution by `modified_at`, youngest first
+    pub age_buckets: Vec<AgeBucket>,
+
+    /// Number of files and folders at each depth (index 0 = depth 0)
+    pub depth_counts: Vec<u64>,
+
+    /// File count and size per owner (user name, or uid if it has none);
+    /// empty unless ownership was recorded
+    pub owners: HashMap<String, GroupStats>,
+
+    /// Moment file ages are measured from
+    #[serde(skip)]
+    reference_time: DateTime<Utc>,
+}
+
+impl Default for ScanStats {
+    fn default() -> Self {
+        let size_buckets = std::iter::once(0)
+            .chain((0..=MAX_SIZE_BUCKET_BITS).map(|bits| 1u64 << bits))
+            .map(size_bucket)
+            .collect();
+
+        let age_buckets = AGE_BUCKET_DAYS
+            .iter()
+            .map(|&days| Some(days))
+            .chain(std::iter::once(None))
+            .map(|max_age_days| AgeBucket {
+                max_age_days,
+                files: GroupStats::default(),
+            })
+            .collect();
+
+        Self {
+            total_files: 0,
+            total_folders: 0,
+            total_size: 0,
+            document_count: 0,
+            image_count: 0,
+            video_count: 0,
+            audio_count: 0,
+            other_count: 0,
+            virtual_files: 0,
+            virtual_size: 0,
+            extensions: HashMap::new(),
+            size_buckets,
+            age_buckets,
+            depth_counts: Vec::new(),
+            owners: HashMap::new(),
+            reference_time: Utc::now(),
+        }
+    }
+}
+
+fn size_bucket(min_size: u64) -> SizeBucket {
+    SizeBucket {
+        min_size,
+        files: GroupStats::default(),
+    }
 }
 
 impl ScanStats {
@@ -75,14 +192,43 @@ impl ScanStats {
         Self::default()
     }
 
+    /// Replace the power-of-two size buckets with custom lower bounds
+    ///
+    /// A bucket starting at 0 is always present so every file has a home.
+    pub fn with_size_buckets(mut self, bounds: &[u64]) -> Self {
+        let mut bounds = bounds.to_vec();
+        bounds.push(0);
+        bounds.sort_unstable();
+        bounds.dedup();
+        self.size_buckets = bounds.into_iter().map(size_bucket).collect();
+        self
+    }
+
+    /// Measure file ages from `time` instead of the moment the stats were created
+    pub fn with_reference_time(mut self, time: DateTime<Utc>) -> Self {
+        self.reference_time = time;
+        self
+    }
+
     /// Add a file entry to the statistics
     pub fn add_entry(&mut self, entry: &FileEntry) {
+        let depth = entry.depth as usize;
+        if self.depth_counts.len() <= depth {
+            self.depth_counts.resize(depth + 1, 0);
+        }
+        self.depth_counts[depth] += 1;
+
         if entry.is_directory {
             self.total_folders += 1;
         } else {
             self.total_files += 1;
             self.total_size += entry.size;
 
+            if entry.is_virtual {
+                self.virtual_files += 1;
+                self.virtual_size += entry.size;
+            }
+
             match entry.category {
                 FileCategory::Document => self.document_count += 1,
                 FileCategory::Image => self.image_count += 1,
@@ -91,6 +237,52 @@ impl ScanStats {
                 FileCategory::Other => self.other_count += 1,
                 FileCategory::Folder => {} // Handled above
             }
+
+            self.extensions
+                .entry(entry.extension.to_lowercase())
+                .or_default()
+                .add(entry.size);
+
+            if let Some(owner) = entry.owner_key() {
+                self.owners.entry(owner).or_default().add(entry.size);
+            }
+
+            self.add_size(entry.size);
+            self.add_age(entry);
+        }
+    }
+
+    fn add_size(&mut self, size: u64) {
+        let index = self
+            .size_buckets
+            .partition_point(|bucket| bucket.min_size <= size);
+        if let Some(bucket) = index
+            .checked_sub(1)
+            .and_then(|i| self.size_buckets.get_mut(i))
+        {
+            bucket.files.add(size);
+        }
+    }
+
+    fn add_age(&mut self, entry: &FileEntry) {
+        let Ok(modified) = DateTime::parse_from_rfc3339(&entry.modified_at) else {
+            return;
+        };
+
+        // Timestamps in the future count as brand new
+        let age_days = (self.reference_time - modified.with_timezone(&Utc))
+            .num_days()
+            .max(0);
+
+        let bucket = self
+            .age_buckets
+            .iter_mut()
+            .find(|bucket| match bucket.max_age_days {
+                Some(days) => age_days < i64::from(days),
+                None => true,
+            });
+        if let Some(bucket) = bucket {
+            bucket.files.add(entry.size);
         }
     }
 
@@ -207,13 +399,25 @@ mod tests {
         let file_entry = FileEntry {
             path: "/test/file.pdf".to_string(),
             name: "file.pdf".to_string(),
+            display_path: None,
             is_directory: false,
             size: 1024,
             modified_at: "2025-01-01T00:00:00Z".to_string(),
+            accessed_at: None,
+            created_at: None,
             category: FileCategory::Document,
             extension: "pdf".to_string(),
             depth: 1,
             parent_path: "/test".to_string(),
+            is_virtual: false,
+            is_symlink: false,
+            link_target: None,
+            uid: None,
+            owner: None,
+            gid: None,
+            group: None,
+            names_unresolved: false,
+            mode: None,
         };
 
         stats.add_entry(&file_entry);
@@ -231,13 +435,25 @@ mod tests {
         let folder_entry = FileEntry {
             path: "/test/folder".to_string(),
             name: "folder".to_string(),
+            display_path: None,
             is_directory: true,
             size: 0,
             modified_at: "2025-01-01T00:00:00Z".to_string(),
+            accessed_at: None,
+            created_at: None,
             category: FileCategory::Folder,
             extension: "".to_string(),
             depth: 1,
             parent_path: "/test".to_string(),
+            is_virtual: false,
+            is_symlink: false,
+            link_target: None,
+            uid: None,
+            owner: None,
+            gid: None,
+            group: None,
+            names_unresolved: false,
+            mode: None,
         };
 
         stats.add_entry(&folder_entry);
@@ -247,6 +463,93 @@ mod tests {
         assert_eq!(stats.total_size, 0);
     }
 
+    fn file(name: &str, size: u64, modified_at: &str, depth: u32) -> FileEntry {
+        FileEntry::new(
+            format!("/test/{}", name),
+            name.to_string(),
+            false,
+            size,
+            modified_at.to_string(),
+            FileCategory::Other,
+            name.rsplit_once('.')
+                .map(|(_, ext)| ext.to_string())
+                .unwrap_or_default(),
+            depth,
+            "/test".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_scan_stats_distributions() {
+        let now = DateTime::parse_from_rfc3339("2025-06-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut stats = ScanStats::new().with_reference_time(now);
+
+        stats.add_entry(&file("a.txt", 0, "2025-05-31T12:00:00Z", 1));
+        stats.add_entry(&file("b.TXT", 3, "2025-05-20T00:00:00Z", 2));
+        stats.add_entry(&file("c.log", 1000, "2020-01-01T00:00:00Z", 2));
+        stats.add_entry(&file("noext", 4, "not a date", 3));
+
+        assert_eq!(
+            stats.extensions["txt"],
+            GroupStats {
+                count: 2,
+                total_size: 3
+            }
+        );
+        assert_eq!(stats.extensions["log"].total_size, 1000);
+        assert_eq!(stats.extensions[""].count, 1);
+
+        // Buckets start at 0, 1, 2, 4, ... so 3 lands in [2, 4) and 1000 in [512, 1024)
+        let count = |min: u64| {
+            stats
+                .size_buckets
+                .iter()
+                .find(|b| b.min_size == min)
+                .map(|b| b.files.count)
+        };
+        assert_eq!(count(0), Some(1));
+        assert_eq!(count(2), Some(1));
+        assert_eq!(count(4), Some(1));
+        assert_eq!(count(512), Some(1));
+
+        let ages: Vec<u64> = stats.age_buckets.iter().map(|b| b.files.count).collect();
+        assert_eq!(ages, vec![1, 0, 1, 0, 0, 0, 0, 1]);
+
+        assert_eq!(stats.depth_counts, vec![0, 1, 2, 1]);
+    }
+
+    #[test]
+    fn test_scan_stats_custom_size_buckets() {
+        let mut stats = ScanStats::new().with_size_buckets(&[1_000, 100]);
+        stats.add_entry(&file("a", 50, "2025-01-01T00:00:00Z", 1));
+        stats.add_entry(&file("b", 100, "2025-01-01T00:00:00Z", 1));
+        stats.add_entry(&file("c", 5_000, "2025-01-01T00:00:00Z", 1));
+
+        let buckets: Vec<(u64, u64)> = stats
+            .size_buckets
+            .iter()
+            .map(|b| (b.min_size, b.files.count))
+            .collect();
+        assert_eq!(buckets, vec![(0, 1), (100, 1), (1_000, 1)]);
+    }
+
+    #[test]
+    fn test_scan_stats_serialization() {
+        let mut stats = ScanStats::new();
+        stats.add_entry(&file("a.pdf", 10, "2025-01-01T00:00:00Z", 1));
+
+        let json = serde_json::to_string(&stats).unwrap();
+        assert!(json.contains("\"extensions\":{\"pdf\":{\"count\":1,\"totalSize\":10}}"));
+        assert!(json.contains("\"minSize\":8,\"count\":1,\"totalSize\":10"));
+        assert!(json.contains("\"depthCounts\":[0,1]"));
+
+        let restored: ScanStats = serde_json::from_str("{\"totalFiles\":3}").unwrap();
+        assert_eq!(restored.total_files, 3);
+        assert!(restored.extensions.is_empty());
+    }
+
     #[test]
     fn test_failure_reason_serialization() {
         let reason = FailureReason::PermissionDenied;
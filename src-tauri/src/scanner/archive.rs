@@ -0,0 +1,552 @@
+//! Listing of ZIP and tar archive members as virtual file entries
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+use flate2::read::GzDecoder;
+use xz2::read::XzDecoder;
+use zip::ZipArchive;
+
+use crate::models::{decode_path, encode_os_str, encode_path, is_encoded, FileCategory, FileEntry};
+use crate::scanner::file_info::{classify_extension, get_extension};
+
+/// Separator between an archive path and a member name, e.g. `backup.zip!/docs/a.pdf`
+pub const ARCHIVE_PATH_SEPARATOR: &str = "!/";
+
+/// Largest compressed tar archive expanded while scanning (listing one means
+/// decompressing all of it)
+pub const MAX_COMPRESSED_TAR_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Most bytes a tar stream may decompress to while it is listed for a scan
+pub const MAX_EXPANDED_TAR_SIZE: u64 = 4 * 1024 * 1024 * 1024;
+
+/// Most members listed per archive while scanning
+pub const MAX_LISTED_MEMBERS: usize = 10_000;
+
+/// Archive formats that can be listed and extracted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+    TarXz,
+    TarZst,
+}
+
+impl ArchiveKind {
+    /// Detect the archive kind from a file name
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        if name.ends_with(".zip") {
+            Some(Self::Zip)
+        } else if name.ends_with(".tar") {
+            Some(Self::Tar)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else if name.ends_with(".tar.xz") || name.ends_with(".txz") {
+            Some(Self::TarXz)
+        } else if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+            Some(Self::TarZst)
+        } else {
+            None
+        }
+    }
+}
+
+/// A member as read from an archive listing
+#[derive(Debug, Clone)]
+pub struct ArchiveMemberInfo {
+    /// Normalized member name with `/` separators, encoded like a path (see
+    /// `encode_path`) if it is not valid Unicode
+    pub name: String,
+    pub is_directory: bool,
+    /// Uncompressed size in bytes (0 for directories)
+    pub size: u64,
+    /// Modification time (ISO 8601 format), if the archive stores one
+    pub modified_at: Option<String>,
+}
+
+/// List the members of an archive as virtual entries below `archive`
+///
+/// Member paths are `<archive path>!/<member name>`, sizes are uncompressed
+/// sizes and depths continue from the archive's own depth. Folders that are
+/// only implied by member names are added as well, so the archive can be
+/// browsed like a folder. Nested archives are not expanded.
+///
+/// Compressed tar archives larger than `MAX_COMPRESSED_TAR_SIZE` or expanding
+/// to more than `MAX_EXPANDED_TAR_SIZE`, and archives with more than
+/// `MAX_LISTED_MEMBERS` members are not listed but reported as an error.
+pub fn list_archive_entries(archive: &FileEntry) -> Result<Vec<FileEntry>, String> {
+    let path = decode_path(&archive.path);
+    let compressed = !matches!(
+        ArchiveKind::from_path(&path),
+        Some(ArchiveKind::Zip | ArchiveKind::Tar)
+    );
+    if compressed && archive.size > MAX_COMPRESSED_TAR_SIZE {
+        return Err(format!(
+            "Compressed archive too large to expand ({} bytes, limit {})",
+            archive.size, MAX_COMPRESSED_TAR_SIZE
+        ));
+    }
+
+    let mut members = BTreeMap::new();
+    for member in read_members(&path, MAX_LISTED_MEMBERS, MAX_EXPANDED_TAR_SIZE)? {
+        add_implied_folders(&mut members, &member.name);
+        members.insert(member.name.clone(), member);
+    }
+
+    Ok(members
+        .values()
+        .map(|member| virtual_entry(archive, member))
+        .collect())
+}
+
+/// Read the member listing of a ZIP or tar archive, sorted by name
+///
+/// Members whose names would leave the archive (`..`) are skipped. If a name
+/// occurs more than once, the last member wins, as it would on extraction.
+pub fn read_archive_members(path: &Path) -> Result<Vec<ArchiveMemberInfo>, String> {
+    read_members(path, usize::MAX, u64::MAX)
+}
+
+/// Path of an archive member, e.g. `backup.zip!/docs/a.pdf`
+///
+/// Both the archive path and the member name may be encoded.
+pub fn member_path(archive_path: &str, member_name: &str) -> String {
+    let mut path = decode_path(archive_path).into_os_string();
+    path.push(ARCHIVE_PATH_SEPARATOR);
+    path.push(decode_path(member_name));
+    encode_os_str(&path)
+}
+
+/// Read a member listing, failing on more than `max_members` members or a
+/// tar stream that decompresses to more than `max_expanded` bytes
+fn read_members(
+    path: &Path,
+    max_members: usize,
+    max_expanded: u64,
+) -> Result<Vec<ArchiveMemberInfo>, String> {
+    let kind = ArchiveKind::from_path(path)
+        .ok_or_else(|| format!("Not a supported archive: {}", path.display()))?;
+    let file = File::open(path).map_err(|e| format!("Failed to open archive: {}", e))?;
+
+    let members = if kind == ArchiveKind::Zip {
+        read_zip_members(file, max_members)?
+    } else {
+        let reader =
+            open_tar_stream(file, kind).map_err(|e| format!("Failed to open archive: {}", e))?;
+        let reader = BoundedReader {
+            inner: reader,
+            remaining: max_expanded,
+        };
+        read_tar_members(reader, max_members)?
+    };
+
+    let mut by_name = BTreeMap::new();
+    for member in members {
+        by_name.insert(member.name.clone(), member);
+    }
+    Ok(by_name.into_values().collect())
+}
+
+/// Wrap an archive file in the decompressor for its tar variant
+///
+/// Returns the plain file reader for `Tar`; must not be called for `Zip`.
+pub fn open_tar_stream(file: File, kind: ArchiveKind) -> std::io::Result<Box<dyn Read>> {
+    let reader = BufReader::new(file);
+    Ok(match kind {
+        ArchiveKind::Zip | ArchiveKind::Tar => Box::new(reader),
+        ArchiveKind::TarGz => Box::new(GzDecoder::new(reader)),
+        ArchiveKind::TarXz => Box::new(XzDecoder::new(reader)),
+        ArchiveKind::TarZst => Box::new(zstd::Decoder::with_buffer(reader)?),
+    })
+}
+
+/// Convert a ZIP timestamp, which is stored in local time, to UTC
+pub fn zip_datetime_to_utc(time: zip::DateTime) -> Option<DateTime<Utc>> {
+    let naive =
+        NaiveDate::from_ymd_opt(time.year().into(), time.month().into(), time.day().into())?
+            .and_hms_opt(
+                time.hour().into(),
+                time.minute().into(),
+                time.second().into(),
+            )?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|datetime| datetime.with_timezone(&Utc))
+}
+
+/// Normalize a member name to `/`-separated components without `.` and empty parts
+///
+/// Returns `None` for names that are empty or contain `..`. Encoded names keep
+/// their encoding; escapes never contain separators or dots.
+pub fn normalize_member_name(raw: &str) -> Option<String> {
+    let (marker, raw) = if is_encoded(raw) {
+        raw.split_at(1)
+    } else {
+        ("", raw)
+    };
+    let mut components = Vec::new();
+    for component in raw.split(['/', '\\']) {
+        match component {
+            "" | "." => {}
+            ".." => return None,
+            other => components.push(other),
+        }
+    }
+    if components.is_empty() {
+        None
+    } else {
+        Some(format!("{}{}", marker, components.join("/")))
+    }
+}
+
+/// Reader that fails once more than `remaining` bytes have been read
+struct BoundedReader<R> {
+    inner: R,
+    remaining: u64,
+}
+
+impl<R: Read> Read for BoundedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.remaining = self.remaining.checked_sub(read as u64).ok_or_else(|| {
+            std::io::Error::other("Archive expands to more data than can be listed")
+        })?;
+        Ok(read)
+    }
+}
+
+fn too_many_members(max_members: usize) -> String {
+    format!("Archive has more than {} members", max_members)
+}
+
+fn read_zip_members(file: File, max_members: usize) -> Result<Vec<ArchiveMemberInfo>, String> {
+    let mut zip =
+        ZipArchive::new(BufReader::new(file)).map_err(|e| format!("Invalid ZIP archive: {}", e))?;
+    if zip.len() > max_members {
+        return Err(too_many_members(max_members));
+    }
+    let mut members = Vec::new();
+
+    for index in 0..zip.len() {
+        let member = zip
+            .by_index_raw(index)
+            .map_err(|e| format!("Failed to read ZIP member: {}", e))?;
+        let Some(name) = normalize_member_name(member.name()) else {
+            continue;
+        };
+        let modified_at = member
+            .last_modified()
+            .and_then(zip_datetime_to_utc)
+            .map(|datetime| datetime.to_rfc3339());
+        members.push(ArchiveMemberInfo {
+            name,
+            is_directory: member.is_dir(),
+            size: if member.is_dir() { 0 } else { member.size() },
+            modified_at,
+        });
+    }
+
+    Ok(members)
+}
+
+fn read_tar_members<R: Read>(
+    reader: R,
+    max_members: usize,
+) -> Result<Vec<ArchiveMemberInfo>, String> {
+    let mut archive = tar::Archive::new(reader);
+    let mut members = Vec::new();
+
+    let entries = archive
+        .entries()
+        .map_err(|e| format!("Invalid tar archive: {}", e))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read tar member: {}", e))?;
+        let entry_type = entry.header().entry_type();
+        if !(entry_type.is_file()
+            || entry_type.is_dir()
+            || entry_type.is_symlink()
+            || entry_type.is_hard_link())
+        {
+            continue;
+        }
+
+        let raw_name = encode_path(
+            &entry
+                .path()
+                .map_err(|e| format!("Invalid tar member name: {}", e))?,
+        );
+        let Some(name) = normalize_member_name(&raw_name) else {
+            continue;
+        };
+        if members.len() == max_members {
+            return Err(too_many_members(max_members));
+        }
+        let modified_at = entry
+            .header()
+            .mtime()
+            .ok()
+            .and_then(|secs| DateTime::<Utc>::from_timestamp(i64::try_from(secs).ok()?, 0))
+            .map(|datetime| datetime.to_rfc3339());
+        let is_directory = entry_type.is_dir();
+        members.push(ArchiveMemberInfo {
+            name,
+            is_directory,
+            size: if is_directory { 0 } else { entry.size() },
+            modified_at,
+        });
+    }
+
+    Ok(members)
+}
+
+/// Add the parent folders of a member that are not listed on their own
+fn add_implied_folders(members: &mut BTreeMap<String, ArchiveMemberInfo>, name: &str) {
+    let mut parent = name;
+    while let Some((folder, _)) = parent.rsplit_once('/') {
+        // The folder part of an encoded name may be valid Unicode on its own
+        let folder_name = encode_path(&decode_path(folder));
+        members
+            .entry(folder_name.clone())
+            .or_insert_with(|| ArchiveMemberInfo {
+                name: folder_name,
+                is_directory: true,
+                size: 0,
+                modified_at: None,
+            });
+        parent = folder;
+    }
+}
+
+fn virtual_entry(archive: &FileEntry, member: &ArchiveMemberInfo) -> FileEntry {
+    let name = member.name.as_str();
+    let parent_path = match name.rsplit_once('/') {
+        Some((folder, _)) => member_path(&archive.path, folder),
+        None => archive.path.clone(),
+    };
+    let file_name = decode_path(name)
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let extension = if member.is_directory {
+        String::new()
+    } else {
+        get_extension(&file_name)
+    };
+    let category = if member.is_directory {
+        FileCategory::Folder
+    } else {
+        classify_extension(&extension)
+    };
+
+    FileEntry::new(
+        member_path(&archive.path, name),
+        file_name,
+        member.is_directory,
+        member.size,
+        member
+            .modified_at
+            .clone()
+            .unwrap_or_else(|| archive.modified_at.clone()),
+        category,
+        extension,
+        archive.depth + name.split('/').count() as u32,
+        parent_path,
+    )
+    .with_virtual(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::tempdir;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    fn archive_entry(path: &Path) -> FileEntry {
+        FileEntry::new(
+            path.to_string_lossy().to_string(),
+            path.file_name().unwrap().to_string_lossy().to_string(),
+            false,
+            0,
+            "2025-01-01T00:00:00+00:00".to_string(),
+            FileCategory::Other,
+            "zip".to_string(),
+            1,
+            path.parent().unwrap().to_string_lossy().to_string(),
+        )
+    }
+
+    #[test]
+    fn test_normalize_member_name() {
+        assert_eq!(
+            normalize_member_name("./docs//a.pdf"),
+            Some("docs/a.pdf".to_string())
+        );
+        assert_eq!(
+            normalize_member_name("docs\\a.pdf"),
+            Some("docs/a.pdf".to_string())
+        );
+        assert_eq!(normalize_member_name("../etc/passwd"), None);
+        assert_eq!(normalize_member_name("/"), None);
+    }
+
+    #[test]
+    fn test_list_zip_members() {
+        let dir = tempdir().unwrap();
+        let archive_path = dir.path().join("backup.zip");
+        let mut zip = ZipWriter::new(File::create(&archive_path).unwrap());
+        zip.start_file("docs/a.pdf", SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(&[0u8; 1000]).unwrap();
+        zip.start_file("readme.txt", SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"hello").unwrap();
+        zip.finish().unwrap();
+
+        let archive = archive_entry(&archive_path);
+        let entries = list_archive_entries(&archive).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert!(entries.iter().all(|entry| entry.is_virtual));
+
+        let folder = &entries[0];
+        assert_eq!(folder.path, format!("{}!/docs", archive.path));
+        assert!(folder.is_directory);
+        assert_eq!(folder.depth, 2);
+        assert_eq!(folder.parent_path, archive.path);
+
+        let pdf = &entries[1];
+        assert_eq!(pdf.path, format!("{}!/docs/a.pdf", archive.path));
+        assert_eq!(pdf.name, "a.pdf");
+        assert_eq!(pdf.size, 1000);
+        assert_eq!(pdf.category, FileCategory::Document);
+        assert_eq!(pdf.depth, 3);
+        assert_eq!(pdf.parent_path, folder.path);
+    }
+
+    #[test]
+    fn test_list_tar_gz_members() {
+        let dir = tempdir().unwrap();
+        let archive_path = dir.path().join("photos.tar.gz");
+        let encoder = flate2::write::GzEncoder::new(
+            File::create(&archive_path).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut builder = tar::Builder::new(encoder);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(3);
+        header.set_mtime(1_700_000_000);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "2024/pic.jpg", &b"jpg"[..])
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let entries = list_archive_entries(&archive_entry(&archive_path)).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].is_directory);
+        assert_eq!(entries[1].name, "pic.jpg");
+        assert_eq!(entries[1].size, 3);
+        assert_eq!(entries[1].category, FileCategory::Image);
+        assert!(entries[1].modified_at.starts_with("2023-11-14"));
+    }
+
+    #[test]
+    fn test_invalid_archive() {
+        let dir = tempdir().unwrap();
+        let archive_path = dir.path().join("broken.zip");
+        std::fs::write(&archive_path, b"not a zip").unwrap();
+
+        assert!(list_archive_entries(&archive_entry(&archive_path)).is_err());
+    }
+
+    #[test]
+    fn test_read_tar_zst_members() {
+        let dir = tempdir().unwrap();
+        let archive_path = dir.path().join("logs.tar.zst");
+        let encoder = zstd::Encoder::new(File::create(&archive_path).unwrap(), 0).unwrap();
+        let mut builder = tar::Builder::new(encoder);
+        for name in ["b.log", "a.log", "b.log"] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(4);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, &b"line"[..])
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let members = read_archive_members(&archive_path).unwrap();
+        let names: Vec<&str> = members.iter().map(|member| member.name.as_str()).collect();
+        assert_eq!(names, ["a.log", "b.log"]);
+        assert_eq!(members[1].size, 4);
+    }
+
+    #[test]
+    fn test_listing_limits() {
+        let dir = tempdir().unwrap();
+        let archive_path = dir.path().join("many.tar.gz");
+        let encoder = flate2::write::GzEncoder::new(
+            File::create(&archive_path).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut builder = tar::Builder::new(encoder);
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(4);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, &b"text"[..])
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let too_many = read_members(&archive_path, 2, u64::MAX).unwrap_err();
+        assert!(too_many.contains("more than 2 members"));
+        assert!(read_members(&archive_path, 10, 1024).is_err());
+        assert_eq!(read_members(&archive_path, 3, u64::MAX).unwrap().len(), 3);
+
+        let mut archive = archive_entry(&archive_path);
+        archive.size = MAX_COMPRESSED_TAR_SIZE + 1;
+        assert!(list_archive_entries(&archive).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_unicode_tar_member() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = tempdir().unwrap();
+        let archive_path = dir.path().join("latin1.tar");
+        let mut builder = tar::Builder::new(File::create(&archive_path).unwrap());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(4);
+        header.set_cksum();
+        builder
+            .append_data(
+                &mut header,
+                OsStr::from_bytes(b"docs/caf\xe9.txt"),
+                &b"text"[..],
+            )
+            .unwrap();
+        builder.into_inner().unwrap();
+
+        let entries = list_archive_entries(&archive_entry(&archive_path)).unwrap();
+        assert_eq!(entries.len(), 2);
+        let file = entries.iter().find(|entry| !entry.is_directory).unwrap();
+        let mut expected = archive_path.into_os_string();
+        expected.push("!/docs");
+        assert_eq!(decode_path(&file.parent_path), Path::new(&expected));
+        expected.push(OsStr::from_bytes(b"/caf\xe9.txt"));
+        assert_eq!(decode_path(&file.path), Path::new(&expected));
+        assert_eq!(file.name, "caf\u{fffd}.txt");
+    }
+}
@@ -1,7 +1,15 @@
 //! Scanner module for directory traversal and file information extraction
 
+mod archive;
 mod file_info;
+mod ownership;
 mod walker;
 
+pub use archive::{
+    list_archive_entries, member_path, normalize_member_name, open_tar_stream,
+    read_archive_members, zip_datetime_to_utc, ArchiveKind, ArchiveMemberInfo,
+    ARCHIVE_PATH_SEPARATOR,
+};
 pub use file_info::classify_extension;
+pub use ownership::OwnerNames;
 pub use walker::{scan_directory, scan_directory_with_progress, ProgressCallback, ScanOptions};
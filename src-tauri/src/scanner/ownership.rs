@@ -0,0 +1,187 @@
+//! File ownership and permission bits with cached user and group names
+
+use std::collections::HashMap;
+use std::fs::Metadata;
+
+use crate::models::FileEntry;
+
+/// Answer of the user or group database for an id
+#[derive(Debug, Clone, PartialEq)]
+pub enum NameLookup {
+    Found(String),
+    /// The id has no entry
+    Missing,
+    /// The database could not be queried (e.g. NSS or LDAP errors)
+    Failed,
+}
+
+impl NameLookup {
+    fn name(&self) -> Option<String> {
+        match self {
+            NameLookup::Found(name) => Some(name.clone()),
+            NameLookup::Missing | NameLookup::Failed => None,
+        }
+    }
+}
+
+/// Resolves uids and gids to names, remembering every answer
+///
+/// A scan sees the same few owners over and over, so each id is looked up in
+/// the user and group databases only once.
+#[derive(Debug, Default)]
+pub struct OwnerNames {
+    users: HashMap<u32, NameLookup>,
+    groups: HashMap<u32, NameLookup>,
+}
+
+impl OwnerNames {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// User name for a uid
+    pub fn user_name(&mut self, uid: u32) -> NameLookup {
+        self.users
+            .entry(uid)
+            .or_insert_with(|| lookup_user(uid))
+            .clone()
+    }
+
+    /// Group name for a gid
+    pub fn group_name(&mut self, gid: u32) -> NameLookup {
+        self.groups
+            .entry(gid)
+            .or_insert_with(|| lookup_group(gid))
+            .clone()
+    }
+
+    /// Add owner, group and mode bits from the metadata to an entry
+    #[cfg(unix)]
+    pub fn annotate(&mut self, entry: FileEntry, metadata: &Metadata) -> FileEntry {
+        use std::os::unix::fs::MetadataExt;
+
+        let (uid, gid) = (metadata.uid(), metadata.gid());
+        let (owner, group) = (self.user_name(uid), self.group_name(gid));
+        entry
+            .with_owner(uid, owner.name())
+            .with_group(gid, group.name())
+            .with_names_unresolved(owner == NameLookup::Failed || group == NameLookup::Failed)
+            .with_mode(metadata.mode() & 0o7777)
+    }
+
+    /// Ownership is a Unix concept; entries are left unchanged elsewhere
+    #[cfg(not(unix))]
+    pub fn annotate(&mut self, entry: FileEntry, _metadata: &Metadata) -> FileEntry {
+        entry
+    }
+}
+
+/// Call a reentrant `get*_r` database lookup, growing the buffer on ERANGE
+///
+/// Besides a null result, POSIX allows ENOENT and ESRCH for ids without an
+/// entry. Any other error (or a name longer than 1 MiB) is a failed lookup.
+#[cfg(unix)]
+fn lookup_with_buffer(
+    mut lookup: impl FnMut(&mut [libc::c_char]) -> Result<Option<String>, i32>,
+) -> NameLookup {
+    let mut buffer = vec![0; 1024];
+    loop {
+        match lookup(&mut buffer) {
+            Ok(Some(name)) => return NameLookup::Found(name),
+            Ok(None) | Err(libc::ENOENT | libc::ESRCH) => return NameLookup::Missing,
+            Err(libc::ERANGE) if buffer.len() < 1 << 20 => {
+                let len = buffer.len() * 2;
+                buffer.resize(len, 0);
+            }
+            Err(_) => return NameLookup::Failed,
+        }
+    }
+}
+
+#[cfg(unix)]
+fn lookup_user(uid: u32) -> NameLookup {
+    use std::ffi::CStr;
+
+    lookup_with_buffer(|buffer| {
+        // SAFETY: passwd is plain data; getpwuid_r only writes into it and into
+        // the buffer, whose length is passed along
+        let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+        let mut result = std::ptr::null_mut();
+        let status = unsafe {
+            libc::getpwuid_r(
+                uid,
+                std::ptr::addr_of_mut!(passwd),
+                buffer.as_mut_ptr(),
+                buffer.len(),
+                std::ptr::addr_of_mut!(result),
+            )
+        };
+        if status != 0 {
+            return Err(status);
+        }
+        if result.is_null() {
+            return Ok(None);
+        }
+        // SAFETY: on success pw_name points to a NUL-terminated string in buffer
+        let name = unsafe { CStr::from_ptr(passwd.pw_name) };
+        Ok(Some(name.to_string_lossy().into_owned()))
+    })
+}
+
+#[cfg(unix)]
+fn lookup_group(gid: u32) -> NameLookup {
+    use std::ffi::CStr;
+
+    lookup_with_buffer(|buffer| {
+        // SAFETY: group is plain data; getgrgid_r only writes into it and into
+        // the buffer, whose length is passed along
+        let mut group: libc::group = unsafe { std::mem::zeroed() };
+        let mut result = std::ptr::null_mut();
+        let status = unsafe {
+            libc::getgrgid_r(
+                gid,
+                std::ptr::addr_of_mut!(group),
+                buffer.as_mut_ptr(),
+                buffer.len(),
+                std::ptr::addr_of_mut!(result),
+            )
+        };
+        if status != 0 {
+            return Err(status);
+        }
+        if result.is_null() {
+            return Ok(None);
+        }
+        // SAFETY: on success gr_name points to a NUL-terminated string in buffer
+        let name = unsafe { CStr::from_ptr(group.gr_name) };
+        Ok(Some(name.to_string_lossy().into_owned()))
+    })
+}
+
+#[cfg(not(unix))]
+fn lookup_user(_uid: u32) -> NameLookup {
+    NameLookup::Missing
+}
+
+#[cfg(not(unix))]
+fn lookup_group(_gid: u32) -> NameLookup {
+    NameLookup::Missing
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_root_names() {
+        let mut names = OwnerNames::new();
+        assert_eq!(names.user_name(0), NameLookup::Found("root".to_string()));
+        assert!(names.group_name(0).name().is_some());
+        assert_eq!(names.users.len(), 1);
+
+        // Cached misses stay misses
+        assert_eq!(names.user_name(u32::MAX - 7), NameLookup::Missing);
+        assert_eq!(names.user_name(u32::MAX - 7), NameLookup::Missing);
+        assert_eq!(names.users.len(), 2);
+    }
+}
@@ -1,15 +1,18 @@
 //! Directory traversal using walkdir crate
 
 use std::path::Path;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 
 use chrono::{DateTime, Utc};
 use walkdir::WalkDir;
 
 use crate::models::{
-    FailedEntry, FailureReason, FileCategory, FileEntry, ScanProgress, ScanResult, ScanStats,
+    decode_path, encode_path, FailedEntry, FailureReason, FileCategory, FileEntry, ScanProgress,
+    ScanResult, ScanStats,
 };
+use crate::scanner::archive::{list_archive_entries, ArchiveKind};
 use crate::scanner::file_info::{classify_extension, get_extension};
+use crate::scanner::ownership::OwnerNames;
 
 /// Progress callback type for reporting scan progress
 pub type ProgressCallback = Box<dyn Fn(ScanProgress) + Send + Sync>;
@@ -22,6 +25,15 @@ pub struct ScanOptions {
 
     /// Follow symbolic links
     pub follow_links: bool,
+
+    /// List the members of ZIP and tar archives as virtual entries
+    pub expand_archives: bool,
+
+    /// Lower bounds of the file-size buckets in the stats (None = powers of two)
+    pub size_buckets: Option<Vec<u64>>,
+
+    /// Record owner, group and permission bits of every entry (Unix only)
+    pub include_ownership: bool,
 }
 
 impl ScanOptions {
@@ -38,6 +50,29 @@ impl ScanOptions {
         self.follow_links = follow;
         self
     }
+
+    pub fn with_expand_archives(mut self, expand: bool) -> Self {
+        self.expand_archives = expand;
+        self
+    }
+
+    pub fn with_size_buckets(mut self, bounds: Vec<u64>) -> Self {
+        self.size_buckets = Some(bounds);
+        self
+    }
+
+    pub fn with_ownership(mut self, include: bool) -> Self {
+        self.include_ownership = include;
+        self
+    }
+}
+
+/// Empty stats with the size buckets requested in the options
+fn empty_stats(options: &ScanOptions) -> ScanStats {
+    match &options.size_buckets {
+        Some(bounds) => ScanStats::new().with_size_buckets(bounds),
+        None => ScanStats::new(),
+    }
 }
 
 /// Scan a directory and return all file entries
@@ -51,7 +86,7 @@ pub fn scan_directory_with_progress(
     options: &ScanOptions,
     progress_callback: Option<ProgressCallback>,
 ) -> Result<ScanResult, String> {
-    let path = Path::new(root_path);
+    let path = decode_path(root_path);
 
     // Validate the path exists and is a directory
     if !path.exists() {
@@ -63,18 +98,18 @@ pub fn scan_directory_with_progress(
     }
 
     let start_time = Instant::now();
-    let root_path_str = path
+    let root = path
         .canonicalize()
-        .map_err(|e| format!("Failed to canonicalize path: {}", e))?
-        .to_string_lossy()
-        .to_string();
+        .map_err(|e| format!("Failed to canonicalize path: {}", e))?;
+    let root_path_str = encode_path(&root);
 
     let mut entries: Vec<FileEntry> = Vec::new();
-    let mut stats = ScanStats::new();
+    let mut stats = empty_stats(options);
     let mut failed_entries: Vec<FailedEntry> = Vec::new();
+    let mut owner_names = options.include_ownership.then(OwnerNames::new);
 
     // Configure walkdir
-    let mut walker = WalkDir::new(path).follow_links(options.follow_links);
+    let mut walker = WalkDir::new(&path).follow_links(options.follow_links);
 
     if let Some(max_depth) = options.max_depth {
         walker = walker.max_depth(max_depth);
@@ -89,7 +124,7 @@ pub fn scan_directory_with_progress(
     for result in walker {
         match result {
             Ok(dir_entry) => {
-                let entry_path = dir_entry.path();
+                let entry_path = dir_entry.path().to_path_buf();
 
                 // Skip the root directory itself
                 if entry_path == path {
@@ -99,23 +134,46 @@ pub fn scan_directory_with_progress(
                 // Emit progress update at regular intervals
                 if let Some(ref callback) = progress_callback {
                     if last_progress_update.elapsed() >= progress_interval {
-                        callback(ScanProgress::new(
-                            scanned_count,
-                            entry_path.to_string_lossy().to_string(),
-                        ));
+                        callback(ScanProgress::new(scanned_count, encode_path(&entry_path)));
                         last_progress_update = Instant::now();
                     }
                 }
 
-                match create_file_entry(dir_entry, &root_path_str) {
+                match create_file_entry(dir_entry, &root, owner_names.as_mut()) {
                     Ok(file_entry) => {
+                        let members = archive_members(&file_entry, options, || {
+                            // Listing a large archive takes a while, so show which one
+                            if let Some(ref callback) = progress_callback {
+                                callback(ScanProgress::new(scanned_count, file_entry.path.clone()));
+                                last_progress_update = Instant::now();
+                            }
+                        });
+
                         stats.add_entry(&file_entry);
                         entries.push(file_entry);
                         scanned_count += 1;
+
+                        match members {
+                            Some(Ok(members)) => {
+                                for member in members {
+                                    stats.add_entry(&member);
+                                    entries.push(member);
+                                    scanned_count += 1;
+                                }
+                            }
+                            Some(Err(e)) => {
+                                failed_entries.push(FailedEntry::new(
+                                    encode_path(&entry_path),
+                                    FailureReason::Unknown,
+                                    e,
+                                ));
+                            }
+                            None => {}
+                        }
                     }
                     Err(e) => {
                         failed_entries.push(FailedEntry::new(
-                            entry_path.to_string_lossy().to_string(),
+                            encode_path(&entry_path),
                             FailureReason::Unknown,
                             e,
                         ));
@@ -125,18 +183,14 @@ pub fn scan_directory_with_progress(
             Err(e) => {
                 let path_str = e
                     .path()
-                    .map(|p| p.to_string_lossy().to_string())
+                    .map(encode_path)
                     .unwrap_or_else(|| "unknown".to_string());
 
-                let reason = if e.io_error().map(|io| io.kind())
-                    == Some(std::io::ErrorKind::PermissionDenied)
-                {
-                    FailureReason::PermissionDenied
-                } else {
-                    FailureReason::Unknown
-                };
-
-                failed_entries.push(FailedEntry::new(path_str, reason, e.to_string()));
+                failed_entries.push(FailedEntry::new(
+                    path_str,
+                    failure_reason(&e),
+                    e.to_string(),
+                ));
             }
         }
     }
@@ -154,14 +208,61 @@ pub fn scan_directory_with_progress(
     ))
 }
 
+/// List the members of an archive entry if archive expansion is enabled
+///
+/// Members deeper than `max_depth` are left out. `on_expand` is called before
+/// an archive is read.
+fn archive_members(
+    file_entry: &FileEntry,
+    options: &ScanOptions,
+    on_expand: impl FnOnce(),
+) -> Option<Result<Vec<FileEntry>, String>> {
+    if !options.expand_archives
+        || file_entry.is_directory
+        || ArchiveKind::from_path(&decode_path(&file_entry.path)).is_none()
+    {
+        return None;
+    }
+    on_expand();
+
+    let max_depth = options.max_depth.unwrap_or(usize::MAX);
+    Some(list_archive_entries(file_entry).map(|members| {
+        members
+            .into_iter()
+            .filter(|member| member.depth as usize <= max_depth)
+            .collect()
+    }))
+}
+
+/// Classify an error reported while walking the directory tree
+fn failure_reason(error: &walkdir::Error) -> FailureReason {
+    if error.loop_ancestor().is_some() {
+        FailureReason::SymlinkLoop
+    } else if error.io_error().map(|io| io.kind()) == Some(std::io::ErrorKind::PermissionDenied) {
+        FailureReason::PermissionDenied
+    } else {
+        FailureReason::Unknown
+    }
+}
+
+/// Format a file system timestamp as RFC 3339
+fn format_time(time: SystemTime) -> String {
+    let datetime: DateTime<Utc> = time.into();
+    datetime.to_rfc3339()
+}
+
 /// Create a FileEntry from a walkdir DirEntry
-fn create_file_entry(dir_entry: walkdir::DirEntry, root_path: &str) -> Result<FileEntry, String> {
+fn create_file_entry(
+    dir_entry: walkdir::DirEntry,
+    root: &Path,
+    owner_names: Option<&mut OwnerNames>,
+) -> Result<FileEntry, String> {
     let path = dir_entry.path();
     let metadata = dir_entry
         .metadata()
         .map_err(|e| format!("Failed to get metadata: {}", e))?;
 
-    let path_str = path.to_string_lossy().to_string();
+    let path_str = encode_path(path);
     let name = path
         .file_name()
         .map(|n| n.to_string_lossy().to_string())
@@ -180,6 +281,10 @@ fn create_file_entry(dir_entry: walkdir::DirEntry, root_path: &str) -> Result<Fi
         })
         .unwrap_or_else(|| Utc::now().to_rfc3339());
 
+    // Access and birth times are not available on every platform and file system
+    let accessed_at = metadata.accessed().ok().map(format_time);
+    let created_at = metadata.created().ok().map(format_time);
+
     // Get extension and category
     let extension = if is_directory {
         String::new()
@@ -195,17 +300,14 @@ fn create_file_entry(dir_entry: walkdir::DirEntry, root_path: &str) -> Result<Fi
 
     // Calculate depth relative to root
     let depth = path
-        .strip_prefix(root_path)
+        .strip_prefix(root)
         .map(|rel| rel.components().count() as u32)
         .unwrap_or(0);
 
     // Get parent path
-    let parent_path = path
-        .parent()
-        .map(|p| p.to_string_lossy().to_string())
-        .unwrap_or_default();
+    let parent_path = path.parent().map(encode_path).unwrap_or_default();
 
-    Ok(FileEntry::new(
+    let entry = FileEntry::new(
         path_str,
         name,
         is_directory,
@@ -215,7 +317,22 @@ fn create_file_entry(dir_entry: walkdir::DirEntry, root_path: &str) -> Result<Fi
         extension,
         depth,
         parent_path,
-    ))
+    )
+    .with_times(accessed_at, created_at);
+
+    let entry = match owner_names {
+        Some(names) => names.annotate(entry, &metadata),
+        None => entry,
+    };
+
+    // Record where symbolic links point, whether or not they are followed
+    if dir_entry.path_is_symlink() {
+        let target = std::fs::read_link(path)
+            .ok()
+            .map(|target| target.to_string_lossy().to_string());
+        return Ok(entry.with_symlink(target));
+    }
+    Ok(entry)
 }
 
 #[cfg(test)]
@@ -277,6 +394,10 @@ mod tests {
         assert_eq!(scan_result.stats.total_folders, 1);
         assert_eq!(scan_result.stats.document_count, 1);
         assert_eq!(scan_result.stats.image_count, 1);
+        assert!(scan_result
+            .entries
+            .iter()
+            .all(|entry| entry.accessed_at.is_some()));
     }
 
     #[test]
@@ -320,4 +441,101 @@ mod tests {
         assert_eq!(scan_result.stats.audio_count, 1);
         assert_eq!(scan_result.stats.other_count, 1);
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_symlinks() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("folder")).unwrap();
+        std::os::unix::fs::symlink("..", dir.path().join("folder/up")).unwrap();
+        std::os::unix::fs::symlink("missing.txt", dir.path().join("dangling")).unwrap();
+
+        let root = dir.path().to_str().unwrap();
+        let scan_result = scan_directory(root, &ScanOptions::new()).unwrap();
+        let dangling = scan_result
+            .entries
+            .iter()
+            .find(|entry| entry.name == "dangling")
+            .unwrap();
+        assert!(dangling.is_symlink);
+        assert_eq!(dangling.link_target.as_deref(), Some("missing.txt"));
+
+        let options = ScanOptions::new().with_follow_links(true);
+        let scan_result = scan_directory(root, &options).unwrap();
+        assert!(scan_result
+            .failed_entries
+            .iter()
+            .any(|failed| failed.reason == FailureReason::SymlinkLoop));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_ownership() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("shared.txt");
+        File::create(&file_path)
+            .unwrap()
+            .write_all(b"data")
+            .unwrap();
+        fs::set_permissions(&file_path, fs::Permissions::from_mode(0o640)).unwrap();
+
+        let root = dir.path().to_str().unwrap();
+        let scan_result = scan_directory(root, &ScanOptions::new()).unwrap();
+        assert_eq!(scan_result.entries[0].uid, None);
+        assert!(scan_result.stats.owners.is_empty());
+
+        let options = ScanOptions::new().with_ownership(true);
+        let scan_result = scan_directory(root, &options).unwrap();
+        let entry = &scan_result.entries[0];
+        // SAFETY: getuid has no preconditions
+        assert_eq!(entry.uid, Some(unsafe { libc::getuid() }));
+        assert_eq!(entry.mode, Some(0o640));
+        let owner = entry.owner_key().unwrap();
+        assert_eq!(scan_result.stats.owners[&owner].total_size, 4);
+    }
+
+    #[test]
+    fn test_scan_expand_archives() {
+        let dir = tempdir().unwrap();
+
+        let mut zip = zip::ZipWriter::new(File::create(dir.path().join("backup.zip")).unwrap());
+        zip.start_file("docs/a.pdf", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(&[0u8; 100]).unwrap();
+        zip.finish().unwrap();
+        fs::write(dir.path().join("broken.zip"), b"not a zip").unwrap();
+
+        let root = dir.path().to_str().unwrap();
+        let plain = scan_directory(root, &ScanOptions::new()).unwrap();
+        assert_eq!(plain.entries.len(), 2);
+        assert_eq!(plain.stats.virtual_files, 0);
+
+        let options = ScanOptions::new().with_expand_archives(true);
+        let scan_result = scan_directory(root, &options).unwrap();
+        assert_eq!(scan_result.entries.len(), 4);
+        assert_eq!(scan_result.stats.total_files, 3);
+        assert_eq!(scan_result.stats.total_folders, 1);
+        assert_eq!(scan_result.stats.document_count, 1);
+        assert_eq!(scan_result.stats.virtual_files, 1);
+        assert_eq!(scan_result.stats.virtual_size, 100);
+        assert_eq!(scan_result.failed_entries.len(), 1);
+
+        let pdf = scan_result
+            .entries
+            .iter()
+            .find(|entry| entry.name == "a.pdf")
+            .unwrap();
+        assert!(pdf.is_virtual);
+        assert!(pdf.path.ends_with("backup.zip!/docs/a.pdf"));
+        assert_eq!(pdf.depth, 3);
+
+        let options = ScanOptions::new()
+            .with_expand_archives(true)
+            .with_max_depth(2);
+        let scan_result = scan_directory(root, &options).unwrap();
+        assert!(scan_result.entries.iter().all(|entry| entry.depth <= 2));
+        assert!(scan_result.entries.iter().any(|entry| entry.is_virtual));
+    }
 }